@@ -1,8 +1,12 @@
 //! Types for converting ETH-like types to Substrate types and vice versa
 
+use crate::types::ChainConfig;
 use alloy_primitives::Address;
 use sp_core::{blake2_128, blake2_256, twox_128, twox_256, twox_64};
-use subxt::{metadata::types::StorageHasher, utils::AccountId32};
+use std::collections::BTreeMap;
+use subxt::metadata::types::{StorageEntryType, StorageHasher};
+use subxt::storage::Storage;
+use subxt::{utils::AccountId32, Metadata, OnlineClient};
 
 /// Reserved address for generic SCALE-encoded RuntimeCall dispatch
 /// When EthereumTransaction.to == GENERIC_CALL_ADDRESS, the transaction.data
@@ -12,7 +16,15 @@ pub const GENERIC_CALL_ADDRESS: Address = Address::ZERO;
 use subeth_primitives::AddressMapping as PrimitiveAddressMapping;
 pub use subeth_primitives::PalletContractMapping;
 
+/// Pallet keeping the registry of addresses bound to accounts
+const REGISTRY_PALLET: &str = "EvmAdapter";
+
 /// Address mapping logic
+///
+/// Hashing an address can't be reversed, so `to_address(to_ss58(address)) != address`. The
+/// adapter pallet keeps a registry of the addresses bound to accounts, which [`Self::account_of`]
+/// and [`Self::address_of`] query first, falling back to hashing and truncation for addresses and
+/// accounts that aren't bound, or runtimes without the registry.
 pub(crate) struct AddressMapping;
 
 impl AddressMapping {
@@ -27,6 +39,108 @@ impl AddressMapping {
         let inner: &[u8; 32] = account_id.as_ref();
         PrimitiveAddressMapping::to_address(inner)
     }
+
+    /// Account bound to the address in the registry, or the hashed address
+    pub async fn account_of(
+        storage: &Storage<ChainConfig, OnlineClient<ChainConfig>>,
+        metadata: &Metadata,
+        address: Address,
+    ) -> Result<AccountId32, subxt::Error> {
+        let bound =
+            Self::registry_entry(storage, metadata, "Accounts", &[address.as_slice()]).await?;
+
+        Ok(bound
+            .and_then(|account| <[u8; 32]>::try_from(account.as_slice()).ok())
+            .map(AccountId32::from)
+            .unwrap_or_else(|| Self::to_ss58(address)))
+    }
+
+    /// Address bound to the account in the registry, or the truncated account
+    pub async fn address_of(
+        storage: &Storage<ChainConfig, OnlineClient<ChainConfig>>,
+        metadata: &Metadata,
+        account: &AccountId32,
+    ) -> Result<Address, subxt::Error> {
+        let bound =
+            Self::registry_entry(storage, metadata, "Addresses", &[account.as_ref()]).await?;
+
+        Ok(bound
+            .filter(|address| address.len() == 20)
+            .map(|address| Address::from_slice(&address))
+            .unwrap_or_else(|| Self::to_address(account.clone())))
+    }
+
+    /// Addresses of the accounts, see [`Self::address_of`]
+    pub async fn addresses_of(
+        storage: &Storage<ChainConfig, OnlineClient<ChainConfig>>,
+        metadata: &Metadata,
+        accounts: impl IntoIterator<Item = AccountId32>,
+    ) -> Result<AddressBook, subxt::Error> {
+        let mut book = AddressBook::default();
+        for account in accounts {
+            if !book.contains(&account) {
+                let address = Self::address_of(storage, metadata, &account).await?;
+                book.insert(account, address);
+            }
+        }
+
+        Ok(book)
+    }
+
+    /// Raw value of an entry of the adapter pallet's storage, `None` if it isn't set or the
+    /// runtime doesn't have the item
+    ///
+    /// `keys` are the SCALE-encoded keys of the map, one per hasher.
+    pub(crate) async fn registry_entry(
+        storage: &Storage<ChainConfig, OnlineClient<ChainConfig>>,
+        metadata: &Metadata,
+        name: &str,
+        keys: &[&[u8]],
+    ) -> Result<Option<Vec<u8>>, subxt::Error> {
+        let Some(entry) = metadata
+            .pallet_by_name(REGISTRY_PALLET)
+            .and_then(|pallet| pallet.storage())
+            .and_then(|storage| storage.entry_by_name(name))
+        else {
+            return Ok(None);
+        };
+        let StorageEntryType::Map { hashers, .. } = entry.entry_type() else {
+            return Ok(None);
+        };
+        if hashers.len() != keys.len() {
+            return Ok(None);
+        }
+
+        let mut storage_key = twox_128(REGISTRY_PALLET.as_bytes()).to_vec();
+        storage_key.extend(twox_128(name.as_bytes()));
+        for (key, hasher) in keys.iter().zip(hashers) {
+            storage_key.extend(hash_key(key, hasher));
+        }
+
+        storage.fetch_raw(storage_key).await
+    }
+}
+
+/// Addresses of accounts resolved with [`AddressMapping::addresses_of`]
+#[derive(Clone, Debug, Default)]
+pub struct AddressBook(BTreeMap<AccountId32, Address>);
+
+impl AddressBook {
+    pub fn insert(&mut self, account: AccountId32, address: Address) {
+        self.0.insert(account, address);
+    }
+
+    pub fn contains(&self, account: &AccountId32) -> bool {
+        self.0.contains_key(account)
+    }
+
+    /// Address of the account, truncated if it wasn't resolved
+    pub fn address(&self, account: &AccountId32) -> Address {
+        self.0
+            .get(account)
+            .copied()
+            .unwrap_or_else(|| AddressMapping::to_address(account.clone()))
+    }
 }
 
 /// Pallet storage read structure
@@ -67,7 +67,7 @@ impl BlockCache {
             }
 
             // Insert block hash to number mapping
-            inner.number_to_hash.insert(number.into(), hash.0.into());
+            inner.number_to_hash.insert(number, hash.0.into());
 
             // Insert the full block
             inner.hash_to_block.insert(hash.0.into(), block.clone());
@@ -123,7 +123,7 @@ mod tests {
             header: Header {
                 hash: B256::from(hash),
                 inner: ConsensusHeader {
-                    number: number.into(),
+                    number,
                     ..Default::default()
                 },
                 ..Default::default()
@@ -1,11 +1,22 @@
 //! Command line interface for the Subeth RPC adapter.
 
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use jsonrpsee::RpcModule;
 use sc_service::config::RpcConfiguration;
 
-use crate::{sub_client::SubLightClient, traits::EthApiServer};
+use crate::{
+    filter::{
+        FilterLimits, FilterManager, DEFAULT_FILTER_TIMEOUT, DEFAULT_MAX_BLOCK_RANGE,
+        DEFAULT_MAX_LOGS,
+    },
+    index::{TxIndex, DEFAULT_RETENTION},
+    properties::PropertiesOverride,
+    relayer::{KeyScheme, Relayer, RelayerKey, RELAYER_KEY_ENV},
+    sub_client::{BlockStream, SubLightClient},
+    traits::{EthApiServer, EthFilterApiServer, EthPubSubApiServer, SubEthApiServer},
+    units::BalanceUnit,
+};
 use std::{
     cell::RefCell,
     sync::atomic::{AtomicUsize, Ordering},
@@ -28,6 +39,46 @@ pub struct Opts {
     /// Maximum retries for light client initialization
     #[clap(long, default_value = "3")]
     max_retries: u32,
+    /// Directory for persistent adapter data (transaction index). Kept in memory if not set
+    #[clap(long)]
+    data_dir: Option<PathBuf>,
+    /// Number of recent blocks kept in the transaction index, 0 keeps everything
+    #[clap(long, default_value_t = DEFAULT_RETENTION)]
+    index_retention: u64,
+    /// Maximum number of blocks scanned by a single `eth_getLogs` or filter poll
+    #[clap(long, default_value_t = DEFAULT_MAX_BLOCK_RANGE)]
+    max_logs_block_range: u64,
+    /// Maximum number of logs returned by a single `eth_getLogs` or filter poll
+    #[clap(long, default_value_t = DEFAULT_MAX_LOGS)]
+    max_logs: usize,
+    /// Seconds after which a filter that isn't polled is removed
+    #[clap(long, default_value_t = DEFAULT_FILTER_TIMEOUT.as_secs())]
+    filter_timeout: u64,
+    /// Decimals of the native token, overrides the chain's `tokenDecimals`
+    #[clap(long)]
+    token_decimals: Option<u32>,
+    /// Symbol of the native token, overrides the chain's `tokenSymbol`
+    #[clap(long)]
+    token_symbol: Option<String>,
+    /// SS58 address prefix, overrides the chain's `ss58Format`
+    #[clap(long)]
+    ss58_format: Option<u16>,
+    /// Unit of balances, transaction values and fees: `wei` (scaled to 18 decimals) or `planck`
+    #[clap(long, value_enum, default_value_t = BalanceUnit::Wei)]
+    balance_unit: BalanceUnit,
+    /// Blocks followed by subscriptions
+    #[clap(long, value_enum, default_value_t = BlockStream::Finalized)]
+    subscription_blocks: BlockStream,
+    /// Secret URI of the relayer key signing the extrinsics of Ethereum transactions, e.g.
+    /// `//Alice`, a mnemonic or a hex seed
+    #[clap(long, env = RELAYER_KEY_ENV, hide_env_values = true, conflicts_with = "relayer_key_file")]
+    relayer_key: Option<String>,
+    /// File holding the relayer's secret URI
+    #[clap(long)]
+    relayer_key_file: Option<PathBuf>,
+    /// Signature scheme of the relayer key
+    #[clap(long, value_enum, default_value_t = KeyScheme::Sr25519)]
+    relayer_scheme: KeyScheme,
     /// Rpc params
     #[allow(missing_docs)]
     #[clap(flatten)]
@@ -63,6 +114,7 @@ fn tokio_runtime() -> Result<tokio::runtime::Runtime, tokio::io::Error> {
         .build()
 }
 
+#[allow(clippy::result_large_err)]
 pub async fn run(opts: Opts) -> anyhow::Result<()> {
     let chain_id = opts.chain_id;
 
@@ -83,6 +135,42 @@ pub async fn run(opts: Opts) -> anyhow::Result<()> {
         SubLightClient::from_url(DEFAULT_URL, chain_id, None).await?
     };
 
+    let client = client
+        .with_properties_override(PropertiesOverride {
+            decimals: opts.token_decimals,
+            symbol: opts.token_symbol,
+            ss58_format: opts.ss58_format,
+        })
+        .with_balance_unit(opts.balance_unit);
+
+    let relayer_key = match (opts.relayer_key, opts.relayer_key_file) {
+        (Some(suri), _) => Some(
+            RelayerKey::from_suri(opts.relayer_scheme, &suri)
+                .map_err(|e| anyhow::anyhow!("invalid relayer key: {:?}", e))?,
+        ),
+        (None, Some(path)) => Some(RelayerKey::from_file(opts.relayer_scheme, &path)?),
+        (None, None) => None,
+    };
+    let client = match relayer_key {
+        Some(key) => {
+            let relayer = Relayer::new(key);
+            log::info!("Relaying transactions from {}", relayer.account_id());
+            client.with_relayer(relayer)
+        }
+        None => {
+            log::info!("No relayer key, submitting Ethereum transactions unsigned");
+            client
+        }
+    };
+
+    let client = match opts.data_dir {
+        Some(data_dir) => {
+            log::info!("Using data directory: {}", data_dir.display());
+            client.with_tx_index(TxIndex::open(&data_dir, opts.index_retention)?)
+        }
+        None => client.with_tx_index(TxIndex::in_memory(opts.index_retention)?),
+    };
+
     log::info!("Connected to chain: {}", client.chain_id());
 
     let tokio_runtime = tokio_runtime()?;
@@ -90,13 +178,64 @@ pub async fn run(opts: Opts) -> anyhow::Result<()> {
 
     let mut task_manager = sc_service::TaskManager::new(tokio_handle.clone(), None)?;
 
+    let indexer = client.clone();
+    task_manager.spawn_handle().spawn(
+        "subeth-tx-indexer",
+        Some("subeth"),
+        Box::pin(async move {
+            if let Err(e) = indexer.run_indexer().await {
+                log::error!("Transaction indexer stopped: {:?}", e);
+            }
+        }),
+    );
+
+    let runtime_updates = client.clone();
+    task_manager.spawn_handle().spawn(
+        "subeth-runtime-updates",
+        Some("subeth"),
+        Box::pin(async move {
+            if let Err(e) = runtime_updates.run_runtime_updates().await {
+                log::error!("Runtime updates stopped: {:?}", e);
+            }
+        }),
+    );
+
+    let filters = FilterManager::new(
+        client.clone(),
+        FilterLimits {
+            max_block_range: opts.max_logs_block_range,
+            max_logs: opts.max_logs,
+            timeout: std::time::Duration::from_secs(opts.filter_timeout),
+        },
+    );
+    let filters_task = filters.clone();
+    task_manager.spawn_handle().spawn(
+        "subeth-filters",
+        Some("subeth"),
+        Box::pin(async move {
+            if let Err(e) = filters_task.run().await {
+                log::error!("Filter block stream stopped: {:?}", e);
+            }
+        }),
+    );
+
     let mut gen_rpc_module = RpcModule::new(());
-    let rpc_module =
+    let adapter =
         crate::server::EthAdapter::new(client, vec![], Arc::new(task_manager.spawn_handle()))
-            .into_rpc();
+            .with_filters(filters)
+            .with_block_stream(opts.subscription_blocks);
 
     gen_rpc_module
-        .merge(rpc_module)
+        .merge(EthApiServer::into_rpc(adapter.clone()))
+        .map_err(|e| anyhow::anyhow!(e))?;
+    gen_rpc_module
+        .merge(EthFilterApiServer::into_rpc(adapter.clone()))
+        .map_err(|e| anyhow::anyhow!(e))?;
+    gen_rpc_module
+        .merge(SubEthApiServer::into_rpc(adapter.clone()))
+        .map_err(|e| anyhow::anyhow!(e))?;
+    gen_rpc_module
+        .merge(EthPubSubApiServer::into_rpc(adapter))
         .map_err(|e| anyhow::anyhow!(e))?;
 
     let rpc_port = opts.rpc_params.rpc_port.unwrap_or(8545);
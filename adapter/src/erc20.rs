@@ -0,0 +1,351 @@
+//! ERC-20 facades of the native token and the assets
+//!
+//! The `Balances` pallet address, and the address of each `pallet_assets` asset
+//! ([`PalletContractMapping::asset_address`]), behave like ERC-20 token contracts:
+//!
+//! - the views (`name`, `symbol`, `decimals`, `totalSupply`, `balanceOf` and `allowance`) are
+//!   answered from the chain state by `eth_call`
+//! - `transfer`, `approve` and `transferFrom` transactions are dispatched by the adapter pallet,
+//!   which keeps the allowances of the native token, and `pallet_assets` keeps the approvals of
+//!   the assets
+//! - `Transfer` and `Approval` logs are synthesized from the `Balances.Transfer` and
+//!   `EvmAdapter.Approval` events, and from the `Assets.Transferred`, `Assets.TransferredApproved`
+//!   and `Assets.ApprovedTransfer` events
+//!
+//! Amounts are in the token's smallest unit, with the token's decimals, whatever the balance unit
+//! of the adapter: the pallets transfer them as they are.
+
+use crate::adapter::{AddressBook, PalletContractMapping};
+use crate::events::{abi_encode, AbiValue};
+use alloy_primitives::{keccak256, Address, Log, U256};
+use std::collections::BTreeMap;
+use subeth_primitives::erc20;
+use subxt::dynamic::Value;
+use subxt::ext::scale_value::{Composite, ValueDef};
+use subxt::utils::AccountId32;
+
+/// Pallet whose address is the native token contract
+pub const TOKEN_PALLET: &str = "Balances";
+/// Pallet of the assets whose addresses are token contracts
+pub const ASSETS_PALLET: &str = "Assets";
+
+/// Token of an ERC-20 facade
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Token {
+    /// The native token, at the `Balances` address
+    Native,
+    /// A `pallet_assets` asset, at the address of its id
+    Asset(u128),
+}
+
+impl Token {
+    /// Token of the contract address, `None` for other addresses
+    pub fn from_address(address: Address) -> Option<Self> {
+        if address == PalletContractMapping::contract_address(TOKEN_PALLET) {
+            return Some(Self::Native);
+        }
+
+        PalletContractMapping::asset_id(address).map(Self::Asset)
+    }
+
+    /// Contract address of the token
+    pub fn address(&self) -> Address {
+        match self {
+            Self::Native => PalletContractMapping::contract_address(TOKEN_PALLET),
+            Self::Asset(asset_id) => PalletContractMapping::asset_address(*asset_id),
+        }
+    }
+}
+
+/// View of the token, decoded from the call data of an `eth_call`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Erc20View {
+    Name,
+    Symbol,
+    Decimals,
+    TotalSupply,
+    BalanceOf(Address),
+    Allowance { owner: Address, spender: Address },
+}
+
+impl Erc20View {
+    /// Decode the call data of a view, `None` for other calls
+    pub fn decode(input: &[u8]) -> Option<Self> {
+        let selector = <[u8; 4]>::try_from(input.get(..4)?).ok()?;
+        let address = |index: usize| {
+            let start = 4 + 32 * index;
+            input.get(start + 12..start + 32).map(Address::from_slice)
+        };
+
+        Some(match selector {
+            erc20::NAME => Self::Name,
+            erc20::SYMBOL => Self::Symbol,
+            erc20::DECIMALS => Self::Decimals,
+            erc20::TOTAL_SUPPLY => Self::TotalSupply,
+            erc20::BALANCE_OF => Self::BalanceOf(address(0)?),
+            erc20::ALLOWANCE => Self::Allowance {
+                owner: address(0)?,
+                spender: address(1)?,
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// ABI-encoded `uint256` return value
+pub fn encode_uint(value: U256) -> Vec<u8> {
+    abi_encode(&[AbiValue::Word(value.into())])
+}
+
+/// ABI-encoded `string` return value
+pub fn encode_string(value: &str) -> Vec<u8> {
+    abi_encode(&[AbiValue::Dynamic(value.as_bytes().to_vec())])
+}
+
+/// ERC-20 log of the token mirroring the log of a runtime event, if the event has one
+///
+/// `Balances.Transfer` gives `Transfer` and `EvmAdapter.Approval` gives `Approval`. Their fields
+/// are already in the ERC-20 order (two accounts, then the amount), so the log keeps the topics
+/// and data of the event's log and only changes the signature and the emitting address.
+pub fn erc20_log(pallet: &str, variant: &str, log: &Log) -> Option<Log> {
+    let signature = match (pallet, variant) {
+        (TOKEN_PALLET, "Transfer") => erc20::TRANSFER_EVENT,
+        ("EvmAdapter", "Approval") => erc20::APPROVAL_EVENT,
+        _ => return None,
+    };
+    if log.topics().len() != 3 {
+        return None;
+    }
+
+    let mut topics = log.topics().to_vec();
+    topics[0] = keccak256(signature);
+    Some(Log::new_unchecked(
+        Token::Native.address(),
+        topics,
+        log.data.data.clone(),
+    ))
+}
+
+/// ERC-20 log of an asset for a `pallet_assets` event, given the SCALE encodings of its fields by
+/// name, if the event has one
+///
+/// `Transferred` and `TransferredApproved` give `Transfer`, and `ApprovedTransfer` gives
+/// `Approval`, emitted by the asset's address. Accounts missing from `addresses` are truncated.
+pub fn asset_log(
+    variant: &str,
+    fields: &BTreeMap<String, Vec<u8>>,
+    addresses: &AddressBook,
+) -> Option<Log> {
+    let (signature, from, to) = match variant {
+        "Transferred" => (erc20::TRANSFER_EVENT, "from", "to"),
+        "TransferredApproved" => (erc20::TRANSFER_EVENT, "owner", "destination"),
+        "ApprovedTransfer" => (erc20::APPROVAL_EVENT, "source", "delegate"),
+        _ => return None,
+    };
+    let number = |name: &str| {
+        fields
+            .get(name)
+            .filter(|scale| scale.len() <= 16)
+            .map(|scale| U256::from_le_slice(scale))
+    };
+    let topic = |name: &str| {
+        let account = <[u8; 32]>::try_from(fields.get(name)?.as_slice()).ok()?;
+        Some(addresses.address(&AccountId32::from(account)).into_word())
+    };
+
+    let asset_id = u128::try_from(number("asset_id")?).ok()?;
+    Some(Log::new_unchecked(
+        Token::Asset(asset_id).address(),
+        vec![keccak256(signature), topic(from)?, topic(to)?],
+        encode_uint(number("amount")?).into(),
+    ))
+}
+
+/// Number held by a dynamic storage value, zero if it's missing
+pub fn value_amount(value: Option<&Value<u32>>) -> U256 {
+    U256::from(value.and_then(Value::as_u128).unwrap_or_default())
+}
+
+/// String held by a dynamic `Vec<u8>` value, possibly wrapped like a `BoundedVec`, empty if it's
+/// missing
+pub fn value_string(value: Option<&Value<u32>>) -> String {
+    let bytes = value.and_then(value_bytes).unwrap_or_default();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Bytes held by a dynamic `Vec<u8>` or `[u8; N]` value, possibly wrapped in newtypes
+pub fn value_bytes<T>(value: &Value<T>) -> Option<Vec<u8>> {
+    let ValueDef::Composite(composite) = &value.value else {
+        return None;
+    };
+    let values = composite.values().collect::<Vec<_>>();
+    match values.as_slice() {
+        // newtype
+        [inner] if matches!(inner.value, ValueDef::Composite(_)) => value_bytes(inner),
+        _ if matches!(composite, Composite::Unnamed(_)) => values
+            .iter()
+            .map(|value| value.as_u128().and_then(|byte| u8::try_from(byte).ok()))
+            .collect(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, hex};
+
+    #[test]
+    fn test_decode_view() {
+        let owner = address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F");
+        let spender = address!("4261000000000000000000000000000000000001");
+
+        assert_eq!(Erc20View::decode(&hex!("06fdde03")), Some(Erc20View::Name));
+        assert_eq!(
+            Erc20View::decode(&hex!("313ce567")),
+            Some(Erc20View::Decimals)
+        );
+
+        let mut input = erc20::BALANCE_OF.to_vec();
+        input.extend_from_slice(owner.into_word().as_slice());
+        assert_eq!(Erc20View::decode(&input), Some(Erc20View::BalanceOf(owner)));
+
+        let mut input = erc20::ALLOWANCE.to_vec();
+        input.extend_from_slice(owner.into_word().as_slice());
+        // missing spender
+        assert_eq!(Erc20View::decode(&input), None);
+        input.extend_from_slice(spender.into_word().as_slice());
+        assert_eq!(
+            Erc20View::decode(&input),
+            Some(Erc20View::Allowance { owner, spender })
+        );
+
+        // transactions and JSON storage keys
+        assert_eq!(Erc20View::decode(&erc20::TRANSFER), None);
+        assert_eq!(
+            Erc20View::decode(br#"{"name":"TotalIssuance","keys":[]}"#),
+            None
+        );
+    }
+
+    #[test]
+    fn test_encode_return_values() {
+        assert_eq!(
+            encode_uint(U256::from(10)),
+            U256::from(10).to_be_bytes::<32>().to_vec()
+        );
+        // offset, length, padded bytes
+        let encoded = encode_string("DOT");
+        assert_eq!(encoded.len(), 96);
+        assert_eq!(U256::from_be_slice(&encoded[..32]), U256::from(32));
+        assert_eq!(U256::from_be_slice(&encoded[32..64]), U256::from(3));
+        assert_eq!(&encoded[64..67], b"DOT");
+    }
+
+    #[test]
+    fn test_erc20_log() {
+        let from = address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F");
+        let to = address!("4261000000000000000000000000000000000001");
+        let event_log = Log::new_unchecked(
+            PalletContractMapping::contract_address("Balances"),
+            vec![
+                keccak256("Balances.Transfer(address,address,uint256)"),
+                from.into_word(),
+                to.into_word(),
+            ],
+            encode_uint(U256::from(500)).into(),
+        );
+
+        let log = erc20_log("Balances", "Transfer", &event_log).unwrap();
+        assert_eq!(log.address, Token::Native.address());
+        assert_eq!(
+            log.topics(),
+            &[
+                keccak256("Transfer(address,address,uint256)"),
+                from.into_word(),
+                to.into_word(),
+            ]
+        );
+        assert_eq!(log.data.data, event_log.data.data);
+
+        let log = erc20_log("EvmAdapter", "Approval", &event_log).unwrap();
+        assert_eq!(log.address, Token::Native.address());
+        assert_eq!(
+            log.topics()[0],
+            keccak256("Approval(address,address,uint256)")
+        );
+
+        assert_eq!(erc20_log("Balances", "Deposit", &event_log), None);
+    }
+
+    #[test]
+    fn test_token_from_address() {
+        assert_eq!(
+            Token::from_address(PalletContractMapping::contract_address("Balances")),
+            Some(Token::Native)
+        );
+        assert_eq!(
+            Token::from_address(address!("ffffffff00000000000000000000000000000007")),
+            Some(Token::Asset(7))
+        );
+        assert_eq!(
+            Token::Asset(7).address(),
+            PalletContractMapping::asset_address(7)
+        );
+
+        assert_eq!(
+            Token::from_address(PalletContractMapping::contract_address("Assets")),
+            None
+        );
+        assert_eq!(
+            Token::from_address(address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_asset_log() {
+        let owner = AccountId32::from([1u8; 32]);
+        let delegate = AccountId32::from([2u8; 32]);
+        let destination = AccountId32::from([3u8; 32]);
+        let owner_address = address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F");
+        let mut addresses = AddressBook::default();
+        addresses.insert(owner.clone(), owner_address);
+
+        let fields = BTreeMap::from([
+            ("asset_id".to_string(), 7u32.to_le_bytes().to_vec()),
+            ("owner".to_string(), owner.0.to_vec()),
+            ("delegate".to_string(), delegate.0.to_vec()),
+            ("destination".to_string(), destination.0.to_vec()),
+            ("amount".to_string(), 500u64.to_le_bytes().to_vec()),
+        ]);
+        let log = asset_log("TransferredApproved", &fields, &addresses).unwrap();
+        assert_eq!(log.address, Token::Asset(7).address());
+        assert_eq!(
+            log.topics(),
+            &[
+                keccak256("Transfer(address,address,uint256)"),
+                owner_address.into_word(),
+                // truncated
+                Address::repeat_byte(3).into_word(),
+            ]
+        );
+        assert_eq!(log.data.data.to_vec(), encode_uint(U256::from(500)));
+
+        assert_eq!(asset_log("Issued", &fields, &addresses), None);
+        // missing fields
+        assert_eq!(asset_log("Transferred", &fields, &addresses), None);
+    }
+
+    #[test]
+    fn test_value_string() {
+        let symbol = Value::unnamed_composite([Value::from_bytes("TEST")]);
+        let symbol = symbol.map_context(|_| 0u32);
+        assert_eq!(value_string(Some(&symbol)), "TEST");
+        assert_eq!(value_string(None), "");
+
+        let amount = Value::u128(42).map_context(|_| 0u32);
+        assert_eq!(value_amount(Some(&amount)), U256::from(42));
+        assert_eq!(value_string(Some(&amount)), "");
+    }
+}
@@ -0,0 +1,174 @@
+//! ERC-721 facades of the NFT collections
+//!
+//! The address of each `pallet_nfts` collection ([`PalletContractMapping::collection_address`])
+//! behaves like an ERC-721 token contract, whose token ids are the ids of the collection's items:
+//!
+//! - the views (`balanceOf`, `ownerOf` and `tokenURI`) are answered from the chain state by
+//!   `eth_call`, `tokenURI` being the item's metadata
+//! - `approve`, `transferFrom` and `safeTransferFrom` transactions are dispatched by the adapter
+//!   pallet as `pallet_nfts` calls
+//! - `Transfer` logs are synthesized from the `Nfts.Transferred` events
+
+use crate::adapter::{AddressBook, PalletContractMapping};
+use crate::erc20::value_bytes;
+use crate::events::{abi_encode, AbiValue};
+use alloy_primitives::{keccak256, Address, Log, U256};
+use std::collections::BTreeMap;
+use subeth_primitives::erc721;
+use subxt::dynamic::Value;
+use subxt::utils::AccountId32;
+
+/// Pallet of the collections whose addresses are token contracts
+pub const NFTS_PALLET: &str = "Nfts";
+
+/// View of a collection, decoded from the call data of an `eth_call`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Erc721View {
+    BalanceOf(Address),
+    OwnerOf(U256),
+    TokenUri(U256),
+}
+
+impl Erc721View {
+    /// Decode the call data of a view, `None` for other calls
+    pub fn decode(input: &[u8]) -> Option<Self> {
+        let selector = <[u8; 4]>::try_from(input.get(..4)?).ok()?;
+        let word = input.get(4..36)?;
+
+        Some(match selector {
+            erc721::BALANCE_OF => Self::BalanceOf(Address::from_slice(&word[12..])),
+            erc721::OWNER_OF => Self::OwnerOf(U256::from_be_slice(word)),
+            erc721::TOKEN_URI => Self::TokenUri(U256::from_be_slice(word)),
+            _ => return None,
+        })
+    }
+}
+
+/// ABI-encoded `address` return value
+pub fn encode_address(address: Address) -> Vec<u8> {
+    abi_encode(&[AbiValue::Word(address.into_word())])
+}
+
+/// ERC-721 log of a collection for a `pallet_nfts` event, given the SCALE encodings of its fields
+/// by name, if the event has one
+///
+/// `Transferred` gives `Transfer`, emitted by the collection's address, with the item id as the
+/// indexed token id. Accounts missing from `addresses` are truncated.
+pub fn nft_log(
+    variant: &str,
+    fields: &BTreeMap<String, Vec<u8>>,
+    addresses: &AddressBook,
+) -> Option<Log> {
+    if variant != "Transferred" {
+        return None;
+    }
+    let number = |name: &str| {
+        fields
+            .get(name)
+            .filter(|scale| scale.len() <= 16)
+            .map(|scale| U256::from_le_slice(scale))
+    };
+    let topic = |name: &str| {
+        let account = <[u8; 32]>::try_from(fields.get(name)?.as_slice()).ok()?;
+        Some(addresses.address(&AccountId32::from(account)).into_word())
+    };
+
+    let collection_id = u128::try_from(number("collection")?).ok()?;
+    Some(Log::new_unchecked(
+        PalletContractMapping::collection_address(collection_id),
+        vec![
+            keccak256(erc721::TRANSFER_EVENT),
+            topic("from")?,
+            topic("to")?,
+            number("item")?.into(),
+        ],
+        Default::default(),
+    ))
+}
+
+/// Account held by a dynamic `AccountId32` value, `None` if it's missing
+pub fn value_account(value: Option<&Value<u32>>) -> Option<AccountId32> {
+    let bytes = <[u8; 32]>::try_from(value_bytes(value?)?).ok()?;
+    Some(AccountId32::from(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn test_decode_view() {
+        let owner = address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F");
+
+        let mut input = erc721::BALANCE_OF.to_vec();
+        input.extend_from_slice(owner.into_word().as_slice());
+        assert_eq!(
+            Erc721View::decode(&input),
+            Some(Erc721View::BalanceOf(owner))
+        );
+
+        let mut input = erc721::OWNER_OF.to_vec();
+        // missing token id
+        assert_eq!(Erc721View::decode(&input), None);
+        input.extend_from_slice(&U256::from(7).to_be_bytes::<32>());
+        assert_eq!(
+            Erc721View::decode(&input),
+            Some(Erc721View::OwnerOf(U256::from(7)))
+        );
+
+        input[..4].copy_from_slice(&erc721::TOKEN_URI);
+        assert_eq!(
+            Erc721View::decode(&input),
+            Some(Erc721View::TokenUri(U256::from(7)))
+        );
+
+        input[..4].copy_from_slice(&erc721::TRANSFER_FROM);
+        assert_eq!(Erc721View::decode(&input), None);
+    }
+
+    #[test]
+    fn test_nft_log() {
+        let from = AccountId32::from([1u8; 32]);
+        let to = AccountId32::from([2u8; 32]);
+        let from_address = address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F");
+        let mut addresses = AddressBook::default();
+        addresses.insert(from.clone(), from_address);
+
+        let fields = BTreeMap::from([
+            ("collection".to_string(), 3u32.to_le_bytes().to_vec()),
+            ("item".to_string(), 7u32.to_le_bytes().to_vec()),
+            ("from".to_string(), from.0.to_vec()),
+            ("to".to_string(), to.0.to_vec()),
+        ]);
+        let log = nft_log("Transferred", &fields, &addresses).unwrap();
+        assert_eq!(log.address, PalletContractMapping::collection_address(3));
+        assert_eq!(
+            log.topics(),
+            &[
+                keccak256("Transfer(address,address,uint256)"),
+                from_address.into_word(),
+                // truncated
+                Address::repeat_byte(2).into_word(),
+                U256::from(7).into(),
+            ]
+        );
+        assert!(log.data.data.is_empty());
+
+        assert_eq!(nft_log("Issued", &fields, &addresses), None);
+    }
+
+    #[test]
+    fn test_value_account() {
+        let account = AccountId32::from([5u8; 32]);
+        let value = Value::unnamed_composite([Value::from_bytes(account.0)]);
+        assert_eq!(
+            value_account(Some(&value.map_context(|_| 0u32))),
+            Some(account)
+        );
+        assert_eq!(value_account(None), None);
+
+        let value = Value::from_bytes([5u8; 20]).map_context(|_| 0u32);
+        assert_eq!(value_account(Some(&value)), None);
+    }
+}
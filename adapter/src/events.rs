@@ -0,0 +1,461 @@
+//! Conversion of Substrate runtime events to Ethereum logs
+//!
+//! Every event becomes a log emitted by its pallet's contract address
+//! ([`PalletContractMapping::contract_address`]). Event fields are given Solidity types from the
+//! runtime metadata:
+//!
+//! - `AccountId32` -> `address` (bound in the registry or truncated, see
+//!   [`AddressMapping`](crate::adapter::AddressMapping)), `H160` -> `address`
+//! - unsigned integers (incl. `Compact`) -> `uintN`, signed integers -> `intN`, with the integer's
+//!   width, e.g. `u128` -> `uint128`
+//! - `bool` -> `bool`, `str` -> `string`
+//! - `[u8; N]` with `N <= 32` -> `bytesN`, `Vec<u8>` -> `bytes`
+//! - single-field wrappers (newtypes) -> the type of the inner field
+//! - anything else -> `bytes`, holding the SCALE encoding of the field
+//!
+//! `topic0` is the keccak hash of the canonical signature `Pallet.Event(type1,type2,...)`, e.g.
+//! `Balances.Transfer(address,address,uint128)`. Addresses and integers of at most 32 bits are
+//! indexed, i.e. go into `topic1..topic3` in field order, and the remaining fields are ABI-encoded
+//! in `data`.
+
+use crate::adapter::{AddressBook, PalletContractMapping};
+use crate::types::ChainConfig;
+use alloy_primitives::{keccak256, Address, Log, B256, U256};
+use parity_scale_codec::{Compact, Decode};
+use scale_info::{form::PortableForm, PortableRegistry, TypeDef, TypeDefPrimitive, Variant};
+use std::collections::BTreeMap;
+use subxt::events::EventDetails;
+use subxt::ext::scale_decode::visitor::{decode_with_visitor, IgnoreVisitor};
+use subxt::utils::AccountId32;
+use subxt::Metadata;
+
+/// Max number of indexed fields, `topic0` is the event signature
+const MAX_INDEXED: usize = 3;
+
+/// Solidity type of an event field or a storage key or value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum SolType {
+    /// 32-byte `AccountId32` or 20-byte `H160`
+    Address(usize),
+    /// Unsigned integer of the given size in bytes
+    Uint {
+        size: usize,
+        compact: bool,
+    },
+    /// Signed integer of the given size in bytes
+    Int(usize),
+    Bool,
+    FixedBytes(usize),
+    Bytes,
+    String,
+    /// Type without a Solidity counterpart, passed as its SCALE encoding
+    Encoded,
+}
+
+impl SolType {
+    /// Resolve the Solidity type of a metadata type
+    pub(crate) fn from_metadata(id: u32, types: &PortableRegistry) -> Self {
+        let Some(ty) = types.resolve(id) else {
+            return SolType::Encoded;
+        };
+
+        match ty.path.ident().as_deref() {
+            Some("AccountId32") => return SolType::Address(32),
+            Some("H160") | Some("AccountId20") => return SolType::Address(20),
+            _ => {}
+        }
+
+        match &ty.type_def {
+            TypeDef::Composite(composite) if composite.fields.len() == 1 => {
+                SolType::from_metadata(composite.fields[0].ty.id, types)
+            }
+            TypeDef::Tuple(tuple) if tuple.fields.len() == 1 => {
+                SolType::from_metadata(tuple.fields[0].id, types)
+            }
+            TypeDef::Array(array) if array.len <= 32 && is_u8(array.type_param.id, types) => {
+                SolType::FixedBytes(array.len as usize)
+            }
+            TypeDef::Sequence(seq) if is_u8(seq.type_param.id, types) => SolType::Bytes,
+            TypeDef::Compact(compact) => {
+                match SolType::from_metadata(compact.type_param.id, types) {
+                    SolType::Uint { size, .. } => SolType::Uint {
+                        size,
+                        compact: true,
+                    },
+                    _ => SolType::Encoded,
+                }
+            }
+            TypeDef::Primitive(primitive) => match primitive {
+                TypeDefPrimitive::Bool => SolType::Bool,
+                TypeDefPrimitive::Str => SolType::String,
+                TypeDefPrimitive::Char => SolType::Uint {
+                    size: 4,
+                    compact: false,
+                },
+                TypeDefPrimitive::U8 => SolType::uint(1),
+                TypeDefPrimitive::U16 => SolType::uint(2),
+                TypeDefPrimitive::U32 => SolType::uint(4),
+                TypeDefPrimitive::U64 => SolType::uint(8),
+                TypeDefPrimitive::U128 => SolType::uint(16),
+                TypeDefPrimitive::U256 => SolType::uint(32),
+                TypeDefPrimitive::I8 => SolType::Int(1),
+                TypeDefPrimitive::I16 => SolType::Int(2),
+                TypeDefPrimitive::I32 => SolType::Int(4),
+                TypeDefPrimitive::I64 => SolType::Int(8),
+                TypeDefPrimitive::I128 => SolType::Int(16),
+                TypeDefPrimitive::I256 => SolType::Int(32),
+            },
+            _ => SolType::Encoded,
+        }
+    }
+
+    fn uint(size: usize) -> Self {
+        SolType::Uint {
+            size,
+            compact: false,
+        }
+    }
+
+    /// Name of the type in the canonical event signature
+    pub(crate) fn name(&self) -> String {
+        match self {
+            SolType::Address(_) => "address".into(),
+            SolType::Uint { size, .. } => format!("uint{}", size * 8),
+            SolType::Int(size) => format!("int{}", size * 8),
+            SolType::Bool => "bool".into(),
+            SolType::FixedBytes(len) => format!("bytes{}", len),
+            SolType::Bytes | SolType::Encoded => "bytes".into(),
+            SolType::String => "string".into(),
+        }
+    }
+
+    /// Whether fields of this type go into topics
+    fn is_indexed(&self) -> bool {
+        match self {
+            SolType::Address(_) => true,
+            SolType::Uint { size, .. } | SolType::Int(size) => *size <= 4,
+            _ => false,
+        }
+    }
+
+    /// ABI-encode the SCALE-encoded value of a field
+    pub(crate) fn encode(&self, scale: &[u8], addresses: &AddressBook) -> AbiValue {
+        let mut word = [0u8; 32];
+        match self {
+            SolType::Address(32) => {
+                let Ok(account) = <[u8; 32]>::try_from(scale) else {
+                    return AbiValue::Dynamic(scale.to_vec());
+                };
+                let address = addresses.address(&AccountId32::from(account));
+                AbiValue::Word(address.into_word())
+            }
+            SolType::Address(_) => match <[u8; 20]>::try_from(scale) {
+                Ok(address) => AbiValue::Word(Address::from(address).into_word()),
+                Err(_) => AbiValue::Dynamic(scale.to_vec()),
+            },
+            SolType::Uint { compact: true, .. } => {
+                let value = Compact::<u128>::decode(&mut &scale[..])
+                    .map(|v| v.0)
+                    .unwrap_or_default();
+                AbiValue::Word(U256::from(value).into())
+            }
+            SolType::Uint { .. } => AbiValue::Word(U256::from_le_slice(scale).into()),
+            SolType::Int(_) => {
+                if scale.last().is_some_and(|b| b & 0x80 != 0) {
+                    word = [0xff; 32];
+                }
+                for (i, byte) in scale.iter().enumerate() {
+                    word[31 - i] = *byte;
+                }
+                AbiValue::Word(word.into())
+            }
+            SolType::Bool => {
+                word[31] = scale.first().copied().unwrap_or_default();
+                AbiValue::Word(word.into())
+            }
+            SolType::FixedBytes(len) => {
+                let Some(bytes) = scale.get(..*len) else {
+                    return AbiValue::Dynamic(scale.to_vec());
+                };
+                word[..*len].copy_from_slice(bytes);
+                AbiValue::Word(word.into())
+            }
+            SolType::Bytes => AbiValue::Dynamic(
+                Vec::<u8>::decode(&mut &scale[..]).unwrap_or_else(|_| scale.to_vec()),
+            ),
+            SolType::String => AbiValue::Dynamic(
+                String::decode(&mut &scale[..])
+                    .map(String::into_bytes)
+                    .unwrap_or_else(|_| scale.to_vec()),
+            ),
+            SolType::Encoded => AbiValue::Dynamic(scale.to_vec()),
+        }
+    }
+}
+
+/// ABI-encoded field value
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum AbiValue {
+    /// Static value, encoded in place
+    Word(B256),
+    /// Dynamic value (`bytes` or `string`), encoded in the tail
+    Dynamic(Vec<u8>),
+}
+
+fn is_u8(id: u32, types: &PortableRegistry) -> bool {
+    types
+        .resolve(id)
+        .is_some_and(|ty| matches!(ty.type_def, TypeDef::Primitive(TypeDefPrimitive::U8)))
+}
+
+/// ABI-encode a sequence of values, as done for non-indexed event fields
+pub(crate) fn abi_encode(values: &[AbiValue]) -> Vec<u8> {
+    let head_len = values.len() * 32;
+    let mut head = Vec::with_capacity(head_len);
+    let mut tail = Vec::new();
+
+    for value in values {
+        match value {
+            AbiValue::Word(word) => head.extend_from_slice(word.as_slice()),
+            AbiValue::Dynamic(bytes) => {
+                head.extend_from_slice(&U256::from(head_len + tail.len()).to_be_bytes::<32>());
+                tail.extend_from_slice(&U256::from(bytes.len()).to_be_bytes::<32>());
+                tail.extend_from_slice(bytes);
+                tail.resize(tail.len().next_multiple_of(32), 0);
+            }
+        }
+    }
+
+    head.extend(tail);
+    head
+}
+
+/// Canonical signature of a runtime event, e.g. `Balances.Transfer(address,address,uint128)`
+pub fn event_signature(
+    pallet: &str,
+    variant: &Variant<PortableForm>,
+    types: &PortableRegistry,
+) -> String {
+    let params = variant
+        .fields
+        .iter()
+        .map(|field| SolType::from_metadata(field.ty.id, types).name())
+        .collect::<Vec<_>>();
+
+    format!("{}.{}({})", pallet, variant.name, params.join(","))
+}
+
+/// Solidity types and SCALE encodings of the fields of a runtime event
+fn event_fields<'a>(
+    event: &'a EventDetails<ChainConfig>,
+    types: &'a PortableRegistry,
+) -> Vec<(SolType, &'a [u8])> {
+    let mut fields = Vec::new();
+    let mut input = event.field_bytes();
+    for field in &event.event_metadata().variant.fields {
+        let start = input;
+        if decode_with_visitor(&mut input, field.ty.id, types, IgnoreVisitor::new()).is_err() {
+            break;
+        }
+        let scale = &start[..start.len() - input.len()];
+        fields.push((SolType::from_metadata(field.ty.id, types), scale));
+    }
+
+    fields
+}
+
+/// SCALE encodings of the named fields of a runtime event
+pub fn named_event_fields(
+    event: &EventDetails<ChainConfig>,
+    metadata: &Metadata,
+) -> BTreeMap<String, Vec<u8>> {
+    let fields = &event.event_metadata().variant.fields;
+    fields
+        .iter()
+        .zip(event_fields(event, metadata.types()))
+        .filter_map(|(field, (_, scale))| Some((field.name.clone()?, scale.to_vec())))
+        .collect()
+}
+
+/// `AccountId32` fields of a runtime event, whose addresses [`event_to_log`] needs
+pub fn event_accounts(event: &EventDetails<ChainConfig>, metadata: &Metadata) -> Vec<AccountId32> {
+    event_fields(event, metadata.types())
+        .into_iter()
+        .filter(|(ty, _)| *ty == SolType::Address(32))
+        .filter_map(|(_, scale)| <[u8; 32]>::try_from(scale).ok())
+        .map(AccountId32::from)
+        .collect()
+}
+
+/// Convert a runtime event to a log emitted by the pallet's contract address
+///
+/// Accounts missing from `addresses` are truncated.
+pub fn event_to_log(
+    event: &EventDetails<ChainConfig>,
+    metadata: &Metadata,
+    addresses: &AddressBook,
+) -> Log {
+    let types = metadata.types();
+    let event_metadata = event.event_metadata();
+    let pallet = event_metadata.pallet.name();
+    let variant = event_metadata.variant;
+
+    let address = PalletContractMapping::contract_address(pallet);
+    let mut topics = vec![keccak256(event_signature(pallet, variant, types))];
+    let mut data = Vec::new();
+
+    for (ty, scale) in event_fields(event, types) {
+        match ty.encode(scale, addresses) {
+            AbiValue::Word(word) if ty.is_indexed() && topics.len() <= MAX_INDEXED => {
+                topics.push(word)
+            }
+            value => data.push(value),
+        }
+    }
+
+    Log::new_unchecked(address, topics, abi_encode(&data).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::AddressMapping;
+    use crate::types::{balances, system, Event};
+    use parity_scale_codec::Encode;
+    use sp_core::H256;
+    use subxt::events::{Events, Phase};
+
+    fn local_metadata() -> Metadata {
+        let bytes = std::fs::read("../artifacts/local_metadata.scale").unwrap();
+        Metadata::decode(&mut &bytes[..]).unwrap()
+    }
+
+    fn events(metadata: &Metadata, events: Vec<Event>) -> Events<ChainConfig> {
+        let records = events
+            .into_iter()
+            .map(|event| (Phase::ApplyExtrinsic(0), event, Vec::<H256>::new()))
+            .collect::<Vec<_>>();
+
+        Events::decode_from(records.encode(), metadata.clone())
+    }
+
+    #[test]
+    fn test_transfer_to_log() {
+        let metadata = local_metadata();
+        let from = AccountId32::from([1u8; 32]);
+        let to = AccountId32::from([2u8; 32]);
+        let events = events(
+            &metadata,
+            vec![Event::Balances(balances::Event::Transfer {
+                from: from.clone(),
+                to: to.clone(),
+                amount: 1_000,
+            })],
+        );
+
+        let event = events.iter().next().unwrap().unwrap();
+        let log = event_to_log(&event, &metadata, &AddressBook::default());
+
+        assert_eq!(
+            log.address,
+            PalletContractMapping::contract_address("Balances")
+        );
+        assert_eq!(
+            log.topics(),
+            &[
+                // the balance is a `u64` in the local metadata
+                keccak256("Balances.Transfer(address,address,uint64)"),
+                AddressMapping::to_address(from).into_word(),
+                AddressMapping::to_address(to).into_word(),
+            ]
+        );
+        assert_eq!(log.data.data.to_vec(), U256::from(1_000).to_be_bytes_vec());
+    }
+
+    #[test]
+    fn test_bound_addresses() {
+        let metadata = local_metadata();
+        let from = AccountId32::from([1u8; 32]);
+        let to = AccountId32::from([2u8; 32]);
+        let events = events(
+            &metadata,
+            vec![Event::Balances(balances::Event::Transfer {
+                from: from.clone(),
+                to: to.clone(),
+                amount: 1_000,
+            })],
+        );
+        let event = events.iter().next().unwrap().unwrap();
+        assert_eq!(
+            event_accounts(&event, &metadata),
+            vec![from.clone(), to.clone()]
+        );
+
+        let bound = Address::repeat_byte(0x42);
+        let mut addresses = AddressBook::default();
+        addresses.insert(to.clone(), bound);
+
+        let log = event_to_log(&event, &metadata, &addresses);
+        assert_eq!(
+            log.topics()[1],
+            AddressMapping::to_address(from).into_word()
+        );
+        assert_eq!(log.topics()[2], bound.into_word());
+    }
+
+    #[test]
+    fn test_signature_is_derived_from_metadata() {
+        let metadata = local_metadata();
+        let events = events(
+            &metadata,
+            vec![Event::System(system::Event::NewAccount {
+                account: AccountId32::from([1u8; 32]),
+            })],
+        );
+
+        let event = events.iter().next().unwrap().unwrap();
+        let event_metadata = event.event_metadata();
+
+        assert_eq!(
+            event_signature("System", event_metadata.variant, metadata.types()),
+            "System.NewAccount(address)"
+        );
+    }
+
+    #[test]
+    fn test_encode_unexpected_lengths() {
+        let addresses = AddressBook::default();
+        assert_eq!(
+            SolType::Address(20).encode(&[0xff; 20], &addresses),
+            AbiValue::Word(Address::repeat_byte(0xff).into_word())
+        );
+        // not an `H160`, kept as is instead of panicking
+        assert_eq!(
+            SolType::Address(20).encode(&[0xff; 19], &addresses),
+            AbiValue::Dynamic(vec![0xff; 19])
+        );
+        assert_eq!(
+            SolType::Address(32).encode(&[0xff; 20], &addresses),
+            AbiValue::Dynamic(vec![0xff; 20])
+        );
+        assert_eq!(
+            SolType::FixedBytes(4).encode(&[0xff; 2], &addresses),
+            AbiValue::Dynamic(vec![0xff; 2])
+        );
+    }
+
+    #[test]
+    fn test_abi_encode_dynamic() {
+        let data = abi_encode(&[
+            AbiValue::Word(U256::from(7).into()),
+            AbiValue::Dynamic(vec![0xaa; 33]),
+        ]);
+
+        assert_eq!(data.len(), 32 * 5);
+        assert_eq!(data[..32], U256::from(7).to_be_bytes::<32>());
+        assert_eq!(data[32..64], U256::from(64).to_be_bytes::<32>());
+        assert_eq!(data[64..96], U256::from(33).to_be_bytes::<32>());
+        assert_eq!(data[96..129], [0xaa; 33]);
+        assert!(data[129..].iter().all(|b| *b == 0));
+    }
+}
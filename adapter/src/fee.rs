@@ -0,0 +1,430 @@
+//! Fee oracle (`eth_gasPrice`, `eth_feeHistory`, `eth_maxPriorityFeePerGas`)
+//!
+//! Ethereum transactions pay for their gas to the EVM adapter pallet, so in runtimes that have it
+//! the base fee per gas is the pallet's `BaseFeePerGas` constant, and priority fees are the gas
+//! prices above it in `EvmAdapter.TransactionExecuted` and `EvmAdapter.TransactionFailed` events,
+//! the prices receipts report too.
+//!
+//! Otherwise, Substrate fees aren't priced per gas, so the base fee per gas of a block is the
+//! inclusion fee of a reference transaction ([`REFERENCE_GAS`] worth of weight,
+//! [`REFERENCE_LENGTH`] bytes) divided by its gas. The fee is computed like `pallet_transaction_payment` does, from
+//! `TransactionPayment::NextFeeMultiplier`, `query_weight_to_fee` and `query_length_to_fee`:
+//!
+//! `length_fee + multiplier * weight_fee`
+//!
+//! Since the multiplier stored in a block applies to the next one, the state of a block gives the
+//! base fee of its child. Gas used comes from the dispatch weights of the block's extrinsics, and
+//! priority fees from the tips in `TransactionPayment.TransactionFeePaid` events.
+//!
+//! The fee data of each block is cached, so fee histories over recent blocks only fetch the new
+//! ones.
+
+use crate::events::named_event_fields;
+use crate::receipt::{transaction_gas, weight_to_gas, EVM_ADAPTER_PALLET};
+use crate::sub_client::SubLightClient;
+use crate::types::*;
+use crate::units::Units;
+use alloy_primitives::U256;
+use alloy_rpc_types_eth::{BlockNumberOrTag, FeeHistory};
+use frame_support::sp_runtime::{FixedPointNumber, FixedU128};
+use futures::{StreamExt, TryStreamExt};
+use parking_lot::Mutex;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+use subxt::events::{Events, Phase};
+use subxt::utils::H256;
+use subxt::Metadata;
+
+/// Gas of the reference transaction used to price the base fee
+pub const REFERENCE_GAS: u64 = 21_000;
+/// Length in bytes of the reference transaction used to price the base fee
+pub const REFERENCE_LENGTH: u32 = 256;
+/// Maximum number of blocks in a fee history, larger requests are truncated
+pub const MAX_FEE_HISTORY: u64 = 1024;
+/// Maximum number of reward percentiles in a fee history
+pub const MAX_REWARD_PERCENTILES: usize = 100;
+/// Number of recent blocks sampled by `eth_maxPriorityFeePerGas`
+pub const PRIORITY_FEE_BLOCKS: u64 = 20;
+/// Percentile of the sampled tips returned by `eth_maxPriorityFeePerGas`
+pub const PRIORITY_FEE_PERCENTILE: f64 = 60.0;
+/// Number of blocks whose fee data is cached
+pub const DEFAULT_FEE_CACHE: usize = 2048;
+/// Number of blocks fetched concurrently
+const FETCH_CONCURRENCY: usize = 16;
+
+/// Fee data of a block
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockFees {
+    /// Base fee per gas of the next block, in wei
+    pub next_base_fee_per_gas: u128,
+    /// Gas used by the block
+    pub gas_used: u64,
+    /// Maximum gas of the block
+    pub gas_limit: u64,
+    /// Gas used and tip per gas (in wei) of each transaction that paid a fee
+    pub tips: Vec<(u64, u128)>,
+}
+
+impl BlockFees {
+    /// Ratio of gas used to the block's maximum
+    pub fn gas_used_ratio(&self) -> f64 {
+        if self.gas_limit == 0 {
+            return 0.0;
+        }
+        self.gas_used as f64 / self.gas_limit as f64
+    }
+
+    /// Tip per gas at each percentile, weighted by the gas used by each transaction
+    ///
+    /// Same as geth: transactions are sorted by tip, and the reward at `p` is the tip of the
+    /// transaction in which `p`% of the gas is reached.
+    pub fn rewards(&self, percentiles: &[f64]) -> Vec<u128> {
+        let mut tips = self.tips.clone();
+        if tips.is_empty() {
+            return vec![0; percentiles.len()];
+        }
+        tips.sort_by_key(|(_, tip)| *tip);
+
+        let total_gas = tips.iter().map(|(gas, _)| *gas).sum::<u64>();
+        let mut index = 0;
+        let mut cumulative_gas = tips[0].0;
+
+        percentiles
+            .iter()
+            .map(|percentile| {
+                let threshold = (total_gas as f64 * percentile / 100.0) as u64;
+                while cumulative_gas < threshold && index < tips.len() - 1 {
+                    index += 1;
+                    cumulative_gas += tips[index].0;
+                }
+                tips[index].1
+            })
+            .collect()
+    }
+}
+
+/// Base fee per gas in wei, from the fee of the reference transaction
+pub fn base_fee_per_gas(
+    multiplier: FixedU128,
+    weight_fee: u128,
+    length_fee: u128,
+    units: Units,
+) -> u128 {
+    let fee = length_fee.saturating_add(multiplier.saturating_mul_int(weight_fee));
+    let fee = units.to_wei(U256::from(fee)) / U256::from(REFERENCE_GAS);
+    fee.saturating_to()
+}
+
+/// `BaseFeePerGas` of the EVM adapter pallet, in the native token's smallest unit, `None` if the
+/// runtime doesn't have it
+pub fn pallet_base_fee_per_gas(metadata: &Metadata) -> Option<U256> {
+    let value = metadata
+        .pallet_by_name(EVM_ADAPTER_PALLET)?
+        .constant_by_name("BaseFeePerGas")?
+        .value();
+    (value.len() <= 32).then(|| U256::from_le_slice(value))
+}
+
+/// Gas used by the block, and the gas used and tip per gas (in wei) of each transaction that paid
+/// a fee
+///
+/// With the EVM adapter pallet's `base_fee` (see [`pallet_base_fee_per_gas`]), the transactions
+/// are the Ethereum transactions and their tips the part of their gas price above it.
+pub fn block_usage(
+    events: &Events<ChainConfig>,
+    metadata: &Metadata,
+    base_fee: Option<U256>,
+    units: Units,
+) -> Result<(u64, Vec<(u64, u128)>), SubEthError> {
+    // weight, tip and Ethereum transaction gas by extrinsic index
+    let mut extrinsics = BTreeMap::<u32, (u64, Option<u128>, Option<(u64, U256)>)>::new();
+
+    for event in events.iter() {
+        let event = event?;
+        let Phase::ApplyExtrinsic(index) = event.phase() else {
+            continue;
+        };
+        let extrinsic = extrinsics.entry(index).or_default();
+
+        if let Some(ev) = event.as_event::<system::events::ExtrinsicSuccess>()? {
+            extrinsic.0 = ev.dispatch_info.weight.ref_time;
+        } else if let Some(ev) = event.as_event::<system::events::ExtrinsicFailed>()? {
+            extrinsic.0 = ev.dispatch_info.weight.ref_time;
+        } else if let Some(ev) =
+            event.as_event::<transaction_payment::events::TransactionFeePaid>()?
+        {
+            extrinsic.1 = Some(ev.tip.into());
+        } else if event.pallet_name() == EVM_ADAPTER_PALLET
+            && ["TransactionExecuted", "TransactionFailed"].contains(&event.variant_name())
+        {
+            extrinsic.2 = transaction_gas(&named_event_fields(&event, metadata));
+        }
+    }
+
+    let gas_used = extrinsics
+        .values()
+        .map(|(weight, _, _)| weight_to_gas(*weight))
+        .sum();
+    let tips = extrinsics
+        .into_values()
+        .filter_map(|(weight, tip, transaction)| {
+            if let Some(base_fee) = base_fee {
+                let (gas, gas_price) = transaction?;
+                let tip_per_gas = units.to_wei(gas_price.saturating_sub(base_fee));
+                return Some((gas, tip_per_gas.saturating_to()));
+            }
+            let gas = weight_to_gas(weight);
+            let tip = tip.filter(|_| gas > 0)?;
+            let tip_per_gas = units.to_wei(U256::from(tip)) / U256::from(gas);
+            Some((gas, tip_per_gas.saturating_to()))
+        })
+        .collect();
+
+    Ok((gas_used, tips))
+}
+
+/// Check that the reward percentiles are increasing and within `[0, 100]`
+fn validate_percentiles(percentiles: &[f64]) -> Result<(), SubEthError> {
+    if percentiles.len() > MAX_REWARD_PERCENTILES {
+        return Err(SubEthError::InvalidParams(format!(
+            "too many reward percentiles, max {}",
+            MAX_REWARD_PERCENTILES
+        )));
+    }
+
+    let mut previous = 0.0;
+    for percentile in percentiles {
+        if !(0.0..=100.0).contains(percentile) || *percentile < previous {
+            return Err(SubEthError::InvalidParams(format!(
+                "invalid reward percentile: {}",
+                percentile
+            )));
+        }
+        previous = *percentile;
+    }
+
+    Ok(())
+}
+
+/// Fee data of recent blocks, by block hash
+#[derive(Debug)]
+struct FeeCache {
+    order: VecDeque<H256>,
+    blocks: HashMap<H256, BlockFees>,
+    capacity: usize,
+}
+
+impl FeeCache {
+    fn get(&self, hash: &H256) -> Option<BlockFees> {
+        self.blocks.get(hash).cloned()
+    }
+
+    fn insert(&mut self, hash: H256, fees: BlockFees) {
+        if self.blocks.contains_key(&hash) {
+            return;
+        }
+        if self.order.len() >= self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.blocks.remove(&oldest);
+            }
+        }
+        self.order.push_back(hash);
+        self.blocks.insert(hash, fees);
+    }
+}
+
+/// Computes fees from on-chain fee data
+#[derive(Clone)]
+pub struct FeeOracle {
+    client: SubLightClient,
+    cache: Arc<Mutex<FeeCache>>,
+}
+
+impl FeeOracle {
+    pub fn new(client: SubLightClient) -> Self {
+        Self::with_capacity(client, DEFAULT_FEE_CACHE)
+    }
+
+    /// Cache the fee data of at most `capacity` blocks
+    pub fn with_capacity(client: SubLightClient, capacity: usize) -> Self {
+        Self {
+            client,
+            cache: Arc::new(Mutex::new(FeeCache {
+                order: VecDeque::with_capacity(capacity),
+                blocks: HashMap::new(),
+                capacity,
+            })),
+        }
+    }
+
+    /// Fee data of the block with the given number
+    async fn block_fees(&self, number: u64) -> Result<BlockFees, SubEthError> {
+        let hash = self
+            .client
+            .block_hash_by_tag(BlockNumberOrTag::Number(number))
+            .await?
+            .ok_or(SubEthError::BlockNotFound)?;
+
+        if let Some(fees) = self.cache.lock().get(&hash) {
+            return Ok(fees);
+        }
+
+        let fees = self.client.block_fees(hash).await?;
+        self.cache.lock().insert(hash, fees.clone());
+        Ok(fees)
+    }
+
+    /// Fee data of the blocks in the range, in order
+    async fn blocks_fees(
+        &self,
+        range: std::ops::RangeInclusive<u64>,
+    ) -> Result<Vec<BlockFees>, SubEthError> {
+        futures::stream::iter(range)
+            .map(|number| self.block_fees(number))
+            .buffered(FETCH_CONCURRENCY)
+            .try_collect()
+            .await
+    }
+
+    /// Base fees, gas used ratios and rewards of up to [`MAX_FEE_HISTORY`] blocks
+    pub async fn fee_history(
+        &self,
+        block_count: u64,
+        newest_block: BlockNumberOrTag,
+        reward_percentiles: Option<Vec<f64>>,
+    ) -> Result<FeeHistory, SubEthError> {
+        if let Some(percentiles) = &reward_percentiles {
+            validate_percentiles(percentiles)?;
+        }
+
+        let newest = self
+            .client
+            .block_number_by_tag(newest_block)
+            .await?
+            .ok_or(SubEthError::BlockNotFound)?;
+        let block_count = block_count.min(MAX_FEE_HISTORY).min(newest + 1);
+        if block_count == 0 {
+            return Ok(FeeHistory::default());
+        }
+        let oldest = newest + 1 - block_count;
+
+        // the parent of the oldest block gives its base fee
+        let parent = oldest.saturating_sub(1);
+        let mut fees = self.blocks_fees(parent..=newest).await?;
+        if oldest == 0 {
+            fees.insert(0, fees[0].clone());
+        }
+        let (parent_fees, fees) = fees.split_first().expect("range isn't empty; qed");
+
+        Ok(FeeHistory {
+            base_fee_per_gas: std::iter::once(parent_fees)
+                .chain(fees.iter())
+                .map(|block| block.next_base_fee_per_gas)
+                .collect(),
+            gas_used_ratio: fees.iter().map(BlockFees::gas_used_ratio).collect(),
+            oldest_block: oldest,
+            reward: reward_percentiles.map(|percentiles| {
+                fees.iter()
+                    .map(|block| block.rewards(&percentiles))
+                    .collect()
+            }),
+            ..Default::default()
+        })
+    }
+
+    /// Suggested tip per gas: a percentile of the tips paid in recent blocks
+    pub async fn max_priority_fee_per_gas(&self) -> Result<u128, SubEthError> {
+        let newest = self.client.block_number().await?;
+        let oldest = newest.saturating_sub(PRIORITY_FEE_BLOCKS - 1);
+
+        let mut tips = self
+            .blocks_fees(oldest..=newest)
+            .await?
+            .into_iter()
+            .flat_map(|block| block.tips.into_iter().map(|(_, tip)| tip))
+            .collect::<Vec<_>>();
+        if tips.is_empty() {
+            return Ok(0);
+        }
+        tips.sort_unstable();
+
+        let index = ((tips.len() - 1) as f64 * PRIORITY_FEE_PERCENTILE / 100.0) as usize;
+        Ok(tips[index])
+    }
+
+    /// Base fee of the next block plus the suggested tip
+    pub async fn gas_price(&self) -> Result<u128, SubEthError> {
+        let latest = self.block_fees(self.client.block_number().await?).await?;
+        let tip = self.max_priority_fee_per_gas().await?;
+
+        Ok(latest.next_base_fee_per_gas.saturating_add(tip))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::units::BalanceUnit;
+
+    fn block(tips: Vec<(u64, u128)>) -> BlockFees {
+        BlockFees {
+            tips,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rewards() {
+        // out of order, weighted by gas
+        let fees = block(vec![(50_000, 30), (21_000, 10), (29_000, 20)]);
+
+        assert_eq!(
+            fees.rewards(&[0.0, 25.0, 50.0, 100.0]),
+            vec![10, 20, 20, 30]
+        );
+        assert_eq!(fees.rewards(&[20.0, 21.0, 51.0]), vec![10, 10, 30]);
+        assert_eq!(block(vec![]).rewards(&[10.0, 90.0]), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_gas_used_ratio() {
+        let fees = BlockFees {
+            gas_used: 250,
+            gas_limit: 1_000,
+            ..Default::default()
+        };
+        assert_eq!(fees.gas_used_ratio(), 0.25);
+        assert_eq!(BlockFees::default().gas_used_ratio(), 0.0);
+    }
+
+    #[test]
+    fn test_base_fee_per_gas() {
+        let units = Units::new(18, BalanceUnit::Wei);
+        let one = FixedU128::from_u32(1);
+
+        assert_eq!(base_fee_per_gas(one, 21_000, 0, units), 1);
+        // the multiplier only applies to the weight fee
+        assert_eq!(
+            base_fee_per_gas(FixedU128::from_u32(2), 21_000, 21_000, units),
+            3
+        );
+        assert_eq!(
+            base_fee_per_gas(FixedU128::from_rational(1, 2), 42_000, 0, units),
+            1
+        );
+
+        // scaled to wei
+        let units = Units::new(10, BalanceUnit::Wei);
+        assert_eq!(base_fee_per_gas(one, 21_000, 0, units), 100_000_000);
+    }
+
+    #[test]
+    fn test_validate_percentiles() {
+        assert!(validate_percentiles(&[]).is_ok());
+        assert!(validate_percentiles(&[0.0, 25.0, 25.0, 100.0]).is_ok());
+        assert!(validate_percentiles(&[50.0, 25.0]).is_err());
+        assert!(validate_percentiles(&[101.0]).is_err());
+        assert!(validate_percentiles(&[-1.0]).is_err());
+        assert!(validate_percentiles(&[1.0; MAX_REWARD_PERCENTILES + 1]).is_err());
+    }
+}
@@ -0,0 +1,431 @@
+//! Polling filters (`eth_newFilter`, `eth_getFilterChanges`, `eth_getLogs`, ...)
+//!
+//! Installed filters are kept in memory, each with its own cursor:
+//!
+//! - block filters collect the hashes of new best blocks until they're polled
+//! - log filters remember the next block to scan, and scan block events up to the best block when
+//!   polled
+//! - pending transaction filters remember which pool transactions were already reported
+//!
+//! Filters that aren't polled within the timeout are removed. Every log query is bounded by a
+//! maximum block range and result size, so a single request can't scan the whole chain.
+
+use crate::sub_client::{BlockStream, SubLightClient};
+use crate::types::SubEthError;
+use alloy_primitives::B256;
+use alloy_rpc_types_eth::{
+    BlockId, BlockNumberOrTag, Filter, FilterBlockOption, FilterChanges, FilteredParams, Log,
+};
+use futures::StreamExt;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Default maximum number of blocks scanned by a single log query
+pub const DEFAULT_MAX_BLOCK_RANGE: u64 = 1024;
+/// Default maximum number of logs returned by a single log query
+pub const DEFAULT_MAX_LOGS: usize = 10_000;
+/// Default time after which a filter that isn't polled is removed
+pub const DEFAULT_FILTER_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Limits applied to filters and `eth_getLogs`
+#[derive(Clone, Copy, Debug)]
+pub struct FilterLimits {
+    /// Maximum number of blocks scanned by a single query
+    pub max_block_range: u64,
+    /// Maximum number of logs returned by a single query
+    pub max_logs: usize,
+    /// Filters that aren't polled for this long are removed
+    pub timeout: Duration,
+}
+
+impl Default for FilterLimits {
+    fn default() -> Self {
+        Self {
+            max_block_range: DEFAULT_MAX_BLOCK_RANGE,
+            max_logs: DEFAULT_MAX_LOGS,
+            timeout: DEFAULT_FILTER_TIMEOUT,
+        }
+    }
+}
+
+/// Kind of an installed filter, along with its cursor
+#[derive(Debug)]
+enum FilterKind {
+    /// New block hashes since the last poll
+    Block(Vec<B256>),
+    /// Log filter and the next block to scan
+    Log { filter: Filter, next_block: u64 },
+    /// Pending transactions that were already reported
+    PendingTransaction(HashSet<B256>),
+}
+
+#[derive(Debug)]
+struct InstalledFilter {
+    kind: FilterKind,
+    last_poll: Instant,
+}
+
+/// In-memory filter storage
+#[derive(Debug)]
+struct FilterStore {
+    filters: HashMap<usize, InstalledFilter>,
+    next_id: usize,
+    timeout: Duration,
+}
+
+impl FilterStore {
+    fn new(timeout: Duration) -> Self {
+        Self {
+            filters: HashMap::new(),
+            next_id: 1,
+            timeout,
+        }
+    }
+
+    /// Install a new filter, returning its id
+    fn install(&mut self, kind: FilterKind) -> usize {
+        self.evict_expired();
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.filters.insert(
+            id,
+            InstalledFilter {
+                kind,
+                last_poll: Instant::now(),
+            },
+        );
+
+        id
+    }
+
+    /// Get a filter for polling, resetting its expiry
+    fn poll(&mut self, id: usize) -> Option<&mut FilterKind> {
+        self.evict_expired();
+
+        let filter = self.filters.get_mut(&id)?;
+        filter.last_poll = Instant::now();
+        Some(&mut filter.kind)
+    }
+
+    fn uninstall(&mut self, id: usize) -> bool {
+        self.filters.remove(&id).is_some()
+    }
+
+    /// Record a new best block in all block filters
+    fn on_new_block(&mut self, hash: B256) {
+        self.evict_expired();
+
+        for filter in self.filters.values_mut() {
+            if let FilterKind::Block(hashes) = &mut filter.kind {
+                hashes.push(hash);
+            }
+        }
+    }
+
+    fn evict_expired(&mut self) {
+        let timeout = self.timeout;
+        self.filters
+            .retain(|_, filter| filter.last_poll.elapsed() < timeout);
+    }
+}
+
+/// Manages installed filters and answers log queries
+#[derive(Clone)]
+pub struct FilterManager {
+    client: SubLightClient,
+    limits: FilterLimits,
+    store: Arc<Mutex<FilterStore>>,
+}
+
+impl FilterManager {
+    pub fn new(client: SubLightClient, limits: FilterLimits) -> Self {
+        Self {
+            client,
+            limits,
+            store: Arc::new(Mutex::new(FilterStore::new(limits.timeout))),
+        }
+    }
+
+    /// Install a log filter
+    ///
+    /// Logs are reported starting from `fromBlock`, or from the next block if it's not a number.
+    pub async fn new_log_filter(&self, filter: Filter) -> Result<usize, SubEthError> {
+        let next_block = match filter.get_from_block() {
+            Some(number) => number,
+            None => self.client.block_number().await? + 1,
+        };
+
+        Ok(self
+            .store
+            .lock()
+            .install(FilterKind::Log { filter, next_block }))
+    }
+
+    /// Install a block filter
+    pub fn new_block_filter(&self) -> usize {
+        self.store.lock().install(FilterKind::Block(vec![]))
+    }
+
+    /// Install a pending transaction filter
+    pub async fn new_pending_transaction_filter(&self) -> Result<usize, SubEthError> {
+        let seen = self
+            .client
+            .pending_transaction_hashes()
+            .await?
+            .into_iter()
+            .collect();
+
+        Ok(self
+            .store
+            .lock()
+            .install(FilterKind::PendingTransaction(seen)))
+    }
+
+    /// Remove a filter
+    pub fn uninstall(&self, id: usize) -> bool {
+        self.store.lock().uninstall(id)
+    }
+
+    /// Changes since the last poll of the filter
+    pub async fn filter_changes(&self, id: usize) -> Result<FilterChanges, SubEthError> {
+        let cursor = {
+            let mut store = self.store.lock();
+            match store.poll(id).ok_or(SubEthError::FilterNotFound)? {
+                FilterKind::Block(hashes) => {
+                    return Ok(FilterChanges::Hashes(std::mem::take(hashes)))
+                }
+                FilterKind::Log { filter, next_block } => Some((filter.clone(), *next_block)),
+                FilterKind::PendingTransaction(_) => None,
+            }
+        };
+        let Some((filter, from)) = cursor else {
+            return self.pending_transaction_changes(id).await;
+        };
+
+        let best = self.client.block_number().await?;
+        let to = filter.get_to_block().map_or(best, |to| to.min(best));
+        if from > to {
+            return Ok(FilterChanges::Empty);
+        }
+        // the rest of the range is scanned on the next polls
+        let to = to.min(from + self.limits.max_block_range.max(1) - 1);
+
+        let logs = self.logs_in_range(&filter, from, to).await?;
+
+        if let Some(FilterKind::Log { next_block, .. }) = self.store.lock().poll(id) {
+            *next_block = to + 1;
+        }
+
+        Ok(FilterChanges::Logs(logs))
+    }
+
+    async fn pending_transaction_changes(&self, id: usize) -> Result<FilterChanges, SubEthError> {
+        let pending = self.client.pending_transaction_hashes().await?;
+
+        let mut store = self.store.lock();
+        let Some(FilterKind::PendingTransaction(seen)) = store.poll(id) else {
+            return Err(SubEthError::FilterNotFound);
+        };
+
+        let new = pending
+            .iter()
+            .filter(|hash| !seen.contains(*hash))
+            .copied()
+            .collect();
+        *seen = pending.into_iter().collect();
+
+        Ok(FilterChanges::Hashes(new))
+    }
+
+    /// All logs matching an installed log filter
+    pub async fn filter_logs(&self, id: usize) -> Result<Vec<Log>, SubEthError> {
+        let filter = match self.store.lock().poll(id) {
+            Some(FilterKind::Log { filter, .. }) => filter.clone(),
+            _ => return Err(SubEthError::FilterNotFound),
+        };
+
+        self.logs(filter).await
+    }
+
+    /// Logs matching the filter (`eth_getLogs`)
+    pub async fn logs(&self, filter: Filter) -> Result<Vec<Log>, SubEthError> {
+        let (from, to) = match filter.block_option {
+            FilterBlockOption::AtBlockHash(hash) => {
+                let logs = self.client.get_block_logs(BlockId::hash(hash)).await?;
+                let mut matched = vec![];
+                self.add_matching(&filter, logs, &mut matched)?;
+                return Ok(matched);
+            }
+            FilterBlockOption::Range {
+                from_block,
+                to_block,
+            } => (
+                self.resolve(from_block.unwrap_or_default()).await?,
+                self.resolve(to_block.unwrap_or_default()).await?,
+            ),
+        };
+
+        if from > to {
+            return Ok(vec![]);
+        }
+        if to - from >= self.limits.max_block_range {
+            return Err(SubEthError::LimitExceeded(format!(
+                "block range is limited to {} blocks",
+                self.limits.max_block_range
+            )));
+        }
+
+        self.logs_in_range(&filter, from, to).await
+    }
+
+    /// Keep block filters up to date
+    ///
+    /// Follows the best block stream, records new block hashes in block filters and removes
+    /// expired filters.
+    pub async fn run(self) -> Result<(), SubEthError> {
+        let mut hashes = Box::pin(self.client.block_hashes(BlockStream::Best).await?);
+        while let Some(hash) = hashes.next().await {
+            self.store.lock().on_new_block(hash);
+        }
+
+        Ok(())
+    }
+
+    async fn resolve(&self, block: BlockNumberOrTag) -> Result<u64, SubEthError> {
+        match block {
+            BlockNumberOrTag::Number(number) => Ok(number),
+            BlockNumberOrTag::Earliest => Ok(0),
+            _ => self.client.block_number().await,
+        }
+    }
+
+    async fn logs_in_range(
+        &self,
+        filter: &Filter,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<Log>, SubEthError> {
+        let mut logs = vec![];
+        for number in from..=to {
+            let block_logs = self.client.get_block_logs(BlockId::number(number)).await?;
+            self.add_matching(filter, block_logs, &mut logs)?;
+        }
+
+        Ok(logs)
+    }
+
+    /// Add the logs matching the filter's addresses and topics to the logs matched so far by the
+    /// query, failing as soon as they exceed the query's limit
+    fn add_matching(
+        &self,
+        filter: &Filter,
+        logs: Vec<Log>,
+        matched: &mut Vec<Log>,
+    ) -> Result<(), SubEthError> {
+        add_limited(matched, filter_logs(filter, logs), self.limits.max_logs)
+    }
+}
+
+/// Add `logs` to `matched`, failing if there would be more than `max_logs`
+fn add_limited(matched: &mut Vec<Log>, logs: Vec<Log>, max_logs: usize) -> Result<(), SubEthError> {
+    if matched.len().saturating_add(logs.len()) > max_logs {
+        return Err(SubEthError::LimitExceeded(format!(
+            "query returned more than {} results",
+            max_logs
+        )));
+    }
+
+    matched.extend(logs);
+    Ok(())
+}
+
+/// Keep the logs matching the filter's addresses and topics
+pub fn filter_logs(filter: &Filter, logs: Vec<Log>) -> Vec<Log> {
+    let params = FilteredParams::new(Some(filter.clone()));
+
+    logs.into_iter()
+        .filter(|log| params.filter_address(&log.address()) && params.filter_topics(log.topics()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, LogData};
+
+    fn log(address: Address, topics: Vec<B256>) -> Log {
+        Log {
+            inner: alloy_primitives::Log {
+                address,
+                data: LogData::new_unchecked(topics, Default::default()),
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_filter_logs_by_address_and_topic() {
+        let balances = Address::repeat_byte(1);
+        let system = Address::repeat_byte(2);
+        let transfer = B256::repeat_byte(10);
+        let deposit = B256::repeat_byte(11);
+        let logs = vec![
+            log(balances, vec![transfer]),
+            log(balances, vec![deposit]),
+            log(system, vec![transfer]),
+        ];
+
+        let filter = Filter::new().address(balances).event_signature(transfer);
+        let matched = filter_logs(&filter, logs.clone());
+        assert_eq!(matched, vec![logs[0].clone()]);
+
+        let filter = Filter::new().event_signature(transfer);
+        assert_eq!(filter_logs(&filter, logs.clone()).len(), 2);
+
+        assert_eq!(filter_logs(&Filter::new(), logs).len(), 3);
+    }
+
+    #[test]
+    fn test_log_limit_applies_to_the_whole_query() {
+        let logs = vec![log(Address::repeat_byte(1), vec![]); 3];
+        let mut matched = vec![];
+
+        assert!(add_limited(&mut matched, logs.clone(), 5).is_ok());
+        assert_eq!(matched.len(), 3);
+        // a block under the limit on its own
+        assert!(matches!(
+            add_limited(&mut matched, logs, 5),
+            Err(SubEthError::LimitExceeded(_))
+        ));
+        assert!(add_limited(&mut matched, vec![], 5).is_ok());
+    }
+
+    #[test]
+    fn test_block_filter_collects_hashes() {
+        let mut store = FilterStore::new(DEFAULT_FILTER_TIMEOUT);
+        let id = store.install(FilterKind::Block(vec![]));
+
+        store.on_new_block(B256::repeat_byte(1));
+        store.on_new_block(B256::repeat_byte(2));
+
+        match store.poll(id) {
+            Some(FilterKind::Block(hashes)) => {
+                assert_eq!(hashes, &vec![B256::repeat_byte(1), B256::repeat_byte(2)])
+            }
+            other => panic!("unexpected filter: {:?}", other),
+        }
+        assert!(store.uninstall(id));
+        assert!(!store.uninstall(id));
+    }
+
+    #[test]
+    fn test_filters_expire() {
+        let mut store = FilterStore::new(Duration::ZERO);
+        let id = store.install(FilterKind::Block(vec![]));
+
+        assert!(store.poll(id).is_none());
+    }
+}
@@ -0,0 +1,448 @@
+//! Gas estimation
+//!
+//! If the runtime implements `EvmAdapterApi`, `eth_estimateGas` dry-runs the transaction with
+//! `EvmAdapterApi_dry_run`: a failed call is returned as a revert error, and the estimate is the gas
+//! the pallet requires for the call's weight.
+//!
+//! Otherwise, it builds the call that `pallet_evm_adapter::transact` would dispatch for the
+//! transaction (see [`dispatched_call`]), and:
+//!
+//! - dry-runs it with the sender as origin, if the runtime implements `DryRunApi`. A failed dry-run
+//!   is returned as a revert error carrying the dispatch error
+//! - asks `TransactionPaymentApi_query_info` for the weight of the call, and of the `transact`
+//!   extrinsic wrapping it
+//!
+//! The total `ref_time` is converted to gas with [`WEIGHT_PER_GAS`](crate::receipt::WEIGHT_PER_GAS),
+//! the ratio used for receipts.
+//!
+//! `system_dryRun` isn't used, as it applies a whole extrinsic, which needs a valid signature.
+
+use crate::adapter::{AddressMapping, PalletContractMapping, GENERIC_CALL_ADDRESS};
+use crate::types::*;
+use alloy_primitives::{Address, TxKind, U256};
+use alloy_rpc_types_eth::TransactionRequest;
+use frame_support::dispatch::{DispatchClass, DispatchErrorWithPostInfo, PostDispatchInfo};
+use frame_support::sp_runtime::DispatchError;
+use frame_support::weights::Weight;
+use parity_scale_codec::{Decode, Encode};
+use subeth_primitives::EthereumTransaction;
+use subxt::tx::Payload;
+use subxt::utils::MultiAddress;
+use subxt::Metadata;
+
+/// Selector of ERC-20 `transfer(address,uint256)`
+const TRANSFER_SELECTOR: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+
+/// XCM version asked for in `DryRunApi_dry_run_call` (v4), the XCMs aren't used
+pub const DRY_RUN_XCM_VERSION: u32 = 4;
+
+/// Result of `TransactionPaymentApi_query_info`
+#[derive(Debug, Decode)]
+pub struct RuntimeDispatchInfo {
+    /// Weight of the extrinsic
+    pub weight: Weight,
+    /// Dispatch class of the extrinsic
+    pub class: DispatchClass,
+    /// Fee without the tip
+    pub partial_fee: RuntimeBalance,
+}
+
+/// Result of `DryRunApi_dry_run_call`, only the execution result is decoded
+#[derive(Debug, Decode)]
+pub struct CallDryRunEffects {
+    pub execution_result: Result<PostDispatchInfo, DispatchErrorWithPostInfo>,
+}
+
+/// Result of `EvmAdapterApi_dry_run`, the events aren't decoded
+#[derive(Debug, Decode)]
+pub struct EvmDryRunResult {
+    /// Result of the dispatched call
+    pub result: Result<(), DispatchError>,
+    /// Weight used by the transaction
+    pub weight: Weight,
+    /// Gas limit the transaction needs
+    pub gas_required: u64,
+}
+
+/// Error of `DryRunApi_dry_run_call`
+#[derive(Debug, Decode)]
+pub enum DryRunError {
+    /// The runtime doesn't implement the API
+    Unimplemented,
+    /// The requested XCM version isn't supported
+    VersionedConversionFailed,
+}
+
+/// Call data that is passed through as is
+pub struct RawCall(pub Vec<u8>);
+
+impl Payload for RawCall {
+    fn encode_call_data_to(
+        &self,
+        _metadata: &Metadata,
+        out: &mut Vec<u8>,
+    ) -> Result<(), subxt::ext::subxt_core::Error> {
+        out.extend_from_slice(&self.0);
+        Ok(())
+    }
+}
+
+/// Build the call dispatched by the adapter pallet for the transaction
+///
+/// Mirrors `pallet_evm_adapter::decode_call`:
+///
+/// - an ERC-20 `transfer` to the `Balances` address becomes `Balances::transfer_allow_death`
+/// - the data of a transaction to [`GENERIC_CALL_ADDRESS`] is the SCALE encoded call
+/// - a transaction without data to an address that isn't a pallet's transfers `value` (in planck)
+///   with `Balances::transfer_keep_alive`
+///
+/// Anything else is rejected with the pallet's error.
+pub fn dispatched_call(
+    request: &TransactionRequest,
+    value: U256,
+    metadata: &Metadata,
+) -> Result<Vec<u8>, SubEthError> {
+    let to = match request.to {
+        Some(TxKind::Call(to)) => to,
+        _ => return Err(SubEthError::Unsupported),
+    };
+    let data = request
+        .input
+        .input()
+        .map(|input| input.to_vec())
+        .unwrap_or_default();
+
+    let Some(pallet_name) = PalletContractMapping::pallet_name(to) else {
+        return value_transfer(to, &data, value, metadata);
+    };
+    if !value.is_zero() {
+        return Err(SubEthError::Reverted("ValueNotAccepted".to_string()));
+    }
+
+    if to == GENERIC_CALL_ADDRESS {
+        return generic_call(data, metadata);
+    }
+
+    match pallet_name.as_str() {
+        "Balances" => balances_transfer(&data, metadata),
+        _ => Err(SubEthError::Reverted("UnsupportedPallet".to_string())),
+    }
+}
+
+/// Transfer of `value` to the account mapped from `to`
+fn value_transfer(
+    to: Address,
+    data: &[u8],
+    value: U256,
+    metadata: &Metadata,
+) -> Result<Vec<u8>, SubEthError> {
+    if !data.is_empty() {
+        return Err(SubEthError::Reverted("InvalidTransactionData".to_string()));
+    }
+
+    let call = tx().balances().transfer_keep_alive(
+        MultiAddress::Id(AddressMapping::to_ss58(to)),
+        value.saturating_to::<u64>(),
+    );
+
+    Ok(call.encode_call_data(metadata)?)
+}
+
+/// Check that the data is a call known to the runtime
+fn generic_call(data: Vec<u8>, metadata: &Metadata) -> Result<Vec<u8>, SubEthError> {
+    let known = match data.as_slice() {
+        [pallet, call, ..] => metadata
+            .pallet_by_index(*pallet)
+            .and_then(|pallet| pallet.call_variant_by_index(*call))
+            .is_some(),
+        _ => false,
+    };
+
+    if known {
+        Ok(data)
+    } else {
+        Err(SubEthError::Reverted("CallDecodeFailed".to_string()))
+    }
+}
+
+/// Decode an ERC-20 `transfer(address,uint256)` as a balances transfer
+fn balances_transfer(data: &[u8], metadata: &Metadata) -> Result<Vec<u8>, SubEthError> {
+    if !data.starts_with(&TRANSFER_SELECTOR) {
+        return Err(SubEthError::Reverted("CallDecodeFailed".to_string()));
+    }
+    if data.len() < 68 {
+        return Err(SubEthError::Reverted("InvalidTransactionData".to_string()));
+    }
+
+    let dest = Address::from_slice(&data[16..36]);
+    let value = U256::from_be_slice(&data[36..68]).saturating_to::<u64>();
+
+    let call = tx()
+        .balances()
+        .transfer_allow_death(MultiAddress::Id(AddressMapping::to_ss58(dest)), value);
+
+    Ok(call.encode_call_data(metadata)?)
+}
+
+/// Build `EvmAdapter::transact` with the transaction
+pub fn transact_call(
+    transaction: &EthereumTransaction,
+    metadata: &Metadata,
+) -> Result<Vec<u8>, SubEthError> {
+    evm_adapter_call("transact", transaction, metadata)
+}
+
+/// Build `EvmAdapter::transact_unsigned` with the transaction
+pub fn transact_unsigned_call(
+    transaction: &EthereumTransaction,
+    metadata: &Metadata,
+) -> Result<Vec<u8>, SubEthError> {
+    evm_adapter_call("transact_unsigned", transaction, metadata)
+}
+
+/// Build the `EvmAdapter` call taking the transaction, with the indices from the metadata
+fn evm_adapter_call(
+    name: &str,
+    transaction: &EthereumTransaction,
+    metadata: &Metadata,
+) -> Result<Vec<u8>, SubEthError> {
+    let pallet = metadata
+        .pallet_by_name("EvmAdapter")
+        .ok_or(SubEthError::Unsupported)?;
+    let call = pallet
+        .call_variant_by_name(name)
+        .ok_or(SubEthError::Unsupported)?;
+
+    let mut data = vec![pallet.index(), call.index];
+    transaction.encode_to(&mut data);
+    Ok(data)
+}
+
+/// Ethereum transaction carried by an `EvmAdapter::transact` or `transact_unsigned` call, `None`
+/// for other calls
+pub fn decode_transact(call: &[u8], metadata: &Metadata) -> Option<EthereumTransaction> {
+    let [pallet_index, call_index, args @ ..] = call else {
+        return None;
+    };
+    let mut args = args;
+    let pallet = metadata.pallet_by_name("EvmAdapter")?;
+    let variant = pallet.call_variant_by_index(*call_index)?;
+    if pallet.index() != *pallet_index
+        || !["transact", "transact_unsigned"].contains(&variant.name.as_str())
+    {
+        return None;
+    }
+
+    let transaction = EthereumTransaction::decode(&mut args).ok()?;
+    args.is_empty().then_some(transaction)
+}
+
+/// Human readable dispatch error, with pallet and error names for module errors
+pub fn dispatch_error_message(error: DispatchError, metadata: &Metadata) -> String {
+    if let DispatchError::Module(module) = error {
+        if let Some(pallet) = metadata.pallet_by_index(module.index) {
+            if let Some(variant) = pallet.error_variant_by_index(module.error[0]) {
+                return format!("{}::{}", pallet.name(), variant.name);
+            }
+        }
+    }
+
+    format!("{:?}", error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::Bytes;
+
+    fn local_metadata() -> Metadata {
+        let bytes = std::fs::read("../artifacts/local_metadata.scale").unwrap();
+        Metadata::decode(&mut &bytes[..]).unwrap()
+    }
+
+    fn request(to: Address, data: Vec<u8>) -> TransactionRequest {
+        TransactionRequest::default()
+            .to(to)
+            .input(Bytes::from(data).into())
+    }
+
+    #[test]
+    fn test_balances_transfer() {
+        let metadata = local_metadata();
+        let dest = Address::repeat_byte(0x11);
+        let mut data = TRANSFER_SELECTOR.to_vec();
+        data.extend_from_slice(&[0u8; 12]);
+        data.extend_from_slice(dest.as_slice());
+        data.extend_from_slice(&U256::from(1_000u64).to_be_bytes::<32>());
+
+        let call = dispatched_call(
+            &request(PalletContractMapping::contract_address("Balances"), data),
+            U256::ZERO,
+            &metadata,
+        )
+        .unwrap();
+
+        let expected = tx()
+            .balances()
+            .transfer_allow_death(MultiAddress::Id(AddressMapping::to_ss58(dest)), 1_000)
+            .encode_call_data(&metadata)
+            .unwrap();
+        assert_eq!(call, expected);
+    }
+
+    #[test]
+    fn test_invalid_calls_revert() {
+        let metadata = local_metadata();
+        let balances = PalletContractMapping::contract_address("Balances");
+
+        let reverted = |request, value| match dispatched_call(&request, value, &metadata) {
+            Err(SubEthError::Reverted(reason)) => reason,
+            other => panic!("expected a revert, got {:?}", other),
+        };
+
+        assert_eq!(
+            reverted(request(balances, vec![1, 2, 3, 4]), U256::ZERO),
+            "CallDecodeFailed"
+        );
+        assert_eq!(
+            reverted(request(balances, TRANSFER_SELECTOR.to_vec()), U256::ZERO),
+            "InvalidTransactionData"
+        );
+        assert_eq!(
+            reverted(request(GENERIC_CALL_ADDRESS, vec![0xff, 0xff]), U256::ZERO),
+            "CallDecodeFailed"
+        );
+        assert_eq!(
+            reverted(
+                request(PalletContractMapping::contract_address("Sudo"), vec![]),
+                U256::ZERO
+            ),
+            "UnsupportedPallet"
+        );
+        assert_eq!(
+            reverted(request(GENERIC_CALL_ADDRESS, vec![]), U256::from(1)),
+            "ValueNotAccepted"
+        );
+        assert_eq!(
+            reverted(request(Address::repeat_byte(0x11), vec![1]), U256::from(1)),
+            "InvalidTransactionData"
+        );
+    }
+
+    #[test]
+    fn test_value_transfer() {
+        let metadata = local_metadata();
+        let dest = Address::repeat_byte(0x11);
+
+        let call = dispatched_call(&request(dest, vec![]), U256::from(5_000u64), &metadata);
+
+        let expected = tx()
+            .balances()
+            .transfer_keep_alive(MultiAddress::Id(AddressMapping::to_ss58(dest)), 5_000)
+            .encode_call_data(&metadata)
+            .unwrap();
+        assert_eq!(call.unwrap(), expected);
+    }
+
+    #[test]
+    fn test_generic_call() {
+        let metadata = local_metadata();
+        let remark = tx()
+            .system()
+            .remark(b"hello".to_vec())
+            .encode_call_data(&metadata)
+            .unwrap();
+
+        let call = dispatched_call(
+            &request(GENERIC_CALL_ADDRESS, remark.clone()),
+            U256::ZERO,
+            &metadata,
+        );
+        assert_eq!(call.unwrap(), remark);
+    }
+
+    #[test]
+    fn test_transact_call() {
+        let metadata = local_metadata();
+        let transaction = EthereumTransaction {
+            transaction_type: subeth_primitives::TransactionType::Eip1559,
+            chain_id: 42,
+            nonce: 0,
+            max_priority_fee_per_gas: 0.into(),
+            max_fee_per_gas: 0.into(),
+            gas_limit: 21_000,
+            to: Default::default(),
+            value: 0.into(),
+            data: vec![],
+            access_list: vec![],
+            v: 0,
+            r: Default::default(),
+            s: Default::default(),
+        };
+
+        let call = transact_call(&transaction, &metadata).unwrap();
+        let pallet = metadata.pallet_by_name("EvmAdapter").unwrap();
+        assert_eq!(call[..2], [pallet.index(), 0]);
+        assert_eq!(call[2..], transaction.encode());
+
+        // the local metadata predates `transact_unsigned`
+        assert!(matches!(
+            transact_unsigned_call(&transaction, &metadata),
+            Err(SubEthError::Unsupported)
+        ));
+
+        assert_eq!(decode_transact(&call, &metadata), Some(transaction));
+        assert_eq!(decode_transact(&call[..call.len() - 1], &metadata), None);
+        let remark = tx().system().remark(call[2..].to_vec());
+        let remark = remark.encode_call_data(&metadata).unwrap();
+        assert_eq!(decode_transact(&remark, &metadata), None);
+    }
+
+    #[test]
+    fn test_decode_dispatch_info() {
+        let weight = Weight::from_parts(1_000_000, 1_000);
+        for (fee, encoded_fee) in [(42u128, 42u64.encode()), (u128::MAX, u128::MAX.encode())] {
+            let mut bytes = (weight, DispatchClass::Normal).encode();
+            bytes.extend(encoded_fee);
+
+            let info = RuntimeDispatchInfo::decode(&mut &bytes[..]).unwrap();
+            assert_eq!(info.weight, weight);
+            assert_eq!(info.partial_fee, RuntimeBalance(fee));
+        }
+    }
+
+    #[test]
+    fn test_decode_evm_dry_run() {
+        let weight = Weight::from_parts(1_000_000, 1_000);
+        let error = DispatchError::Other("");
+        // the events follow
+        let mut bytes = (Err::<(), _>(error), weight, 21_000u64).encode();
+        bytes.extend(vec![0u8; 8].encode());
+
+        let result = EvmDryRunResult::decode(&mut &bytes[..]).unwrap();
+        assert_eq!(result.result, Err(error));
+        assert_eq!(result.weight, weight);
+        assert_eq!(result.gas_required, 21_000);
+    }
+
+    #[test]
+    fn test_dispatch_error_message() {
+        let metadata = local_metadata();
+        let balances = metadata.pallet_by_name("Balances").unwrap();
+        let error = DispatchError::Module(frame_support::sp_runtime::ModuleError {
+            index: balances.index(),
+            error: [2, 0, 0, 0],
+            message: None,
+        });
+
+        let expected = format!(
+            "Balances::{}",
+            balances.error_variant_by_index(2).unwrap().name
+        );
+        assert_eq!(dispatch_error_message(error, &metadata), expected);
+        assert_eq!(
+            dispatch_error_message(DispatchError::BadOrigin, &metadata),
+            "BadOrigin"
+        );
+    }
+}
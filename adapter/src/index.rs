@@ -0,0 +1,307 @@
+//! Persistent transaction index
+//!
+//! Maps extrinsic hashes, and the Ethereum hashes of the transactions they carry, to their position
+//! in the chain, so that transactions can be looked up by hash (`eth_getTransactionByHash`). The
+//! index is kept in an embedded [`redb`] database under the adapter's data directory and survives
+//! restarts. Blocks older than the retention depth are pruned as new blocks are indexed.
+
+use parity_scale_codec::{Decode, Encode};
+use redb::{backends::InMemoryBackend, Database, ReadableTable, TableDefinition};
+use sp_core::H256;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Default number of blocks to keep in the index
+pub const DEFAULT_RETENTION: u64 = 100_800;
+
+/// Name of the database file inside the data directory
+const DB_FILE: &str = "tx_index.redb";
+
+/// Extrinsic or Ethereum transaction hash -> SCALE-encoded [`TxLocation`]
+const TXS: TableDefinition<&[u8; 32], &[u8]> = TableDefinition::new("txs");
+/// Block number -> SCALE-encoded indexed block
+const BLOCKS: TableDefinition<u64, &[u8]> = TableDefinition::new("blocks");
+
+/// Location of an extrinsic in the chain
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode)]
+pub struct TxLocation {
+    /// Hash of the block that includes the extrinsic
+    pub block_hash: H256,
+    /// Number of the block that includes the extrinsic
+    pub block_number: u64,
+    /// Index of the extrinsic in the block
+    pub index: u32,
+}
+
+/// A block as recorded in the index, used for pruning and reorg handling
+#[derive(Encode, Decode)]
+struct IndexedBlock {
+    hash: H256,
+    tx_hashes: Vec<H256>,
+}
+
+/// Thread-safe handle to the transaction index
+#[derive(Clone)]
+pub struct TxIndex {
+    db: Arc<Database>,
+    /// Number of blocks to keep, `0` keeps everything
+    retention: u64,
+}
+
+#[allow(clippy::result_large_err)]
+impl TxIndex {
+    /// Open (or create) the index inside `data_dir`
+    pub fn open(data_dir: &Path, retention: u64) -> Result<Self, redb::Error> {
+        std::fs::create_dir_all(data_dir).map_err(redb::StorageError::Io)?;
+        let db = Database::create(data_dir.join(DB_FILE))?;
+        Self::init(db, retention)
+    }
+
+    /// Create a non-persistent index, used when no data directory is configured
+    pub fn in_memory(retention: u64) -> Result<Self, redb::Error> {
+        let db = Database::builder().create_with_backend(InMemoryBackend::new())?;
+        Self::init(db, retention)
+    }
+
+    fn init(db: Database, retention: u64) -> Result<Self, redb::Error> {
+        // make sure tables exist, so that readers don't fail on a fresh database
+        let tx = db.begin_write()?;
+        tx.open_table(TXS)?;
+        tx.open_table(BLOCKS)?;
+        tx.commit()?;
+
+        Ok(Self {
+            db: Arc::new(db),
+            retention,
+        })
+    }
+
+    /// Index all extrinsics of a block, given their hashes in order, and the Ethereum hashes of
+    /// the transactions carried by some of them, with the extrinsic's index
+    ///
+    /// Re-indexing a block number with a different hash (i.e. after a reorg) replaces the
+    /// entries of the previously indexed block.
+    pub fn insert_block(
+        &self,
+        block_number: u64,
+        block_hash: H256,
+        tx_hashes: Vec<H256>,
+        eth_hashes: Vec<(H256, u32)>,
+    ) -> Result<(), redb::Error> {
+        let tx = self.db.begin_write()?;
+        {
+            let mut txs = tx.open_table(TXS)?;
+            let mut blocks = tx.open_table(BLOCKS)?;
+
+            let previous = blocks
+                .get(block_number)?
+                .and_then(|v| IndexedBlock::decode(&mut v.value()).ok());
+            if let Some(previous) = previous {
+                if previous.hash == block_hash {
+                    return Ok(());
+                }
+                for hash in previous.tx_hashes {
+                    remove_tx(&mut txs, &hash, previous.hash)?;
+                }
+            }
+
+            let indexed = tx_hashes
+                .into_iter()
+                .enumerate()
+                .map(|(index, hash)| (hash, index as u32))
+                .chain(eth_hashes)
+                .collect::<Vec<_>>();
+            for (hash, index) in &indexed {
+                let location = TxLocation {
+                    block_hash,
+                    block_number,
+                    index: *index,
+                };
+                txs.insert(hash.as_fixed_bytes(), location.encode().as_slice())?;
+            }
+
+            let block = IndexedBlock {
+                hash: block_hash,
+                tx_hashes: indexed.into_iter().map(|(hash, _)| hash).collect(),
+            };
+            blocks.insert(block_number, block.encode().as_slice())?;
+
+            if self.retention > 0 && block_number >= self.retention {
+                let cutoff = block_number - self.retention;
+                let pruned = blocks.extract_from_if(..=cutoff, |_, _| true)?;
+                for entry in pruned {
+                    let (_, value) = entry?;
+                    if let Ok(block) = IndexedBlock::decode(&mut value.value()) {
+                        for hash in block.tx_hashes {
+                            remove_tx(&mut txs, &hash, block.hash)?;
+                        }
+                    }
+                }
+            }
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Find the location of an extrinsic by its hash
+    pub fn get(&self, tx_hash: &H256) -> Result<Option<TxLocation>, redb::Error> {
+        let tx = self.db.begin_read()?;
+        let txs = tx.open_table(TXS)?;
+
+        Ok(txs
+            .get(tx_hash.as_fixed_bytes())?
+            .and_then(|v| TxLocation::decode(&mut v.value()).ok()))
+    }
+
+    /// Hash of the block indexed at the given number, if any
+    pub fn block_hash(&self, block_number: u64) -> Result<Option<H256>, redb::Error> {
+        let tx = self.db.begin_read()?;
+        let blocks = tx.open_table(BLOCKS)?;
+
+        Ok(blocks
+            .get(block_number)?
+            .and_then(|v| IndexedBlock::decode(&mut v.value()).ok())
+            .map(|block| block.hash))
+    }
+
+    /// Highest indexed block number
+    pub fn last_indexed(&self) -> Result<Option<u64>, redb::Error> {
+        let tx = self.db.begin_read()?;
+        let blocks = tx.open_table(BLOCKS)?;
+
+        let last = blocks.last()?.map(|(number, _)| number.value());
+        Ok(last)
+    }
+}
+
+/// Remove the entry of a hash if it still locates it in the given block, the hash may have been
+/// indexed again in another block since (a resubmitted transaction, or the same extrinsic on
+/// another fork)
+#[allow(clippy::result_large_err)]
+fn remove_tx(
+    txs: &mut redb::Table<&[u8; 32], &[u8]>,
+    hash: &H256,
+    block_hash: H256,
+) -> Result<(), redb::Error> {
+    let location = txs
+        .get(hash.as_fixed_bytes())?
+        .and_then(|v| TxLocation::decode(&mut v.value()).ok());
+    if location.is_some_and(|location| location.block_hash == block_hash) {
+        txs.remove(hash.as_fixed_bytes())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(n: u8) -> H256 {
+        H256::repeat_byte(n)
+    }
+
+    #[test]
+    fn test_insert_and_get() {
+        let index = TxIndex::in_memory(0).unwrap();
+        index
+            .insert_block(1, hash(1), vec![hash(10), hash(11)], vec![])
+            .unwrap();
+
+        assert_eq!(
+            index.get(&hash(11)).unwrap(),
+            Some(TxLocation {
+                block_hash: hash(1),
+                block_number: 1,
+                index: 1,
+            })
+        );
+        assert_eq!(index.get(&hash(12)).unwrap(), None);
+        assert_eq!(index.block_hash(1).unwrap(), Some(hash(1)));
+        assert_eq!(index.last_indexed().unwrap(), Some(1));
+    }
+
+    #[test]
+    fn test_ethereum_hashes() {
+        let index = TxIndex::in_memory(2).unwrap();
+        index
+            .insert_block(1, hash(1), vec![hash(10), hash(11)], vec![(hash(20), 1)])
+            .unwrap();
+
+        assert_eq!(index.get(&hash(20)).unwrap(), index.get(&hash(11)).unwrap());
+
+        // pruned with the block
+        index.insert_block(3, hash(3), vec![], vec![]).unwrap();
+        assert_eq!(index.get(&hash(20)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_reorg_replaces_block() {
+        let index = TxIndex::in_memory(0).unwrap();
+        index
+            .insert_block(5, hash(1), vec![hash(10)], vec![])
+            .unwrap();
+        index
+            .insert_block(5, hash(2), vec![hash(20)], vec![])
+            .unwrap();
+
+        assert_eq!(index.get(&hash(10)).unwrap(), None);
+        assert_eq!(index.get(&hash(20)).unwrap().unwrap().block_hash, hash(2));
+    }
+
+    #[test]
+    fn test_retention_prunes_old_blocks() {
+        let index = TxIndex::in_memory(2).unwrap();
+        for n in 1..=4u8 {
+            index
+                .insert_block(n as u64, hash(n), vec![hash(n + 100)], vec![])
+                .unwrap();
+        }
+
+        assert_eq!(index.get(&hash(101)).unwrap(), None);
+        assert_eq!(index.get(&hash(102)).unwrap(), None);
+        assert!(index.get(&hash(103)).unwrap().is_some());
+        assert!(index.get(&hash(104)).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_pruning_keeps_reindexed_hashes() {
+        let index = TxIndex::in_memory(2).unwrap();
+        index
+            .insert_block(1, hash(1), vec![hash(100)], vec![])
+            .unwrap();
+        // the same hash included again in a later block
+        index
+            .insert_block(2, hash(2), vec![hash(100)], vec![])
+            .unwrap();
+        index.insert_block(3, hash(3), vec![], vec![]).unwrap();
+
+        assert_eq!(index.get(&hash(100)).unwrap().unwrap().block_hash, hash(2));
+
+        // nor does replacing a reorged block
+        index
+            .insert_block(4, hash(4), vec![hash(101)], vec![])
+            .unwrap();
+        index
+            .insert_block(5, hash(5), vec![hash(101)], vec![])
+            .unwrap();
+        index.insert_block(4, hash(6), vec![], vec![]).unwrap();
+        assert_eq!(index.get(&hash(101)).unwrap().unwrap().block_hash, hash(5));
+    }
+
+    #[test]
+    fn test_survives_reopen() {
+        let dir = std::env::temp_dir().join(format!("subeth-index-{}", std::process::id()));
+        {
+            let index = TxIndex::open(&dir, 0).unwrap();
+            index
+                .insert_block(7, hash(7), vec![hash(70)], vec![])
+                .unwrap();
+        }
+        let index = TxIndex::open(&dir, 0).unwrap();
+        assert_eq!(index.get(&hash(70)).unwrap().unwrap().block_number, 7);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
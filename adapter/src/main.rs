@@ -10,12 +10,25 @@
 mod adapter;
 mod cache;
 mod command;
+mod erc20;
+mod erc721;
+mod events;
+mod fee;
+mod filter;
+mod gas;
+mod index;
+mod properties;
+mod raw_tx;
+mod receipt;
+mod relayer;
 mod server;
+mod storage_view;
 mod sub_client;
 #[cfg(test)]
 mod tests;
 mod traits;
 mod types;
+mod units;
 
 use crate::sub_client::SubLightClient;
 use clap::Parser;
@@ -0,0 +1,152 @@
+//! Chain properties
+//!
+//! Native token decimals and symbol, and the SS58 address prefix, as reported by the node's
+//! `system_properties` (the chain spec properties). Any of them can be overridden from the command
+//! line.
+
+use serde_json::{Map, Value};
+
+/// Decimals used when the chain doesn't report any
+pub const DEFAULT_DECIMALS: u32 = 0;
+/// Symbol used when the chain doesn't report any
+pub const DEFAULT_SYMBOL: &str = "UNIT";
+
+/// Properties of the chain
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Properties {
+    /// Decimals of the token
+    #[serde(rename = "tokenDecimals")]
+    pub decimals: u32,
+    /// Symbol of the token
+    #[serde(rename = "tokenSymbol")]
+    pub symbol: String,
+    /// SS58 address prefix
+    pub ss58_format: Option<u16>,
+}
+
+impl Default for Properties {
+    fn default() -> Self {
+        Self {
+            decimals: DEFAULT_DECIMALS,
+            symbol: DEFAULT_SYMBOL.to_string(),
+            ss58_format: None,
+        }
+    }
+}
+
+impl Properties {
+    /// Parse the result of `system_properties`
+    ///
+    /// Multi-token chains report arrays of decimals and symbols, the first (native) token is used.
+    pub fn from_system_properties(properties: &Map<String, Value>) -> Self {
+        let first = |key: &str| match properties.get(key) {
+            Some(Value::Array(values)) => values.first().cloned(),
+            value => value.cloned(),
+        };
+
+        let default = Self::default();
+        Self {
+            decimals: first("tokenDecimals")
+                .and_then(|v| v.as_u64())
+                .map_or(default.decimals, |v| v as u32),
+            symbol: first("tokenSymbol")
+                .and_then(|v| v.as_str().map(ToString::to_string))
+                .unwrap_or(default.symbol),
+            ss58_format: properties
+                .get("ss58Format")
+                .and_then(|v| v.as_u64())
+                .map(|v| v as u16),
+        }
+    }
+}
+
+/// Properties set on the command line, taking precedence over the chain's
+#[derive(Debug, Clone, Default)]
+pub struct PropertiesOverride {
+    pub decimals: Option<u32>,
+    pub symbol: Option<String>,
+    pub ss58_format: Option<u16>,
+}
+
+impl PropertiesOverride {
+    /// Apply the overrides to the properties reported by the chain
+    pub fn apply(&self, properties: Properties) -> Properties {
+        Properties {
+            decimals: self.decimals.unwrap_or(properties.decimals),
+            symbol: self.symbol.clone().unwrap_or(properties.symbol),
+            ss58_format: self.ss58_format.or(properties.ss58_format),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(value: Value) -> Properties {
+        Properties::from_system_properties(value.as_object().unwrap())
+    }
+
+    #[test]
+    fn test_from_system_properties() {
+        assert_eq!(
+            parse(json!({"ss58Format": 0, "tokenDecimals": 10, "tokenSymbol": "DOT"})),
+            Properties {
+                decimals: 10,
+                symbol: "DOT".to_string(),
+                ss58_format: Some(0),
+            }
+        );
+
+        // dev chain
+        assert_eq!(
+            parse(json!({"tokenDecimals": 0, "tokenSymbol": "MINI"})),
+            Properties {
+                decimals: 0,
+                symbol: "MINI".to_string(),
+                ss58_format: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_multi_token_and_missing_properties() {
+        let properties = parse(json!({
+            "ss58Format": 8,
+            "tokenDecimals": [12, 12],
+            "tokenSymbol": ["KAR", "KUSD"]
+        }));
+        assert_eq!(properties.decimals, 12);
+        assert_eq!(properties.symbol, "KAR");
+
+        assert_eq!(parse(json!({})), Properties::default());
+    }
+
+    #[test]
+    fn test_override() {
+        let overrides = PropertiesOverride {
+            decimals: Some(18),
+            ..Default::default()
+        };
+        let properties = overrides.apply(parse(json!({"tokenDecimals": 0, "tokenSymbol": "MINI"})));
+
+        assert_eq!(properties.decimals, 18);
+        assert_eq!(properties.symbol, "MINI");
+    }
+
+    #[test]
+    fn test_rpc_format() {
+        let properties = Properties {
+            decimals: 10,
+            symbol: "DOT".to_string(),
+            ss58_format: Some(0),
+        };
+
+        assert_eq!(
+            serde_json::to_value(properties).unwrap(),
+            json!({"tokenDecimals": 10, "tokenSymbol": "DOT", "ss58Format": 0})
+        );
+    }
+}
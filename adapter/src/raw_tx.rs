@@ -0,0 +1,312 @@
+//! Raw transactions (`eth_sendRawTransaction`)
+//!
+//! Wallets send EIP-2718 envelopes: RLP encoded legacy transactions, or typed EIP-2930 and EIP-1559
+//! transactions. The envelope is decoded, the sender is recovered from the signature, and the
+//! transaction is converted to the [`EthereumTransaction`] submitted to `pallet_evm_adapter`, which
+//! recovers the sender again from the same signing payload.
+//!
+//! Legacy transactions must be signed with EIP-155 replay protection, and every transaction must be
+//! signed for the adapter's chain id.
+
+use crate::types::SubEthError;
+use alloy_consensus::{Transaction, TxEnvelope};
+use alloy_eips::eip2718::Decodable2718;
+use alloy_primitives::{Address, ChainId, PrimitiveSignature, TxKind, U256};
+use subeth_primitives::{conversions::*, EthereumTransaction, TransactionType};
+
+/// A decoded raw transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawTransaction {
+    /// Recovered sender
+    pub sender: Address,
+    /// Transaction to submit to the adapter pallet
+    pub transaction: EthereumTransaction,
+}
+
+/// Decode a raw transaction, recover its sender and check it's signed for `chain_id`
+pub fn decode_raw_transaction(
+    mut bytes: &[u8],
+    chain_id: ChainId,
+) -> Result<RawTransaction, SubEthError> {
+    let envelope = TxEnvelope::decode_2718(&mut bytes)
+        .map_err(|e| SubEthError::InvalidParams(format!("invalid transaction: {}", e)))?;
+    if !bytes.is_empty() {
+        return Err(SubEthError::InvalidParams(
+            "invalid transaction: trailing bytes".to_string(),
+        ));
+    }
+
+    match envelope.chain_id() {
+        Some(id) if id == chain_id => {}
+        Some(_) => {
+            return Err(SubEthError::InvalidTransaction(
+                "invalid chain id for signer".to_string(),
+            ))
+        }
+        None => {
+            return Err(SubEthError::InvalidTransaction(
+                "only replay-protected (EIP-155) transactions allowed over RPC".to_string(),
+            ))
+        }
+    }
+
+    let sender = envelope
+        .recover_signer()
+        .map_err(|_| SubEthError::InvalidTransaction("invalid sender".to_string()))?;
+
+    let transaction = match &envelope {
+        TxEnvelope::Legacy(tx) => convert(TransactionType::Legacy, tx.tx(), tx.signature()),
+        TxEnvelope::Eip2930(tx) => convert(TransactionType::Eip2930, tx.tx(), tx.signature()),
+        TxEnvelope::Eip1559(tx) => convert(TransactionType::Eip1559, tx.tx(), tx.signature()),
+        _ => Err(SubEthError::InvalidTransaction(
+            "transaction type not supported".to_string(),
+        )),
+    }?;
+    // the pallet rejects the malleable signatures that `recover_signer` accepts
+    if !transaction.has_low_s() {
+        return Err(SubEthError::InvalidTransaction(
+            "invalid transaction signature: s is above secp256k1n/2 (EIP-2)".to_string(),
+        ));
+    }
+
+    Ok(RawTransaction {
+        sender,
+        transaction,
+    })
+}
+
+/// Convert a signed transaction to the pallet's representation
+///
+/// Legacy and EIP-2930 transactions use their gas price as both max fee and max priority fee.
+fn convert<T: Transaction>(
+    transaction_type: TransactionType,
+    tx: &T,
+    signature: &PrimitiveSignature,
+) -> Result<EthereumTransaction, SubEthError> {
+    let to = match tx.kind() {
+        TxKind::Call(to) => to,
+        TxKind::Create => {
+            return Err(SubEthError::InvalidTransaction(
+                "contract creation is not supported".to_string(),
+            ))
+        }
+    };
+    let max_fee_per_gas = tx.max_fee_per_gas();
+    let max_priority_fee_per_gas = tx.max_priority_fee_per_gas().unwrap_or(max_fee_per_gas);
+
+    Ok(EthereumTransaction {
+        transaction_type,
+        chain_id: tx.chain_id().unwrap_or_default(),
+        nonce: tx.nonce(),
+        max_priority_fee_per_gas: alloy_u256_to_sp_u256(U256::from(max_priority_fee_per_gas)),
+        max_fee_per_gas: alloy_u256_to_sp_u256(U256::from(max_fee_per_gas)),
+        gas_limit: tx.gas_limit(),
+        to: alloy_address_to_h160(to),
+        value: alloy_u256_to_sp_u256(tx.value()),
+        data: tx.input().to_vec(),
+        access_list: tx
+            .access_list()
+            .map(|list| {
+                list.iter()
+                    .map(|item| {
+                        let keys = item
+                            .storage_keys
+                            .iter()
+                            .map(|key| alloy_b256_to_h256(*key))
+                            .collect();
+                        (alloy_address_to_h160(item.address), keys)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        v: signature.v() as u64,
+        r: alloy_b256_to_h256(signature.r().into()),
+        s: alloy_b256_to_h256(signature.s().into()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_consensus::{SignableTransaction, TxEip1559, TxEip2930, TxLegacy};
+    use alloy_eips::eip2718::Encodable2718;
+    use alloy_eips::eip2930::{AccessList, AccessListItem};
+    use alloy_primitives::{address, hex, keccak256, Bytes, B256};
+    use sp_core::Pair;
+
+    /// Private key and transaction of the EIP-155 example
+    const KEY: [u8; 32] = [0x46; 32];
+    const SENDER: Address = address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F");
+    const EIP155_EXAMPLE: &str = "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83";
+
+    fn sign<T: SignableTransaction<PrimitiveSignature>>(tx: T) -> Vec<u8>
+    where
+        TxEnvelope: From<alloy_consensus::Signed<T>>,
+    {
+        let pair = sp_core::ecdsa::Pair::from_seed(&KEY);
+        let signature = pair.sign_prehashed(&tx.signature_hash().0);
+        let signature = PrimitiveSignature::try_from(&signature.0[..]).unwrap();
+
+        TxEnvelope::from(tx.into_signed(signature)).encoded_2718()
+    }
+
+    fn signing_hash(raw: &[u8]) -> B256 {
+        TxEnvelope::decode_2718(&mut &raw[..])
+            .unwrap()
+            .signature_hash()
+    }
+
+    #[test]
+    fn test_eip155_example() {
+        let raw = hex::decode(EIP155_EXAMPLE).unwrap();
+        let decoded = decode_raw_transaction(&raw, 1).unwrap();
+
+        assert_eq!(decoded.sender, SENDER);
+        let tx = decoded.transaction;
+        assert_eq!(tx.transaction_type, TransactionType::Legacy);
+        assert_eq!(tx.nonce, 9);
+        assert_eq!(tx.max_fee_per_gas, 20_000_000_000u64.into());
+        assert_eq!(tx.max_priority_fee_per_gas, 20_000_000_000u64.into());
+        assert_eq!(tx.gas_limit, 21_000);
+        assert_eq!(tx.value, 1_000_000_000_000_000_000u64.into());
+        assert_eq!(
+            tx.message_hash(),
+            hex!("daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53")
+        );
+        assert_eq!(tx.signature().unwrap()[64], 0);
+        assert_eq!(tx.encoded_2718(), raw);
+    }
+
+    #[test]
+    fn test_eip1559() {
+        let raw = sign(TxEip1559 {
+            chain_id: 42,
+            nonce: 7,
+            gas_limit: 100_000,
+            max_fee_per_gas: 2_000_000_000,
+            max_priority_fee_per_gas: 1_000_000,
+            to: TxKind::Call(Address::repeat_byte(0x11)),
+            value: U256::from(12_345),
+            access_list: AccessList(vec![AccessListItem {
+                address: Address::repeat_byte(0x22),
+                storage_keys: vec![B256::repeat_byte(0x33), B256::ZERO],
+            }]),
+            input: Bytes::from(vec![0xa9, 0x05, 0x9c, 0xbb, 0x01]),
+        });
+
+        let decoded = decode_raw_transaction(&raw, 42).unwrap();
+        assert_eq!(decoded.sender, SENDER);
+
+        let tx = decoded.transaction;
+        assert_eq!(tx.transaction_type, TransactionType::Eip1559);
+        assert_eq!(tx.max_priority_fee_per_gas, 1_000_000u64.into());
+        assert_eq!(tx.max_fee_per_gas, 2_000_000_000u64.into());
+        assert_eq!(tx.data, vec![0xa9, 0x05, 0x9c, 0xbb, 0x01]);
+        assert_eq!(tx.access_list.len(), 1);
+        assert_eq!(tx.access_list[0].1.len(), 2);
+        assert_eq!(tx.message_hash(), signing_hash(&raw).0);
+        assert_eq!(tx.hash().0, keccak256(&raw).0);
+    }
+
+    #[test]
+    fn test_eip2930() {
+        let raw = sign(TxEip2930 {
+            chain_id: 42,
+            nonce: 1,
+            gas_price: 1_000_000_000,
+            gas_limit: 50_000,
+            to: TxKind::Call(Address::repeat_byte(0x11)),
+            value: U256::ZERO,
+            access_list: AccessList(vec![AccessListItem {
+                address: Address::repeat_byte(0x22),
+                storage_keys: vec![],
+            }]),
+            input: Bytes::new(),
+        });
+
+        let tx = decode_raw_transaction(&raw, 42).unwrap().transaction;
+        assert_eq!(tx.transaction_type, TransactionType::Eip2930);
+        assert_eq!(tx.max_fee_per_gas, 1_000_000_000u64.into());
+        assert_eq!(tx.max_priority_fee_per_gas, 1_000_000_000u64.into());
+        assert_eq!(tx.message_hash(), signing_hash(&raw).0);
+        assert_eq!(tx.hash().0, keccak256(&raw).0);
+    }
+
+    #[test]
+    fn test_legacy_with_large_chain_id() {
+        let raw = sign(TxLegacy {
+            chain_id: Some(420_420_420),
+            nonce: 3,
+            gas_price: 1,
+            gas_limit: 21_000,
+            to: TxKind::Call(Address::repeat_byte(0x11)),
+            value: U256::from(1),
+            input: Bytes::new(),
+        });
+
+        let decoded = decode_raw_transaction(&raw, 420_420_420).unwrap();
+        assert_eq!(decoded.sender, SENDER);
+        assert_eq!(decoded.transaction.message_hash(), signing_hash(&raw).0);
+        assert_eq!(decoded.transaction.hash().0, keccak256(&raw).0);
+    }
+
+    #[test]
+    fn test_rejected_transactions() {
+        let legacy = TxLegacy {
+            chain_id: None,
+            gas_limit: 21_000,
+            to: TxKind::Call(Address::repeat_byte(0x11)),
+            ..Default::default()
+        };
+
+        let rejected = |raw: &[u8], chain_id| match decode_raw_transaction(raw, chain_id) {
+            Err(SubEthError::InvalidTransaction(reason) | SubEthError::InvalidParams(reason)) => {
+                reason
+            }
+            other => panic!("expected an error, got {:?}", other),
+        };
+
+        // not replay protected
+        assert_eq!(
+            rejected(&sign(legacy.clone()), 1),
+            "only replay-protected (EIP-155) transactions allowed over RPC"
+        );
+        // signed for another chain
+        let raw = hex::decode(EIP155_EXAMPLE).unwrap();
+        assert_eq!(rejected(&raw, 42), "invalid chain id for signer");
+        // contract creation
+        let create = TxLegacy {
+            chain_id: Some(1),
+            to: TxKind::Create,
+            ..legacy.clone()
+        };
+        assert_eq!(
+            rejected(&sign(create), 1),
+            "contract creation is not supported"
+        );
+        // the same transaction with the high `s` of its signature
+        let tx = TxLegacy {
+            chain_id: Some(1),
+            ..legacy
+        };
+        let signed = TxEnvelope::decode_2718(&mut &sign(tx.clone())[..]).unwrap();
+        let signature = signed.signature();
+        let n = U256::from_be_bytes(hex!(
+            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141"
+        ));
+        let high_s = PrimitiveSignature::new(signature.r(), n - signature.s(), !signature.v());
+        let raw = TxEnvelope::from(tx.into_signed(high_s)).encoded_2718();
+        assert_eq!(
+            rejected(&raw, 1),
+            "invalid transaction signature: s is above secp256k1n/2 (EIP-2)"
+        );
+        // not a transaction
+        assert!(rejected(&[0x02, 0x01, 0x02], 1).starts_with("invalid transaction"));
+        let mut trailing = raw.clone();
+        trailing.push(0);
+        assert_eq!(
+            rejected(&trailing, 1),
+            "invalid transaction: trailing bytes"
+        );
+    }
+}
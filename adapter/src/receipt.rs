@@ -0,0 +1,283 @@
+//! Transaction receipts
+//!
+//! Substrate has no receipts, so they are rebuilt from the block's events:
+//!
+//! - `System.ExtrinsicSuccess` / `System.ExtrinsicFailed` give the status and the dispatch weight.
+//!   An extrinsic of the EVM adapter pallet that succeeds but emits `EvmAdapter.TransactionFailed`
+//!   carried a transaction whose call failed, and its status is failed too
+//! - `EvmAdapter.TransactionExecuted` / `EvmAdapter.TransactionFailed` give the gas used by an
+//!   Ethereum transaction and the gas price its signer paid. Other extrinsics use the gas of their
+//!   dispatch weight, and `TransactionPayment.TransactionFeePaid` gives the fee that was paid
+//! - every other event emitted by the extrinsic becomes a log, followed by the ERC-20 log of the
+//!   native token or the asset for transfers and approvals (see [`crate::erc20`]), or the ERC-721
+//!   log of the collection for transfers of items (see [`crate::erc721`])
+
+use crate::adapter::AddressBook;
+use crate::erc20::{asset_log, erc20_log, ASSETS_PALLET};
+use crate::erc721::{nft_log, NFTS_PALLET};
+use crate::events::{event_to_log, named_event_fields};
+use crate::types::*;
+use crate::units::Units;
+use alloy_consensus::{Eip658Value, Receipt, ReceiptEnvelope, ReceiptWithBloom, Transaction};
+use alloy_primitives::{logs_bloom, U256};
+use alloy_rpc_types_eth::{Block as EthBlock, Log, TransactionReceipt};
+use std::collections::BTreeMap;
+use subxt::events::{Events, Phase};
+use subxt::Metadata;
+
+/// Amount of `ref_time` weight that corresponds to one unit of gas
+pub const WEIGHT_PER_GAS: u64 = 20_000;
+
+/// Pallet executing Ethereum transactions
+pub const EVM_ADAPTER_PALLET: &str = "EvmAdapter";
+
+/// Outcome of a single extrinsic, collected from the block events
+#[derive(Default)]
+struct ExtrinsicOutcome {
+    success: bool,
+    /// The extrinsic carried an Ethereum transaction whose call failed
+    reverted: bool,
+    /// Dispatch weight (`ref_time`)
+    weight: u64,
+    /// Fee paid, in the chain's native token
+    fee: u128,
+    /// Gas used and gas price of the extrinsic's Ethereum transaction, see [`transaction_gas`]
+    gas: Option<(u64, U256)>,
+    logs: Vec<alloy_primitives::Log>,
+}
+
+/// Convert `ref_time` weight to gas
+pub fn weight_to_gas(ref_time: u64) -> u64 {
+    ref_time / WEIGHT_PER_GAS
+}
+
+/// Gas used and gas price (in the native token's smallest unit) of an Ethereum transaction, from
+/// the fields of its `EvmAdapter.TransactionExecuted` or `EvmAdapter.TransactionFailed` event
+///
+/// `None` for runtimes whose events don't have them.
+pub fn transaction_gas(fields: &BTreeMap<String, Vec<u8>>) -> Option<(u64, U256)> {
+    let gas_used = u64::from_le_bytes(fields.get("gas_used")?.as_slice().try_into().ok()?);
+    let gas_price = fields
+        .get("gas_price")
+        .filter(|scale| scale.len() <= 32)
+        .map(|scale| U256::from_le_slice(scale))?;
+    Some((gas_used, gas_price))
+}
+
+/// Build receipts for all transactions of a block
+///
+/// `addresses` holds the addresses of the accounts in the events, see
+/// [`crate::events::event_accounts`].
+pub fn block_receipts(
+    block: &EthBlock,
+    events: &Events<ChainConfig>,
+    metadata: &Metadata,
+    addresses: &AddressBook,
+    units: Units,
+) -> Result<Vec<TransactionReceipt>, SubEthError> {
+    let transactions = block.transactions.txns().collect::<Vec<_>>();
+    let outcomes = extrinsic_outcomes(transactions.len(), events, metadata, addresses)?;
+
+    let mut cumulative_gas_used = 0u64;
+    let mut log_index = 0u64;
+    let mut receipts = Vec::with_capacity(transactions.len());
+
+    for (tx, outcome) in transactions.into_iter().zip(outcomes) {
+        let (gas_used, effective_gas_price) = match outcome.gas {
+            // what the transaction's signer paid for, the relayer pays the extrinsic's fee
+            Some((gas_used, gas_price)) => (gas_used, units.to_wei(gas_price).saturating_to()),
+            None => {
+                let gas_used = weight_to_gas(outcome.weight);
+                let effective_gas_price = if gas_used > 0 {
+                    let fee = units.to_wei(U256::from(outcome.fee));
+                    u128::try_from(fee / U256::from(gas_used)).unwrap_or(u128::MAX)
+                } else {
+                    0
+                };
+                (gas_used, effective_gas_price)
+            }
+        };
+        cumulative_gas_used += gas_used;
+
+        let bloom = logs_bloom(outcome.logs.iter());
+        let logs = outcome
+            .logs
+            .into_iter()
+            .map(|inner| {
+                let log = Log {
+                    inner,
+                    block_hash: Some(block.header.hash),
+                    block_number: Some(block.header.number),
+                    block_timestamp: Some(block.header.timestamp),
+                    transaction_hash: Some(*tx.inner.tx_hash()),
+                    transaction_index: tx.transaction_index,
+                    log_index: Some(log_index),
+                    removed: false,
+                };
+                log_index += 1;
+                log
+            })
+            .collect();
+
+        let receipt = ReceiptWithBloom {
+            receipt: Receipt {
+                status: Eip658Value::Eip658(outcome.success && !outcome.reverted),
+                cumulative_gas_used,
+                logs,
+            },
+            logs_bloom: bloom,
+        };
+
+        receipts.push(TransactionReceipt {
+            inner: ReceiptEnvelope::Eip1559(receipt),
+            transaction_hash: *tx.inner.tx_hash(),
+            transaction_index: tx.transaction_index,
+            block_hash: Some(block.header.hash),
+            block_number: Some(block.header.number),
+            gas_used,
+            effective_gas_price,
+            blob_gas_used: None,
+            blob_gas_price: None,
+            from: tx.from,
+            to: tx.to(),
+            contract_address: None,
+        });
+    }
+
+    Ok(receipts)
+}
+
+/// Outcomes of the block's `count` extrinsics, from their events
+fn extrinsic_outcomes(
+    count: usize,
+    events: &Events<ChainConfig>,
+    metadata: &Metadata,
+    addresses: &AddressBook,
+) -> Result<Vec<ExtrinsicOutcome>, SubEthError> {
+    let mut outcomes = (0..count)
+        .map(|_| ExtrinsicOutcome::default())
+        .collect::<Vec<_>>();
+
+    for event in events.iter() {
+        let event = event?;
+        let Phase::ApplyExtrinsic(index) = event.phase() else {
+            continue;
+        };
+        let Some(outcome) = outcomes.get_mut(index as usize) else {
+            continue;
+        };
+
+        match (event.pallet_name(), event.variant_name()) {
+            ("System", "ExtrinsicSuccess") => {
+                outcome.success = true;
+                if let Some(ev) = event.as_event::<system::events::ExtrinsicSuccess>()? {
+                    outcome.weight = ev.dispatch_info.weight.ref_time;
+                }
+            }
+            ("System", "ExtrinsicFailed") => {
+                outcome.success = false;
+                if let Some(ev) = event.as_event::<system::events::ExtrinsicFailed>()? {
+                    outcome.weight = ev.dispatch_info.weight.ref_time;
+                }
+            }
+            ("TransactionPayment", "TransactionFeePaid") => {
+                if let Some(ev) =
+                    event.as_event::<transaction_payment::events::TransactionFeePaid>()?
+                {
+                    outcome.fee = ev.actual_fee.into();
+                }
+            }
+            (EVM_ADAPTER_PALLET, variant @ ("TransactionExecuted" | "TransactionFailed")) => {
+                outcome.reverted |= variant == "TransactionFailed";
+                outcome.gas = transaction_gas(&named_event_fields(&event, metadata));
+                outcome.logs.push(event_to_log(&event, metadata, addresses));
+            }
+            (pallet, variant) => {
+                let log = event_to_log(&event, metadata, addresses);
+                let token_log = match pallet {
+                    ASSETS_PALLET => {
+                        asset_log(variant, &named_event_fields(&event, metadata), addresses)
+                    }
+                    NFTS_PALLET => {
+                        nft_log(variant, &named_event_fields(&event, metadata), addresses)
+                    }
+                    _ => erc20_log(pallet, variant, &log),
+                };
+                outcome.logs.push(log);
+                outcome.logs.extend(token_log);
+            }
+        }
+    }
+
+    Ok(outcomes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::runtime_types::frame_support::dispatch::{DispatchClass, Pays};
+    use crate::types::runtime_types::sp_weights::weight_v2::Weight;
+    use crate::types::{evm_adapter, Event};
+    use parity_scale_codec::{Decode, Encode};
+    use sp_core::H256;
+
+    fn local_metadata() -> Metadata {
+        let bytes = std::fs::read("../artifacts/local_metadata.scale").unwrap();
+        Metadata::decode(&mut &bytes[..]).unwrap()
+    }
+
+    fn extrinsic_success() -> Event {
+        Event::System(system::Event::ExtrinsicSuccess {
+            dispatch_info: system::events::extrinsic_success::DispatchInfo {
+                weight: Weight {
+                    ref_time: 21_000 * WEIGHT_PER_GAS,
+                    proof_size: 0,
+                },
+                class: DispatchClass::Normal,
+                pays_fee: Pays::Yes,
+            },
+        })
+    }
+
+    #[test]
+    fn test_failed_transaction_status() {
+        let metadata = local_metadata();
+        let failed = Event::EvmAdapter(evm_adapter::Event::TransactionFailed {
+            from: Default::default(),
+            to: Default::default(),
+            error: b"Module".to_vec(),
+        });
+        let records = [
+            (Phase::ApplyExtrinsic(0), extrinsic_success()),
+            (Phase::ApplyExtrinsic(1), failed),
+            (Phase::ApplyExtrinsic(1), extrinsic_success()),
+        ]
+        .into_iter()
+        .map(|(phase, event)| (phase, event, Vec::<H256>::new()))
+        .collect::<Vec<_>>();
+        let events = Events::decode_from(records.encode(), metadata.clone());
+
+        let outcomes = extrinsic_outcomes(2, &events, &metadata, &AddressBook::default()).unwrap();
+        assert!(outcomes[0].success && !outcomes[0].reverted);
+        // the extrinsic succeeded, but not the call of its transaction
+        assert!(outcomes[1].success && outcomes[1].reverted);
+        assert_eq!(outcomes[1].logs.len(), 1);
+    }
+
+    #[test]
+    fn test_transaction_gas() {
+        let mut fields = BTreeMap::from([("gas_used".to_string(), 21_000u64.encode())]);
+        // runtimes whose events don't have the gas price
+        assert_eq!(transaction_gas(&fields), None);
+
+        fields.insert("gas_price".to_string(), sp_core::U256::from(1_000).encode());
+        assert_eq!(transaction_gas(&fields), Some((21_000, U256::from(1_000))));
+    }
+
+    #[test]
+    fn test_weight_to_gas() {
+        assert_eq!(weight_to_gas(0), 0);
+        assert_eq!(weight_to_gas(WEIGHT_PER_GAS - 1), 0);
+        assert_eq!(weight_to_gas(21_000 * WEIGHT_PER_GAS), 21_000);
+    }
+}
@@ -0,0 +1,191 @@
+//! Relayer submission
+//!
+//! `pallet_evm_adapter::transact` needs a signed origin, so Ethereum transactions are wrapped in an
+//! `EvmAdapter.transact` extrinsic signed by a relayer account. The relayer pays the extrinsic
+//! fees, the dispatched call still runs as the account mapped from the Ethereum sender.
+//!
+//! The relayer key is a secret URI, as accepted by `subkey`: a dev seed (`//Alice`), a mnemonic
+//! phrase or a hex encoded seed, with optional derivation paths. Nonces are tracked locally, so
+//! transactions submitted concurrently don't reuse the same nonce.
+
+use crate::types::*;
+use sp_core::{crypto::SecretStringError, ecdsa, sr25519, Pair};
+use std::path::Path;
+use subxt::backend::rpc::RpcClient;
+use subxt::config::signed_extensions::{
+    ChargeTransactionPaymentParams, CheckMortalityParams, CheckNonceParams,
+};
+use subxt::rpc_params;
+use subxt::tx::{Payload, Signer};
+use subxt::utils::{AccountId32, MultiAddress, MultiSignature, H256};
+use subxt::OnlineClient;
+use tokio::sync::Mutex;
+
+/// Environment variable holding the relayer's secret URI
+pub const RELAYER_KEY_ENV: &str = "SUBETH_RELAYER_KEY";
+
+/// Signature scheme of the relayer key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum KeyScheme {
+    #[default]
+    Sr25519,
+    Ecdsa,
+}
+
+/// Key signing the relayed extrinsics
+#[derive(Clone)]
+pub enum RelayerKey {
+    Sr25519(sr25519::Pair),
+    Ecdsa(ecdsa::Pair),
+}
+
+impl RelayerKey {
+    /// Key from a secret URI
+    pub fn from_suri(scheme: KeyScheme, suri: &str) -> Result<Self, SecretStringError> {
+        Ok(match scheme {
+            KeyScheme::Sr25519 => RelayerKey::Sr25519(sr25519::Pair::from_string(suri, None)?),
+            KeyScheme::Ecdsa => RelayerKey::Ecdsa(ecdsa::Pair::from_string(suri, None)?),
+        })
+    }
+
+    /// Key from a file holding a secret URI
+    pub fn from_file(scheme: KeyScheme, path: &Path) -> anyhow::Result<Self> {
+        let suri = std::fs::read_to_string(path)?;
+        Self::from_suri(scheme, suri.trim())
+            .map_err(|e| anyhow::anyhow!("invalid relayer key in {}: {:?}", path.display(), e))
+    }
+}
+
+impl Signer<ChainConfig> for RelayerKey {
+    fn account_id(&self) -> AccountId32 {
+        match self {
+            RelayerKey::Sr25519(pair) => AccountId32(pair.public().0),
+            // same as `MultiSigner::Ecdsa` into `AccountId32`
+            RelayerKey::Ecdsa(pair) => AccountId32(sp_core::blake2_256(&pair.public().0)),
+        }
+    }
+
+    fn address(&self) -> MultiAddress<AccountId32, ()> {
+        MultiAddress::Id(self.account_id())
+    }
+
+    fn sign(&self, signer_payload: &[u8]) -> MultiSignature {
+        match self {
+            RelayerKey::Sr25519(pair) => MultiSignature::Sr25519(pair.sign(signer_payload).0),
+            RelayerKey::Ecdsa(pair) => MultiSignature::Ecdsa(pair.sign(signer_payload).0),
+        }
+    }
+}
+
+/// Signs and submits extrinsics with the relayer key
+pub struct Relayer {
+    key: RelayerKey,
+    /// Next nonce of the relayer account, fetched from the node when unknown
+    nonce: Mutex<Option<u64>>,
+}
+
+impl Relayer {
+    pub fn new(key: RelayerKey) -> Self {
+        Self {
+            key,
+            nonce: Mutex::new(None),
+        }
+    }
+
+    /// Account of the relayer
+    pub fn account_id(&self) -> AccountId32 {
+        self.key.account_id()
+    }
+
+    /// Sign the call with the next nonce of the relayer and submit it, returning the extrinsic hash
+    ///
+    /// If the submission fails the nonce is fetched again from the node on the next submission,
+    /// as the failure may come from a stale nonce.
+    pub async fn submit<Call: Payload>(
+        &self,
+        api: &OnlineClient<ChainConfig>,
+        rpc: &RpcClient,
+        call: &Call,
+    ) -> Result<H256, SubEthError> {
+        let mut nonce = self.nonce.lock().await;
+        let next = match *nonce {
+            Some(next) => next,
+            // includes the relayer's transactions in the pool
+            None => {
+                rpc.request("system_accountNextIndex", rpc_params![self.account_id()])
+                    .await?
+            }
+        };
+
+        let params = (
+            (),
+            (),
+            (),
+            CheckMortalityParams::default(),
+            CheckNonceParams(Some(next)),
+            ChargeTransactionPaymentParams::no_tip(),
+        );
+        let result = match api.tx().create_signed(call, &self.key, params).await {
+            Ok(extrinsic) => extrinsic.submit().await,
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(hash) => {
+                *nonce = Some(next + 1);
+                Ok(hash)
+            }
+            Err(e) => {
+                *nonce = None;
+                Err(e.into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dev_keys() {
+        let alice = RelayerKey::from_suri(KeyScheme::Sr25519, "//Alice").unwrap();
+        assert_eq!(
+            alice.account_id().to_string(),
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+        );
+
+        let ecdsa_alice = RelayerKey::from_suri(KeyScheme::Ecdsa, "//Alice").unwrap();
+        assert_ne!(ecdsa_alice.account_id(), alice.account_id());
+
+        assert!(RelayerKey::from_suri(KeyScheme::Sr25519, "not a key").is_err());
+    }
+
+    #[test]
+    fn test_signatures_verify() {
+        let payload = b"payload";
+
+        let suri = "//Bob";
+        let key = RelayerKey::from_suri(KeyScheme::Sr25519, suri).unwrap();
+        let MultiSignature::Sr25519(signature) = key.sign(payload) else {
+            panic!("expected an sr25519 signature");
+        };
+        let pair = sr25519::Pair::from_string(suri, None).unwrap();
+        assert!(sr25519::Pair::verify(
+            &sr25519::Signature::from_raw(signature),
+            payload,
+            &pair.public()
+        ));
+
+        let key = RelayerKey::from_suri(KeyScheme::Ecdsa, suri).unwrap();
+        let MultiSignature::Ecdsa(signature) = key.sign(payload) else {
+            panic!("expected an ecdsa signature");
+        };
+        let pair = ecdsa::Pair::from_string(suri, None).unwrap();
+        assert!(ecdsa::Pair::verify(
+            &ecdsa::Signature::from_raw(signature),
+            payload,
+            &pair.public()
+        ));
+    }
+}
@@ -1,30 +1,69 @@
 use super::*;
-use alloy_consensus::Receipt;
 use alloy_primitives::{Address, Bytes, B256, U256, U64};
 use alloy_rpc_types_eth::{
     pubsub::{Params, SubscriptionKind},
     state::StateOverride,
-    Block as EthBlock, BlockId, BlockNumberOrTag, BlockOverrides, FeeHistory, Index, SyncStatus,
-    Transaction, TransactionRequest, Work,
+    Block as EthBlock, BlockId, BlockNumberOrTag, BlockOverrides, FeeHistory, Filter,
+    FilterChanges, Index, Log, SyncStatus, Transaction, TransactionReceipt, TransactionRequest,
+    Work,
 };
+use fee::FeeOracle;
+use filter::{FilterLimits, FilterManager};
 use futures::FutureExt;
+use jsonrpsee::types::{error::INVALID_PARAMS_CODE, ErrorObjectOwned};
 use jsonrpsee::{
     core::{async_trait, RpcResult},
     PendingSubscriptionSink,
 };
-use sub_client::handle_accepted_subscription;
-use traits::{EthApiServer, EthPubSubApiServer};
+use properties::Properties;
+use raw_tx::{decode_raw_transaction, RawTransaction};
+use sub_client::{handle_accepted_subscription, BlockStream};
+use traits::{EthApiServer, EthFilterApiServer, EthPubSubApiServer, SubEthApiServer};
 
 pub type SubscriptionTaskExecutor = std::sync::Arc<dyn sp_core::traits::SpawnNamed>;
 
-/// A notification when new block is received.
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
-pub struct BlockNotification {
-    pub hash: B256,
-    pub is_new_best: bool,
+/// A validated `eth_subscribe` request
+#[derive(Debug, Clone)]
+pub enum EthSubscription {
+    /// New block headers
+    NewHeads,
+    /// Logs matching the filter
+    Logs(Box<Filter>),
+    /// Hashes of transactions entering the transaction pool
+    NewPendingTransactions,
+}
+
+impl EthSubscription {
+    /// Validate the subscription kind and its params
+    fn new(kind: SubscriptionKind, params: Option<Params>) -> Result<Self, ErrorObjectOwned> {
+        let invalid_params =
+            |msg: &str| ErrorObjectOwned::owned(INVALID_PARAMS_CODE, msg.to_string(), None::<()>);
+
+        match (kind, params) {
+            (SubscriptionKind::NewHeads, None) => Ok(Self::NewHeads),
+            (SubscriptionKind::Logs, None) => Ok(Self::Logs(Box::default())),
+            (SubscriptionKind::Logs, Some(Params::Logs(filter))) => Ok(Self::Logs(filter)),
+            (SubscriptionKind::NewPendingTransactions, None | Some(Params::Bool(false))) => {
+                Ok(Self::NewPendingTransactions)
+            }
+            (SubscriptionKind::NewPendingTransactions, Some(Params::Bool(true))) => Err(
+                invalid_params("full pending transactions are not supported"),
+            ),
+            (SubscriptionKind::Syncing, _) => Err(ErrorObjectOwned::owned(
+                -32601,
+                "syncing subscription is not supported",
+                None::<()>,
+            )),
+            (kind, Some(_)) => Err(invalid_params(&format!(
+                "unexpected params for {:?} subscription",
+                kind
+            ))),
+        }
+    }
 }
 
 /// The main ETH adapter struct responsible for handling all the ETH RPC methods and converting them to Substrate calls.
+#[derive(Clone)]
 pub struct EthAdapter {
     /// The Substrate light client
     client: SubLightClient,
@@ -32,6 +71,12 @@ pub struct EthAdapter {
     accounts: Vec<Address>,
     /// Subscription task executor
     executor: SubscriptionTaskExecutor,
+    /// Installed polling filters
+    filters: FilterManager,
+    /// Fee oracle
+    fees: FeeOracle,
+    /// Blocks followed by `newHeads` and `logs` subscriptions
+    block_stream: BlockStream,
 }
 
 impl EthAdapter {
@@ -42,11 +87,26 @@ impl EthAdapter {
         executor: SubscriptionTaskExecutor,
     ) -> Self {
         Self {
+            filters: FilterManager::new(client.clone(), FilterLimits::default()),
+            fees: FeeOracle::new(client.clone()),
             client,
             accounts,
             executor,
+            block_stream: BlockStream::default(),
         }
     }
+
+    /// Follow the given blocks in subscriptions
+    pub fn with_block_stream(mut self, block_stream: BlockStream) -> Self {
+        self.block_stream = block_stream;
+        self
+    }
+
+    /// Use the given filter manager instead of the default one
+    pub fn with_filters(mut self, filters: FilterManager) -> Self {
+        self.filters = filters;
+        self
+    }
 }
 
 /// Implement the ETH API server
@@ -158,17 +218,24 @@ impl EthApiServer for EthAdapter {
     // ########################################################################
 
     /// Get transaction by its hash.
-    async fn transaction_by_hash(&self, _hash: B256) -> RpcResult<Option<Transaction>> {
-        unimplemented!()
+    async fn transaction_by_hash(&self, hash: B256) -> RpcResult<Option<Transaction>> {
+        let tx = self.client.get_transaction_by_hash(hash).await?;
+
+        Ok(tx)
     }
 
     /// Returns transaction by given block number and index.
     async fn transaction_by_block_hash_and_index(
         &self,
-        _hash: B256,
-        _index: Index,
+        hash: B256,
+        index: Index,
     ) -> RpcResult<Option<Transaction>> {
-        unimplemented!()
+        let tx = self
+            .client
+            .get_transaction_by_block_hash_and_index(hash.0.into(), index)
+            .await?;
+
+        Ok(tx)
     }
 
     /// Returns transaction by given block number and index.
@@ -186,8 +253,17 @@ impl EthApiServer for EthAdapter {
     }
 
     /// Returns transaction receipt by transaction hash.
-    async fn transaction_receipt(&self, _hash: B256) -> RpcResult<Option<Receipt>> {
-        unimplemented!()
+    async fn transaction_receipt(&self, hash: B256) -> RpcResult<Option<TransactionReceipt>> {
+        let receipt = self.client.get_transaction_receipt(hash).await?;
+
+        Ok(receipt)
+    }
+
+    /// Returns the receipts of all transactions in a block.
+    async fn block_receipts(&self, block: BlockId) -> RpcResult<Option<Vec<TransactionReceipt>>> {
+        let receipts = self.client.get_block_receipts(block).await?;
+
+        Ok(receipts)
     }
 
     // ########################################################################
@@ -195,8 +271,8 @@ impl EthApiServer for EthAdapter {
     // ########################################################################
 
     /// Returns balance of the given account.
-    async fn balance(&self, address: Address, _number_or_tag: Option<BlockId>) -> RpcResult<U256> {
-        let balance = self.client.get_balance(address).await?;
+    async fn balance(&self, address: Address, number_or_tag: Option<BlockId>) -> RpcResult<U256> {
+        let balance = self.client.get_balance(address, number_or_tag).await?;
 
         Ok(balance)
     }
@@ -206,9 +282,12 @@ impl EthApiServer for EthAdapter {
         &self,
         address: Address,
         key: B256,
-        _number_or_tag: Option<BlockId>,
+        number_or_tag: Option<BlockId>,
     ) -> RpcResult<Vec<u8>> {
-        let storage = self.client.get_storage_at(address, key.0.into()).await?;
+        let storage = self
+            .client
+            .get_storage_at(address, key.0.into(), number_or_tag)
+            .await?;
 
         Ok(storage)
     }
@@ -217,20 +296,19 @@ impl EthApiServer for EthAdapter {
     async fn transaction_count(
         &self,
         address: Address,
-        _number_or_tag: Option<BlockNumberOrTag>,
+        number_or_tag: Option<BlockId>,
     ) -> RpcResult<U256> {
-        let count = self.client.get_transaction_count(address).await?;
+        let count = self
+            .client
+            .get_transaction_count(address, number_or_tag)
+            .await?;
 
         Ok(count)
     }
 
     /// Returns the code at given address at given time (block number).
-    async fn code_at(
-        &self,
-        address: Address,
-        _number_or_tag: Option<BlockNumberOrTag>,
-    ) -> RpcResult<Bytes> {
-        let code = self.client.get_code(address)?;
+    async fn code_at(&self, address: Address, number_or_tag: Option<BlockId>) -> RpcResult<Bytes> {
+        let code = self.client.get_code(address, number_or_tag).await?;
 
         Ok(code.into())
     }
@@ -243,11 +321,11 @@ impl EthApiServer for EthAdapter {
     async fn call(
         &self,
         request: TransactionRequest,
-        _block_number: Option<BlockId>,
+        block_number: Option<BlockId>,
         _state_overrides: Option<StateOverride>,
         _block_overrides: Option<Box<BlockOverrides>>,
     ) -> RpcResult<Bytes> {
-        let res = self.client.call(request).await?;
+        let res = self.client.call(request, block_number).await?;
 
         if let Some(output) = res {
             Ok(output.into())
@@ -259,11 +337,11 @@ impl EthApiServer for EthAdapter {
     /// Estimate gas needed for execution of given contract.
     async fn estimate_gas(
         &self,
-        _request: TransactionRequest,
-        _block_number: Option<BlockId>,
+        request: TransactionRequest,
+        block_number: Option<BlockId>,
         _state_override: Option<StateOverride>,
     ) -> RpcResult<U256> {
-        unimplemented!()
+        Ok(self.client.estimate_gas(request, block_number).await?)
     }
 
     // ########################################################################
@@ -271,25 +349,27 @@ impl EthApiServer for EthAdapter {
     // ########################################################################
 
     /// Returns current gas_price.
-    fn gas_price(&self) -> RpcResult<U256> {
-        // TODO: fix this
-        Ok(U256::from(1_000_000))
+    async fn gas_price(&self) -> RpcResult<U256> {
+        Ok(U256::from(self.fees.gas_price().await?))
     }
 
     /// Introduced in EIP-1159 for getting information on the appropriate priority fee to use.
     async fn fee_history(
         &self,
-        _block_count: U256,
-        _newest_block: U256,
-        _reward_percentiles: Option<Vec<f64>>,
+        block_count: U64,
+        newest_block: BlockNumberOrTag,
+        reward_percentiles: Option<Vec<f64>>,
     ) -> RpcResult<FeeHistory> {
-        unimplemented!()
+        Ok(self
+            .fees
+            .fee_history(block_count.to(), newest_block, reward_percentiles)
+            .await?)
     }
 
     /// Introduced in EIP-1159, a Geth-specific and simplified priority fee oracle.
     /// Leverages the already existing fee history cache.
-    fn max_priority_fee_per_gas(&self) -> RpcResult<U256> {
-        unimplemented!()
+    async fn max_priority_fee_per_gas(&self) -> RpcResult<U256> {
+        Ok(U256::from(self.fees.max_priority_fee_per_gas().await?))
     }
 
     // ########################################################################
@@ -327,51 +407,29 @@ impl EthApiServer for EthAdapter {
 
     /// Sends transaction; will block waiting for signer to return the
     /// transaction hash.
-    async fn send_transaction(&self, request: TransactionRequest) -> RpcResult<B256> {
-        use subeth_primitives::{conversions::*, EthereumTransaction};
-
-        // Extract transaction details
-        let to = match request.to {
-            Some(alloy_primitives::TxKind::Call(addr)) => alloy_address_to_h160(addr),
-            _ => {
-                return Err(jsonrpsee::types::ErrorObject::owned(
-                    -32602,
-                    "Missing 'to' address",
-                    None::<()>,
-                )
-                .into())
-            }
-        };
-
-        let nonce = request.nonce.unwrap_or(0);
-        let value = alloy_u256_to_sp_u256(request.value.unwrap_or_default());
-        let data = request.input.input.map(|b| b.to_vec()).unwrap_or_default();
-        let gas_limit = request.gas.unwrap_or(21000);
+    ///
+    /// The adapter holds no Ethereum keys, so it can't sign the transaction: an unsigned one
+    /// would never pass the pallet's signature check, the transaction is rejected instead.
+    async fn send_transaction(&self, _request: TransactionRequest) -> RpcResult<B256> {
+        Err(jsonrpsee::types::ErrorObject::owned(
+            -32000,
+            "unsupported: the adapter has no signer, use eth_sendRawTransaction",
+            None::<()>,
+        ))
+    }
 
-        // Get gas price
-        let max_fee_per_gas = alloy_u256_to_sp_u256(
-            alloy_primitives::U256::from(request.max_fee_per_gas.unwrap_or(1_000_000))
+    /// Sends signed transaction, returning its hash.
+    async fn send_raw_transaction(&self, bytes: Bytes) -> RpcResult<B256> {
+        let RawTransaction {
+            sender,
+            transaction: eth_tx,
+        } = decode_raw_transaction(&bytes, self.client.chain_id())?;
+        log::debug!(
+            "Raw {:?} transaction from {} with nonce {}",
+            eth_tx.transaction_type,
+            sender,
+            eth_tx.nonce
         );
-        let max_priority_fee_per_gas =
-            alloy_u256_to_sp_u256(alloy_primitives::U256::from(request.max_priority_fee_per_gas.unwrap_or(0)));
-
-        // Construct EthereumTransaction
-        // Note: For now, signature fields are dummy values since we're using a signed extrinsic
-        // The pallet will verify these, but for MVP we can use placeholder values
-        let eth_tx = EthereumTransaction {
-            chain_id: self.client.chain_id(),
-            nonce,
-            max_priority_fee_per_gas,
-            max_fee_per_gas,
-            gas_limit,
-            to,
-            value,
-            data,
-            access_list: vec![],
-            v: 0,
-            r: Default::default(),
-            s: Default::default(),
-        };
 
         // Submit to chain
         let tx_hash = self
@@ -388,35 +446,62 @@ impl EthApiServer for EthAdapter {
 
         Ok(tx_hash)
     }
+}
 
-    /// Sends signed transaction, returning its hash.
-    async fn send_raw_transaction(&self, bytes: Bytes) -> RpcResult<B256> {
-        use subeth_primitives::EthereumTransaction;
-        use parity_scale_codec::Decode;
-
-        // Decode the raw transaction
-        let eth_tx = EthereumTransaction::decode(&mut bytes.as_ref()).map_err(|e| {
-            jsonrpsee::types::ErrorObject::owned(
-                -32602,
-                format!("Failed to decode transaction: {:?}", e),
-                None::<()>,
-            )
-        })?;
+/// Implement the ETH filter API server
+#[async_trait]
+impl EthFilterApiServer for EthAdapter {
+    /// Returns id of new filter.
+    async fn new_filter(&self, filter: Filter) -> RpcResult<U256> {
+        let id = self.filters.new_log_filter(filter).await?;
 
-        // Submit to chain
-        let tx_hash = self
-            .client
-            .submit_evm_transaction(eth_tx)
-            .await
-            .map_err(|e| {
-                jsonrpsee::types::ErrorObject::owned(
-                    -32000,
-                    format!("Transaction submission failed: {:?}", e),
-                    None::<()>,
-                )
-            })?;
+        Ok(U256::from(id))
+    }
 
-        Ok(tx_hash)
+    /// Returns id of new block filter.
+    fn new_block_filter(&self) -> RpcResult<U256> {
+        Ok(U256::from(self.filters.new_block_filter()))
+    }
+
+    /// Returns id of new pending transaction filter.
+    async fn new_pending_transaction_filter(&self) -> RpcResult<U256> {
+        let id = self.filters.new_pending_transaction_filter().await?;
+
+        Ok(U256::from(id))
+    }
+
+    /// Returns filter changes since last poll.
+    async fn filter_changes(&self, index: Index) -> RpcResult<FilterChanges> {
+        let changes = self.filters.filter_changes(index.0).await?;
+
+        Ok(changes)
+    }
+
+    /// Returns all logs matching given filter (in a range 'from' - 'to').
+    async fn filter_logs(&self, index: Index) -> RpcResult<Vec<Log>> {
+        let logs = self.filters.filter_logs(index.0).await?;
+
+        Ok(logs)
+    }
+
+    /// Uninstalls filter.
+    fn uninstall_filter(&self, index: Index) -> RpcResult<bool> {
+        Ok(self.filters.uninstall(index.0))
+    }
+
+    /// Returns logs matching given filter object.
+    async fn logs(&self, filter: Filter) -> RpcResult<Vec<Log>> {
+        let logs = self.filters.logs(filter).await?;
+
+        Ok(logs)
+    }
+}
+
+/// Implement the Subeth specific API server
+impl SubEthApiServer for EthAdapter {
+    /// Returns the chain properties (token decimals and symbol, SS58 prefix).
+    fn chain_properties(&self) -> RpcResult<Properties> {
+        Ok(self.client.properties())
     }
 }
 
@@ -428,21 +513,25 @@ impl EthPubSubApiServer for EthAdapter {
         kind: SubscriptionKind,
         params: Option<Params>,
     ) -> jsonrpsee::core::SubscriptionResult {
-        // Handle the subscription logic here
-        println!(
-            "Subscribed with kind: {:?}, params: {:?}, id: {:?}",
-            kind, params, pending
-        );
+        log::debug!("Subscribed with kind: {:?}, params: {:?}", kind, params);
+
+        let subscription = match EthSubscription::new(kind, params) {
+            Ok(subscription) => subscription,
+            Err(e) => {
+                pending.reject(e).await;
+                return Ok(());
+            }
+        };
 
         let sink = pending.accept().await?;
         let client = self.client.clone();
+        let block_stream = self.block_stream;
 
         let fut = async move {
-            match kind {
-                SubscriptionKind::NewHeads => {
-                    let _ = handle_accepted_subscription(client, kind, sink).await;
-                }
-                _ => {}
+            if let Err(e) =
+                handle_accepted_subscription(client, subscription, block_stream, sink).await
+            {
+                log::warn!("Subscription stopped: {:?}", e);
             }
         }
         .boxed();
@@ -453,3 +542,48 @@ impl EthPubSubApiServer for EthAdapter {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscription_params() {
+        assert!(matches!(
+            EthSubscription::new(SubscriptionKind::NewHeads, None),
+            Ok(EthSubscription::NewHeads)
+        ));
+        assert!(matches!(
+            EthSubscription::new(SubscriptionKind::Logs, None),
+            Ok(EthSubscription::Logs(_))
+        ));
+        assert!(matches!(
+            EthSubscription::new(
+                SubscriptionKind::Logs,
+                Some(Params::Logs(Box::new(Filter::new().address(Address::ZERO))))
+            ),
+            Ok(EthSubscription::Logs(filter)) if filter.address.matches(&Address::ZERO)
+        ));
+        assert!(matches!(
+            EthSubscription::new(SubscriptionKind::NewPendingTransactions, None),
+            Ok(EthSubscription::NewPendingTransactions)
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_subscriptions_are_rejected() {
+        let err = EthSubscription::new(SubscriptionKind::Syncing, None).unwrap_err();
+        assert_eq!(err.code(), -32601);
+
+        let err = EthSubscription::new(
+            SubscriptionKind::NewPendingTransactions,
+            Some(Params::Bool(true)),
+        )
+        .unwrap_err();
+        assert_eq!(err.code(), INVALID_PARAMS_CODE);
+
+        let err =
+            EthSubscription::new(SubscriptionKind::NewHeads, Some(Params::Bool(true))).unwrap_err();
+        assert_eq!(err.code(), INVALID_PARAMS_CODE);
+    }
+}
@@ -0,0 +1,398 @@
+//! ABI view functions of the pallets' storage entries
+//!
+//! Every storage entry of a pallet is a view function of the pallet's contract address
+//! ([`PalletContractMapping::contract_address`]), named after the entry and taking its keys, e.g.
+//! `Account(address)` for `System.Account` or `ActiveEra()` for `Staking.ActiveEra`. The keys and
+//! the value get Solidity types from the runtime metadata, like event fields (see
+//! [`crate::events`]), and the selector is the keccak hash of the signature.
+//!
+//! - the arguments are ABI-decoded into the SCALE-encoded keys: addresses into the accounts bound
+//!   to them, integers into the key's integer type if they fit, and the `bytes` of keys without a
+//!   Solidity type are their SCALE encoding
+//! - the value is SCALE-decoded and ABI-encoded as the function's return values, structs and
+//!   tuples being flattened into their fields: `System.Account` returns the `nonce`, `consumers`,
+//!   `providers` and `sufficients` of the account, followed by the fields of its `data`
+
+use crate::adapter::{hash_key, AddressBook, PalletContractMapping};
+use crate::events::{abi_encode, SolType};
+use alloy_primitives::{keccak256, Address, U256};
+use parity_scale_codec::{Compact, Encode};
+use scale_info::{PortableRegistry, TypeDef};
+use sp_core::twox_128;
+use subxt::ext::scale_decode::visitor::{decode_with_visitor, IgnoreVisitor};
+use subxt::metadata::types::{StorageEntryModifier, StorageEntryType, StorageHasher};
+use subxt::utils::AccountId32;
+use subxt::Metadata;
+
+/// Storage entry called as a view function
+#[derive(Clone, Debug)]
+pub struct StorageView {
+    /// Storage prefix of the pallet
+    prefix: String,
+    /// Name of the entry
+    name: String,
+    /// Solidity types of the keys, one per hasher
+    key_types: Vec<SolType>,
+    hashers: Vec<StorageHasher>,
+    /// Metadata type of the value
+    value_ty: u32,
+    /// SCALE-encoded value of the keys that aren't set, `None` for optional entries
+    default: Option<Vec<u8>>,
+}
+
+/// Key of a storage view, decoded from its argument
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ViewKey {
+    /// SCALE-encoded key
+    Encoded(Vec<u8>),
+    /// `AccountId32` key given as an address, see
+    /// [`AddressMapping::account_of`](crate::adapter::AddressMapping::account_of)
+    Account(Address),
+}
+
+impl StorageView {
+    /// Storage entry of the pallet at `address` whose view has the selector, `None` if it isn't
+    /// the address of a pallet or no entry matches
+    pub fn find(metadata: &Metadata, address: Address, selector: &[u8]) -> Option<Self> {
+        let pallet = metadata
+            .pallets()
+            .find(|pallet| PalletContractMapping::contract_address(pallet.name()) == address)?;
+        let storage = pallet.storage()?;
+        let types = metadata.types();
+
+        storage.entries().iter().find_map(|entry| {
+            let (hashers, key_types) = match entry.entry_type() {
+                StorageEntryType::Plain(_) => (vec![], vec![]),
+                StorageEntryType::Map {
+                    hashers, key_ty, ..
+                } => (hashers.clone(), key_types(*key_ty, hashers.len(), types)?),
+            };
+            let signature = view_signature(entry.name(), &key_types);
+            if keccak256(signature).get(..4) != Some(selector) {
+                return None;
+            }
+
+            Some(Self {
+                prefix: storage.prefix().to_string(),
+                name: entry.name().to_string(),
+                key_types,
+                hashers,
+                value_ty: entry.entry_type().value_ty(),
+                default: (entry.modifier() == StorageEntryModifier::Default)
+                    .then(|| entry.default_bytes().to_vec()),
+            })
+        })
+    }
+
+    /// Signature of the view, e.g. `Account(address)`
+    pub fn signature(&self) -> String {
+        view_signature(&self.name, &self.key_types)
+    }
+
+    /// Decode the keys from the ABI-encoded arguments, `None` if they aren't valid keys
+    pub fn decode_keys(&self, args: &[u8]) -> Option<Vec<ViewKey>> {
+        self.key_types
+            .iter()
+            .enumerate()
+            .map(|(index, ty)| decode_key(*ty, args, index))
+            .collect()
+    }
+
+    /// Storage key of the entry of the SCALE-encoded keys
+    pub fn storage_key(&self, keys: &[Vec<u8>]) -> Vec<u8> {
+        let mut storage_key = twox_128(self.prefix.as_bytes()).to_vec();
+        storage_key.extend(twox_128(self.name.as_bytes()));
+        for (key, hasher) in keys.iter().zip(&self.hashers) {
+            storage_key.extend(hash_key(key, hasher));
+        }
+
+        storage_key
+    }
+
+    /// SCALE-encoded value of the keys that aren't set, `None` for optional entries
+    pub fn default_value(&self) -> Option<&[u8]> {
+        self.default.as_deref()
+    }
+
+    /// Solidity types and SCALE encodings of the return values of the SCALE-encoded value,
+    /// `None` if it can't be decoded
+    pub fn value_fields<'a>(
+        &self,
+        value: &'a [u8],
+        types: &PortableRegistry,
+    ) -> Option<Vec<(SolType, &'a [u8])>> {
+        let mut input = value;
+        let mut fields = Vec::new();
+        for id in flatten(self.value_ty, types) {
+            let start = input;
+            decode_with_visitor(&mut input, id, types, IgnoreVisitor::new()).ok()?;
+            fields.push((
+                SolType::from_metadata(id, types),
+                &start[..start.len() - input.len()],
+            ));
+        }
+
+        input.is_empty().then_some(fields)
+    }
+}
+
+/// ABI-encoded return values of a view
+///
+/// Accounts missing from `addresses` are truncated.
+pub fn encode_output(fields: &[(SolType, &[u8])], addresses: &AddressBook) -> Vec<u8> {
+    let values = fields
+        .iter()
+        .map(|(ty, scale)| ty.encode(scale, addresses))
+        .collect::<Vec<_>>();
+    abi_encode(&values)
+}
+
+/// `AccountId32` return values of a view, whose addresses [`encode_output`] needs
+pub fn output_accounts(fields: &[(SolType, &[u8])]) -> Vec<AccountId32> {
+    fields
+        .iter()
+        .filter(|(ty, _)| *ty == SolType::Address(32))
+        .filter_map(|(_, scale)| <[u8; 32]>::try_from(*scale).ok())
+        .map(AccountId32::from)
+        .collect()
+}
+
+/// Signature of the view of a storage entry, `Entry(type1,type2,...)`
+fn view_signature(name: &str, key_types: &[SolType]) -> String {
+    let params = key_types.iter().map(SolType::name).collect::<Vec<_>>();
+    format!("{}({})", name, params.join(","))
+}
+
+/// Solidity types of the keys of a map, whose key type is a tuple if it has several hashers
+fn key_types(key_ty: u32, hashers: usize, types: &PortableRegistry) -> Option<Vec<SolType>> {
+    if hashers == 1 {
+        return Some(vec![SolType::from_metadata(key_ty, types)]);
+    }
+
+    match &types.resolve(key_ty)?.type_def {
+        TypeDef::Tuple(tuple) if tuple.fields.len() == hashers => Some(
+            tuple
+                .fields
+                .iter()
+                .map(|field| SolType::from_metadata(field.id, types))
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// Types of the return values of a value type: structs and tuples are flattened into their
+/// fields, unless they have a Solidity type
+fn flatten(id: u32, types: &PortableRegistry) -> Vec<u32> {
+    if SolType::from_metadata(id, types) != SolType::Encoded {
+        return vec![id];
+    }
+
+    let field_ids: Vec<u32> = match types.resolve(id).map(|ty| &ty.type_def) {
+        Some(TypeDef::Composite(composite)) => {
+            composite.fields.iter().map(|field| field.ty.id).collect()
+        }
+        Some(TypeDef::Tuple(tuple)) => tuple.fields.iter().map(|field| field.id).collect(),
+        _ => return vec![id],
+    };
+    field_ids
+        .into_iter()
+        .flat_map(|field_id| flatten(field_id, types))
+        .collect()
+}
+
+/// Key of the `index`th argument, `None` if it isn't a valid value of the key's type
+fn decode_key(ty: SolType, args: &[u8], index: usize) -> Option<ViewKey> {
+    let word = args.get(32 * index..32 * (index + 1))?;
+    let value = U256::from_be_slice(word);
+    let address = || {
+        word[..12]
+            .iter()
+            .all(|b| *b == 0)
+            .then(|| Address::from_slice(&word[12..]))
+    };
+
+    let key = match ty {
+        SolType::Address(32) => return address().map(ViewKey::Account),
+        SolType::Address(_) => address()?.to_vec(),
+        SolType::Uint { size, .. } if value.bit_len() > size * 8 => return None,
+        SolType::Uint { compact: true, .. } => Compact(u128::try_from(value).ok()?).encode(),
+        SolType::Uint { size, .. } => value.to_le_bytes::<32>()[..size].to_vec(),
+        SolType::Int(size) => {
+            // the bytes above the key's size only extend its sign
+            let sign = if word[32 - size] & 0x80 != 0 { 0xff } else { 0 };
+            if word[..32 - size].iter().any(|b| *b != sign) {
+                return None;
+            }
+            word[32 - size..].iter().rev().copied().collect()
+        }
+        SolType::Bool if value > U256::from(1) => return None,
+        SolType::Bool => vec![word[31]],
+        SolType::FixedBytes(len) => word[..len].to_vec(),
+        SolType::Bytes | SolType::String => dynamic_arg(args, value)?.encode(),
+        SolType::Encoded => dynamic_arg(args, value)?.to_vec(),
+    };
+
+    Some(ViewKey::Encoded(key))
+}
+
+/// Bytes of the dynamic argument at `offset` in the arguments
+fn dynamic_arg(args: &[u8], offset: U256) -> Option<&[u8]> {
+    let start = usize::try_from(offset).ok()?.checked_add(32)?;
+    let len = usize::try_from(U256::from_be_slice(args.get(start - 32..start)?)).ok()?;
+    args.get(start..start.checked_add(len)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::AddressMapping;
+    use crate::events::AbiValue;
+    use alloy_primitives::hex;
+    use parity_scale_codec::Decode;
+
+    fn local_metadata() -> Metadata {
+        let bytes = std::fs::read("../artifacts/local_metadata.scale").unwrap();
+        Metadata::decode(&mut &bytes[..]).unwrap()
+    }
+
+    fn find(metadata: &Metadata, pallet: &str, signature: &str) -> Option<StorageView> {
+        StorageView::find(
+            metadata,
+            PalletContractMapping::contract_address(pallet),
+            &keccak256(signature)[..4],
+        )
+    }
+
+    #[test]
+    fn test_account_view() {
+        let metadata = local_metadata();
+        let view = find(&metadata, "System", "Account(address)").unwrap();
+        assert_eq!(view.signature(), "Account(address)");
+        assert!(view.default_value().is_some());
+        // not an entry of another pallet
+        assert!(find(&metadata, "Timestamp", "Account(address)").is_none());
+
+        let address = Address::repeat_byte(0x42);
+        assert_eq!(
+            view.decode_keys(address.into_word().as_slice()),
+            Some(vec![ViewKey::Account(address)])
+        );
+        // not an address
+        assert_eq!(view.decode_keys(&[0xff; 32]), None);
+        assert_eq!(view.decode_keys(&[]), None);
+
+        let account = AddressMapping::to_ss58(address);
+        let mut storage_key = twox_128(b"System").to_vec();
+        storage_key.extend(twox_128(b"Account"));
+        storage_key.extend(hash_key(account.as_ref(), &StorageHasher::Blake2_128Concat));
+        assert_eq!(view.storage_key(&[account.0.to_vec()]), storage_key);
+
+        // nonce, consumers, providers, sufficients, then free, reserved, frozen and flags
+        let mut value = (7u32, 1u32, 1u32, 0u32).encode();
+        value.extend((1_000u64, 20u64, 0u64, 1u128 << 127).encode());
+        let fields = view.value_fields(&value, metadata.types()).unwrap();
+        assert_eq!(fields.len(), 8);
+        assert!(output_accounts(&fields).is_empty());
+
+        let output = encode_output(&fields, &AddressBook::default());
+        let word = |index: usize| U256::from_be_slice(&output[32 * index..32 * (index + 1)]);
+        assert_eq!(output.len(), 8 * 32);
+        assert_eq!(word(0), U256::from(7));
+        assert_eq!(word(4), U256::from(1_000));
+        assert_eq!(word(5), U256::from(20));
+        assert_eq!(word(7), U256::from(1u128 << 127));
+
+        // truncated value
+        assert!(view.value_fields(&value[..20], metadata.types()).is_none());
+    }
+
+    #[test]
+    fn test_plain_view() {
+        let metadata = local_metadata();
+        let view = find(&metadata, "System", "Number()").unwrap();
+        assert_eq!(view.decode_keys(&[]), Some(vec![]));
+        assert_eq!(
+            view.storage_key(&[]),
+            [twox_128(b"System"), twox_128(b"Number")].concat()
+        );
+
+        let value = 42u32.encode();
+        let fields = view.value_fields(&value, metadata.types()).unwrap();
+        assert_eq!(
+            encode_output(&fields, &AddressBook::default()),
+            U256::from(42).to_be_bytes_vec()
+        );
+    }
+
+    #[test]
+    fn test_integer_key() {
+        let metadata = local_metadata();
+        let view = find(&metadata, "System", "BlockHash(uint32)").unwrap();
+        assert_eq!(
+            view.decode_keys(&U256::from(5).to_be_bytes::<32>()),
+            Some(vec![ViewKey::Encoded(5u32.encode())])
+        );
+        // doesn't fit the `u32` block number
+        assert_eq!(
+            view.decode_keys(&U256::from(u64::MAX).to_be_bytes::<32>()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_decode_key() {
+        let word = |value: U256| value.to_be_bytes::<32>().to_vec();
+        let encoded = |key: &[u8]| Some(ViewKey::Encoded(key.to_vec()));
+
+        let compact = SolType::Uint {
+            size: 8,
+            compact: true,
+        };
+        assert_eq!(
+            decode_key(compact, &word(U256::from(1_000)), 0),
+            encoded(&Compact(1_000u64).encode())
+        );
+
+        let minus_two = [0xff; 32];
+        let mut minus_two = minus_two.to_vec();
+        minus_two[31] = 0xfe;
+        assert_eq!(
+            decode_key(SolType::Int(2), &minus_two, 0),
+            encoded(&(-2i16).encode())
+        );
+        assert_eq!(
+            decode_key(SolType::Int(2), &word(U256::from(i16::MAX)), 0),
+            encoded(&i16::MAX.encode())
+        );
+        // doesn't fit in an `i16`
+        assert_eq!(
+            decode_key(SolType::Int(2), &word(U256::from(u16::MAX)), 0),
+            None
+        );
+
+        assert_eq!(
+            decode_key(SolType::Bool, &word(U256::from(1)), 0),
+            encoded(&[1])
+        );
+        assert_eq!(decode_key(SolType::Bool, &word(U256::from(2)), 0), None);
+
+        let args = abi_encode(&[
+            AbiValue::Word(U256::from(1).into()),
+            AbiValue::Dynamic(b"key".to_vec()),
+        ]);
+        assert_eq!(
+            decode_key(SolType::Bytes, &args, 1),
+            encoded(&b"key".to_vec().encode())
+        );
+        assert_eq!(decode_key(SolType::Encoded, &args, 1), encoded(b"key"));
+        // the offset points past the arguments
+        assert_eq!(decode_key(SolType::Bytes, &args, 0), None);
+
+        let h160 = hex!("000000000000000000000000ffffffffffffffffffffffffffffffffffffffff");
+        assert_eq!(
+            decode_key(SolType::Address(20), &h160, 0),
+            encoded(&[0xff; 20])
+        );
+    }
+}
@@ -4,32 +4,55 @@
 
 use crate::adapter::{hash_key, AddressMapping, PalletContractMapping, StorageKey};
 use crate::cache::BlockCache;
-use crate::server::BlockNotification;
+use crate::erc20::{
+    encode_string, encode_uint, value_amount, value_string, Erc20View, Token, ASSETS_PALLET,
+};
+use crate::erc721::{encode_address, value_account, Erc721View, NFTS_PALLET};
+use crate::events::event_accounts;
+use crate::fee::{
+    base_fee_per_gas, block_usage, pallet_base_fee_per_gas, BlockFees, REFERENCE_GAS,
+    REFERENCE_LENGTH,
+};
+use crate::filter::filter_logs;
+use crate::gas::{
+    decode_transact, dispatch_error_message, dispatched_call, transact_call,
+    transact_unsigned_call, CallDryRunEffects, DryRunError, EvmDryRunResult, RawCall,
+    RuntimeDispatchInfo, DRY_RUN_XCM_VERSION,
+};
+use crate::index::{TxIndex, TxLocation, DEFAULT_RETENTION};
+use crate::properties::{Properties, PropertiesOverride};
+use crate::receipt::{block_receipts, weight_to_gas, WEIGHT_PER_GAS};
+use crate::relayer::Relayer;
+use crate::server::EthSubscription;
+use crate::storage_view::{encode_output, output_accounts, StorageView, ViewKey};
 use crate::types::*;
+use crate::units::{BalanceUnit, Units};
 use alloy_consensus::{Signed, TxEip1559};
 use alloy_primitives::{Address, ChainId, PrimitiveSignature, TxKind, B256, U256};
-use alloy_rpc_types_eth::pubsub::SubscriptionKind;
+use alloy_rpc_types_eth::pubsub::SubscriptionResult;
 use alloy_rpc_types_eth::{
-    Block as EthBlock, BlockNumberOrTag, Header as EthHeader, Index, SyncStatus,
-    TransactionReceipt, TransactionRequest,
+    Block as EthBlock, BlockId, BlockNumberOrTag, Filter, Header as EthHeader, Index, Log,
+    SyncStatus, TransactionReceipt, TransactionRequest,
 };
+use frame_support::sp_runtime::FixedU128;
+use frame_support::weights::Weight;
 use frame_support::StorageHasher;
-use futures::{Stream, StreamExt};
+use futures::{stream, Stream, StreamExt};
 use jsonrpsee::{SubscriptionMessage, SubscriptionSink};
+use parity_scale_codec::{Decode, Encode};
+use parking_lot::RwLock;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use subeth_primitives::{conversions::*, EthereumTransaction, TransactionType};
 use subxt::backend::rpc::RpcClient;
 use subxt::blocks::ExtrinsicDetails;
+use subxt::dynamic::{At, Value};
 use subxt::metadata::types::StorageEntryType;
 use subxt::rpc_params;
-use subxt::utils::{AccountId32, MultiAddress, H256};
-use subxt::{lightclient::LightClient, OnlineClient};
-
-#[derive(Debug, Clone)]
-pub struct Properties {
-    /// Decimals of the token
-    decimals: u32,
-    /// Symbol of the token
-    symbol: String,
-}
+use subxt::storage::Storage;
+use subxt::utils::{AccountId32, MultiAddress, MultiSignature, H256};
+use subxt::{lightclient::LightClient, Metadata, OnlineClient};
 
 /// Represents the Substrate light client
 #[derive(Clone)]
@@ -42,10 +65,41 @@ pub struct SubLightClient {
     rpc_client: RpcClient,
     /// Chain ID of the Substrate chain
     chain_id: ChainId,
-    /// Properties of the chain
-    properties: Properties,
+    /// Properties of the chain, refreshed after runtime upgrades
+    properties: Arc<RwLock<Properties>>,
+    /// Properties set on the command line
+    properties_override: PropertiesOverride,
+    /// Unit of the amounts returned over RPC
+    balance_unit: BalanceUnit,
     /// Cache for the chain
     cache: BlockCache,
+    /// Extrinsic hash index
+    tx_index: TxIndex,
+    /// Signs the extrinsics wrapping Ethereum transactions
+    relayer: Option<Arc<Relayer>>,
+}
+
+/// Maximum number of recent blocks scanned when a transaction hash is not in the index
+const BACKFILL_DEPTH: u64 = 256;
+
+/// Maximum number of headers re-emitted when the best chain switches to another fork
+const MAX_REORG_DEPTH: usize = 64;
+
+/// How often the transaction pool is polled for `newPendingTransactions` subscriptions
+const PENDING_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Runtime API of the adapter pallet, used for nonces, `eth_call` and `eth_estimateGas` when the
+/// runtime implements it
+const EVM_ADAPTER_API: &str = "EvmAdapterApi";
+
+/// Blocks followed by subscriptions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum BlockStream {
+    /// New best blocks, may be reverted
+    Best,
+    /// Finalized blocks only
+    #[default]
+    Finalized,
 }
 
 impl SubLightClient {
@@ -55,21 +109,55 @@ impl SubLightClient {
         chain_id: ChainId,
         cache_capacity: Option<usize>,
     ) -> anyhow::Result<Self> {
-        let properties = Properties {
-            decimals: 10,
-            symbol: "Dot".to_string(),
-        };
+        let properties = fetch_properties(&rpc)
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to fetch chain properties: {:?}", e))?;
+        log::info!(
+            "Native token: {} ({} decimals)",
+            properties.symbol,
+            properties.decimals
+        );
 
         Ok(Self {
             inner: None,
             api,
             chain_id,
-            properties,
+            properties: Arc::new(RwLock::new(properties)),
+            properties_override: PropertiesOverride::default(),
+            balance_unit: BalanceUnit::default(),
             rpc_client: rpc,
             cache: BlockCache::new(cache_capacity),
+            tx_index: TxIndex::in_memory(DEFAULT_RETENTION)?,
+            relayer: None,
         })
     }
 
+    /// Override the chain properties reported by the node
+    pub fn with_properties_override(mut self, properties_override: PropertiesOverride) -> Self {
+        let properties = properties_override.apply(self.properties());
+        *self.properties.write() = properties;
+        self.properties_override = properties_override;
+        self
+    }
+
+    /// Return amounts in the given unit
+    pub fn with_balance_unit(mut self, balance_unit: BalanceUnit) -> Self {
+        self.balance_unit = balance_unit;
+        self
+    }
+
+    /// Use the given transaction index instead of the default in-memory one
+    pub fn with_tx_index(mut self, tx_index: TxIndex) -> Self {
+        self.tx_index = tx_index;
+        self
+    }
+
+    /// Submit Ethereum transactions signed by the relayer
+    pub fn with_relayer(mut self, relayer: Relayer) -> Self {
+        self.relayer = Some(Arc::new(relayer));
+        self
+    }
+
     pub async fn from_light_client(
         chain_spec: &str,
         chain_id: ChainId,
@@ -110,99 +198,233 @@ impl SubLightClient {
         self.chain_id
     }
 
+    /// Properties of the chain
+    pub fn properties(&self) -> Properties {
+        self.properties.read().clone()
+    }
+
+    /// Conversion of native token amounts to the unit returned over RPC
+    pub fn units(&self) -> Units {
+        Units::new(self.properties.read().decimals, self.balance_unit)
+    }
+
+    /// Apply runtime upgrades as they're enacted
+    ///
+    /// Keeps the metadata up to date, and fetches the chain properties again after each upgrade.
+    pub async fn run_runtime_updates(self) -> Result<(), SubEthError> {
+        let updater = self.api.updater();
+        let mut updates = updater.runtime_updates().await?;
+
+        while let Some(update) = updates.next().await {
+            let update = update?;
+            let spec_version = update.runtime_version().spec_version;
+            // the first version received is the current one
+            if updater.apply_update(update).is_err() {
+                continue;
+            }
+            log::info!("Runtime upgraded to spec version {}", spec_version);
+
+            let properties = self
+                .properties_override
+                .apply(fetch_properties(&self.rpc_client).await?);
+            log::info!(
+                "Native token: {} ({} decimals)",
+                properties.symbol,
+                properties.decimals
+            );
+            *self.properties.write() = properties;
+        }
+
+        Ok(())
+    }
+
     pub fn syncing(&self) -> Result<SyncStatus, SubEthError> {
         // let status = self.rpc.system_health().await?;
         Ok(SyncStatus::None)
     }
 
-    /// Current block number
+    /// Current best block number
     pub async fn block_number(&self) -> Result<u64, SubEthError> {
-        let latest_block = self.api.blocks().at_latest().await?;
-        Ok(latest_block.number().into())
+        let header = self
+            .rpc_client
+            .request::<<ChainConfig as subxt::Config>::Header>("chain_getHeader", rpc_params![])
+            .await?;
+
+        Ok(header.number.into())
     }
 
-    /// Get current block
+    /// Get block by number or tag
+    ///
+    /// Tags are resolved as in [`Self::block_hash_by_tag`]. The `pending` block is the best
+    /// block, extrinsics from the transaction pool aren't included.
     pub async fn get_block_by_number(
         &self,
         block_number: BlockNumberOrTag,
     ) -> Result<Option<EthBlock>, SubEthError> {
-        let substrate_block = match block_number {
-            BlockNumberOrTag::Latest => {
-                let current_block_number = self.block_number().await?;
-                if let Some(block) = self.cache.get_by_number(current_block_number) {
-                    return Ok(Some(block));
-                }
-
-                Some(self.api.blocks().at_latest().await?)
+        if let BlockNumberOrTag::Number(n) = block_number {
+            if let Some(block) = self.cache.get_by_number(n) {
+                return Ok(Some(block));
             }
-            BlockNumberOrTag::Number(n) => {
-                let block_hash = if let Some(hash) = self.cache.get_hash_by_number(n) {
-                    Some(hash)
-                } else {
-                    self.rpc_client
-                        .request::<Option<H256>>("chain_getBlockHash", rpc_params![n])
-                        .await?
-                };
+        }
 
-                if let Some(hash) = block_hash {
-                    self.cache.insert_number_to_hash(n, hash);
+        match self.block_hash_by_tag(block_number).await? {
+            Some(hash) => Ok(Some(self.get_block_by_hash(hash).await?)),
+            None => Ok(None),
+        }
+    }
 
-                    if let Some(block) = self.cache.get_by_hash(&hash) {
-                        return Ok(Some(block));
-                    }
+    /// Get the hash of the block with the given number
+    async fn block_hash_by_number(&self, number: u64) -> Result<Option<H256>, SubEthError> {
+        if let Some(hash) = self.cache.get_hash_by_number(number) {
+            return Ok(Some(hash));
+        }
 
-                    Some(self.api.blocks().at(hash).await?)
-                } else {
-                    None
-                }
-            }
-            _ => None,
-        };
+        let block_hash = self
+            .rpc_client
+            .request::<Option<H256>>("chain_getBlockHash", rpc_params![number])
+            .await?;
+        if let Some(hash) = block_hash {
+            self.cache.insert_number_to_hash(number, hash);
+        }
 
-        if let Some(block) = substrate_block {
-            let eth_block = convert_block(block, self.properties.decimals).await?;
-            self.cache.insert_block(eth_block.clone());
-            Ok(Some(eth_block))
-        } else {
-            Ok(None)
+        Ok(block_hash)
+    }
+
+    /// Resolve a block id to a block hash
+    async fn block_hash_by_id(&self, block: BlockId) -> Result<Option<H256>, SubEthError> {
+        match block {
+            BlockId::Hash(hash) => Ok(Some(hash.block_hash.0.into())),
+            BlockId::Number(tag) => self.block_hash_by_tag(tag).await,
+        }
+    }
+
+    /// Resolve a block number or tag to a block hash
+    ///
+    /// `latest` and `pending` resolve to the best block, `safe` and `finalized` to the finalized
+    /// head, `earliest` to genesis.
+    pub async fn block_hash_by_tag(
+        &self,
+        tag: BlockNumberOrTag,
+    ) -> Result<Option<H256>, SubEthError> {
+        match tag {
+            BlockNumberOrTag::Number(n) => self.block_hash_by_number(n).await,
+            BlockNumberOrTag::Earliest => self.block_hash_by_number(0).await,
+            BlockNumberOrTag::Latest | BlockNumberOrTag::Pending => Ok(Some(
+                self.rpc_client
+                    .request::<H256>("chain_getBlockHash", rpc_params![])
+                    .await?,
+            )),
+            BlockNumberOrTag::Safe | BlockNumberOrTag::Finalized => Ok(Some(
+                self.rpc_client
+                    .request::<H256>("chain_getFinalizedHead", rpc_params![])
+                    .await?,
+            )),
+        }
+    }
+
+    /// Resolve a block number or tag to a block number, as in [`Self::block_hash_by_tag`]
+    pub async fn block_number_by_tag(
+        &self,
+        tag: BlockNumberOrTag,
+    ) -> Result<Option<u64>, SubEthError> {
+        match tag {
+            BlockNumberOrTag::Number(n) => Ok(Some(n)),
+            BlockNumberOrTag::Earliest => Ok(Some(0)),
+            BlockNumberOrTag::Latest | BlockNumberOrTag::Pending => {
+                Ok(Some(self.block_number().await?))
+            }
+            BlockNumberOrTag::Safe | BlockNumberOrTag::Finalized => {
+                let Some(hash) = self.block_hash_by_tag(tag).await? else {
+                    return Ok(None);
+                };
+                let header = self
+                    .rpc_client
+                    .request::<Option<<ChainConfig as subxt::Config>::Header>>(
+                        "chain_getHeader",
+                        rpc_params![hash],
+                    )
+                    .await?;
+
+                Ok(header.map(|header| header.number.into()))
+            }
         }
     }
 
+    /// Storage at the given block, `latest` if not given
+    async fn storage_at(
+        &self,
+        block: Option<BlockId>,
+    ) -> Result<Storage<ChainConfig, OnlineClient<ChainConfig>>, SubEthError> {
+        let hash = self
+            .block_hash_by_id(block.unwrap_or_default())
+            .await?
+            .ok_or(SubEthError::BlockNotFound)?;
+
+        Ok(self.api.storage().at(hash))
+    }
+
     /// Get block by hash
     pub async fn get_block_by_hash(&self, block_hash: H256) -> Result<EthBlock, SubEthError> {
         if let Some(block) = self.cache.get_by_hash(&block_hash) {
             return Ok(block);
         }
         let block = self.api.blocks().at(block_hash).await?;
-        let eth_block = convert_block(block, self.properties.decimals).await?;
+        let eth_block = convert_block(block, &self.api.metadata(), self.units()).await?;
         self.cache.insert_block(eth_block.clone());
         Ok(eth_block)
     }
 
-    /// Get balance of an address
-    pub async fn get_balance(&self, address: Address) -> Result<U256, SubEthError> {
-        let account_id = AddressMapping::to_ss58(address);
+    /// Get balance of an address at the given block
+    pub async fn get_balance(
+        &self,
+        address: Address,
+        block: Option<BlockId>,
+    ) -> Result<U256, SubEthError> {
+        let state = self.storage_at(block).await?;
+        let account_id = AddressMapping::account_of(&state, &self.api.metadata(), address)
+            .await
+            .map_err(state_error)?;
         let query = storage().system().account(&account_id);
-        let current_block_hash = self.cache.get_hash_by_number(self.block_number().await?);
-
-        let account = if let Some(hash) = current_block_hash {
-            self.api.storage().at(hash).fetch(&query).await?
-        } else {
-            self.api.storage().at_latest().await?.fetch(&query).await?
-        };
+        let account = state.fetch(&query).await.map_err(state_error)?;
 
         if let Some(account_info) = account {
-            Ok(U256::from(account_info.data.free))
+            Ok(self.units().to_wei(U256::from(account_info.data.free)))
         } else {
             Ok(U256::ZERO)
         }
     }
 
-    /// Get transaction count
-    pub async fn get_transaction_count(&self, address: Address) -> Result<U256, SubEthError> {
-        let account_id = AddressMapping::to_ss58(address);
+    /// Get transaction count at the given block
+    pub async fn get_transaction_count(
+        &self,
+        address: Address,
+        block: Option<BlockId>,
+    ) -> Result<U256, SubEthError> {
+        let hash = self
+            .block_hash_by_id(block.unwrap_or_default())
+            .await?
+            .ok_or(SubEthError::BlockNotFound)?;
+        if self
+            .runtime_api_version(EVM_ADAPTER_API, hash)
+            .await?
+            .is_some()
+        {
+            let address = alloy_address_to_h160(address);
+            let nonce: u64 = self
+                .api
+                .runtime_api()
+                .at(hash)
+                .call_raw("EvmAdapterApi_nonce", Some(&address.encode()))
+                .await?;
+            return Ok(U256::from(nonce));
+        }
+
+        let state = self.api.storage().at(hash);
+        let account_id = AddressMapping::account_of(&state, &self.api.metadata(), address)
+            .await
+            .map_err(state_error)?;
         let query = storage().system().account(&account_id);
-        let account = self.api.storage().at_latest().await?.fetch(&query).await?;
+        let account = state.fetch(&query).await.map_err(state_error)?;
 
         if let Some(account_info) = account {
             Ok(U256::from(account_info.nonce))
@@ -211,10 +433,17 @@ impl SubLightClient {
         }
     }
 
-    /// Get code of a contract
+    /// Get code of a contract at the given block
     ///
     /// In our case, (for now) it returns `revert` bytecode if the given address is a pallet's contract address
-    pub fn get_code(&self, address: Address) -> Result<Vec<u8>, SubEthError> {
+    pub async fn get_code(
+        &self,
+        address: Address,
+        block: Option<BlockId>,
+    ) -> Result<Vec<u8>, SubEthError> {
+        // fail the same way as other state methods for unknown blocks
+        self.storage_at(block).await?;
+
         if let Some(name) = PalletContractMapping::pallet_name(address) {
             let code = format!("revert: {}", name);
             return Ok(code.into_bytes());
@@ -228,61 +457,64 @@ impl SubLightClient {
     /// This could be used to read the pallet's storage, however, `eth_call` is more flexible,
     /// so that's preferred for now
     ///
-    /// Due to the nature of Substrate's storage, this function is not implemented
+    /// The key is used as the raw storage key
     pub async fn get_storage_at(
         &self,
         _address: Address,
         key: H256,
+        block: Option<BlockId>,
     ) -> Result<Vec<u8>, SubEthError> {
         let storage_value = self
-            .api
-            .storage()
-            .at_latest()
+            .storage_at(block)
             .await?
             .fetch_raw(key.as_bytes())
-            .await?
+            .await
+            .map_err(state_error)?
             .unwrap_or_default();
 
         Ok(storage_value)
     }
 
-    // pub async fn get_storage_client(
-    //     &self,
-    //     at: BlockNumberOrTag,
-    // ) -> Result<StorageClient<ChainConfig, OnlineClient<ChainConfig>>, SubEthError> {
-    //     if let Some(at) = at.as_number() {
-    //         if let Some(hash) = self.cache.get_hash_by_number(at) {
-    //             Ok(self.api.storage().at(hash))
-    //         } else {
-    //             self.api.storage().at_latest().await.map_err(Into::into)
-    //         }
-    //     }
-    // }
-
     /// Get transaction by hash
     ///
-    /// Please, use `get_transaction_by_block_and_index` instead
-    pub async fn _get_transaction_by_hash(
+    /// Looks the extrinsic up in the transaction index. If it's not there, recent blocks that
+    /// haven't been indexed yet are backfilled before giving up.
+    pub async fn get_transaction_by_hash(
         &self,
-        _tx_hash: B256,
+        tx_hash: B256,
     ) -> Result<Option<EthTransaction>, SubEthError> {
-        unimplemented!("Use `get_transaction_by_block_and_index` instead")
+        match self.locate_transaction(tx_hash).await? {
+            Some(location) => {
+                self.get_transaction_by_block_hash_and_index(
+                    location.block_hash,
+                    Index(location.index as usize),
+                )
+                .await
+            }
+            None => Ok(None),
+        }
     }
 
-    pub async fn get_transaction_by_block_and_index(
+    /// Find where an extrinsic is included, backfilling the index if needed
+    async fn locate_transaction(&self, tx_hash: B256) -> Result<Option<TxLocation>, SubEthError> {
+        let tx_hash = H256::from(tx_hash.0);
+
+        match self.tx_index.get(&tx_hash)? {
+            Some(location) => Ok(Some(location)),
+            None => {
+                self.backfill_recent(Some(tx_hash)).await?;
+                Ok(self.tx_index.get(&tx_hash)?)
+            }
+        }
+    }
+
+    /// Get transaction by block hash and its index in the block
+    pub async fn get_transaction_by_block_hash_and_index(
         &self,
-        block: BlockNumberOrTag,
+        block_hash: H256,
         tx_index: Index,
     ) -> Result<Option<EthTransaction>, SubEthError> {
-        let number = match block {
-            BlockNumberOrTag::Latest => self.block_number().await?,
-            BlockNumberOrTag::Number(n) => n,
-            _ => {
-                return Ok(None);
-            }
-        };
-
-        if let Some(eth_block) = self.cache.get_by_number(number) {
+        if let Some(eth_block) = self.cache.get_by_hash(&block_hash) {
             if let Some(tx) = eth_block
                 .transactions
                 .txns()
@@ -292,19 +524,6 @@ impl SubLightClient {
             }
         }
 
-        let block_hash = if let Some(hash) = self.cache.get_hash_by_number(number) {
-            Some(hash)
-        } else {
-            self.rpc_client
-                .request::<Option<H256>>("chain_getBlockHash", rpc_params![number])
-                .await?
-        };
-
-        let block_hash = match block_hash {
-            Some(hash) => hash,
-            None => return Ok(None),
-        };
-
         let block = self.api.blocks().at(block_hash).await?;
 
         let ext = block
@@ -317,7 +536,9 @@ impl SubLightClient {
             let transaction = convert_extrinsic(
                 (block.number().into(), block_hash.0),
                 ext,
-                self.properties.decimals,
+                &block.storage(),
+                &self.api.metadata(),
+                self.units(),
             )
             .await?;
             Ok(Some(transaction))
@@ -326,33 +547,236 @@ impl SubLightClient {
         }
     }
 
-    pub async fn _get_transaction_receipt(
+    pub async fn get_transaction_by_block_and_index(
+        &self,
+        block: BlockNumberOrTag,
+        tx_index: Index,
+    ) -> Result<Option<EthTransaction>, SubEthError> {
+        match self.block_hash_by_tag(block).await? {
+            Some(hash) => {
+                self.get_transaction_by_block_hash_and_index(hash, tx_index)
+                    .await
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Get the receipt of a transaction by its hash
+    pub async fn get_transaction_receipt(
         &self,
-        _tx_hash: B256,
+        tx_hash: B256,
     ) -> Result<Option<TransactionReceipt>, SubEthError> {
-        unimplemented!("Use `get_transaction_by_block_and_index` instead")
+        let Some(location) = self.locate_transaction(tx_hash).await? else {
+            return Ok(None);
+        };
+
+        let receipts = self.receipts_at(location.block_hash).await?;
+
+        Ok(receipts.into_iter().nth(location.index as usize))
+    }
+
+    /// Get the receipts of all transactions in a block
+    pub async fn get_block_receipts(
+        &self,
+        block: BlockId,
+    ) -> Result<Option<Vec<TransactionReceipt>>, SubEthError> {
+        match self.block_hash_by_id(block).await? {
+            Some(hash) => Ok(Some(self.receipts_at(hash).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Get the logs of all transactions in a block
+    pub async fn get_block_logs(&self, block: BlockId) -> Result<Vec<Log>, SubEthError> {
+        let Some(hash) = self.block_hash_by_id(block).await? else {
+            return Ok(vec![]);
+        };
+
+        let logs = self
+            .receipts_at(hash)
+            .await?
+            .into_iter()
+            .flat_map(|receipt| receipt.inner.logs().to_vec())
+            .collect();
+
+        Ok(logs)
+    }
+
+    /// Hashes of the extrinsics in the node's transaction pool
+    pub async fn pending_transaction_hashes(&self) -> Result<Vec<B256>, SubEthError> {
+        let extrinsics = self
+            .rpc_client
+            .request::<Vec<alloy_primitives::Bytes>>("author_pendingExtrinsics", rpc_params![])
+            .await?;
+
+        Ok(extrinsics
+            .iter()
+            .map(|ext| B256::from(sp_core::blake2_256(ext)))
+            .collect())
+    }
+
+    /// Stream of new block hashes
+    pub async fn block_hashes(
+        &self,
+        blocks: BlockStream,
+    ) -> Result<impl Stream<Item = B256>, SubEthError> {
+        let block_stream = match blocks {
+            BlockStream::Best => self.api.blocks().subscribe_best().await?,
+            BlockStream::Finalized => self.api.blocks().subscribe_finalized().await?,
+        };
+
+        Ok(block_stream
+            .filter_map(|block| async move { block.ok().map(|block| B256::from(block.hash().0)) }))
+    }
+
+    /// Build receipts of the block with the given hash from its events
+    async fn receipts_at(&self, block_hash: H256) -> Result<Vec<TransactionReceipt>, SubEthError> {
+        let eth_block = self.get_block_by_hash(block_hash).await?;
+        let events = self.api.events().at(block_hash).await?;
+        let metadata = self.api.metadata();
+
+        let accounts = events
+            .iter()
+            .filter_map(Result::ok)
+            .flat_map(|event| event_accounts(&event, &metadata))
+            .collect::<Vec<_>>();
+        let addresses =
+            AddressMapping::addresses_of(&self.api.storage().at(block_hash), &metadata, accounts)
+                .await
+                .map_err(state_error)?;
+
+        block_receipts(&eth_block, &events, &metadata, &addresses, self.units())
+    }
+
+    /// Fee data of the block with the given hash, see [`crate::fee`]
+    pub async fn block_fees(&self, hash: H256) -> Result<BlockFees, SubEthError> {
+        let units = self.units();
+        let metadata = self.api.metadata();
+        let pallet_base_fee = pallet_base_fee_per_gas(&metadata);
+
+        let next_base_fee_per_gas = match pallet_base_fee {
+            Some(base_fee) => units.to_wei(base_fee).saturating_to(),
+            None => self.inclusion_base_fee_per_gas(hash).await?,
+        };
+        let events = self.api.events().at(hash).await?;
+        let (gas_used, tips) = block_usage(&events, &metadata, pallet_base_fee, units)?;
+
+        Ok(BlockFees {
+            next_base_fee_per_gas,
+            gas_used,
+            gas_limit: self.block_gas_limit()?,
+            tips,
+        })
     }
 
-    /// Read the storage of a pallet
-    pub async fn call(&self, request: TransactionRequest) -> Result<Option<Vec<u8>>, SubEthError> {
+    /// Base fee per gas of the block's child, in wei, from the inclusion fee of the reference
+    /// transaction, see [`crate::fee`]
+    async fn inclusion_base_fee_per_gas(&self, hash: H256) -> Result<u128, SubEthError> {
+        let runtime_api = self.api.runtime_api().at(hash);
+
+        let multiplier = self
+            .api
+            .storage()
+            .at(hash)
+            .fetch_or_default(&storage().transaction_payment().next_fee_multiplier())
+            .await
+            .map_err(state_error)?;
+        let weight = Weight::from_parts(REFERENCE_GAS.saturating_mul(WEIGHT_PER_GAS), 0);
+        let RuntimeBalance(weight_fee) = runtime_api
+            .call_raw(
+                "TransactionPaymentApi_query_weight_to_fee",
+                Some(&weight.encode()),
+            )
+            .await?;
+        let RuntimeBalance(length_fee) = runtime_api
+            .call_raw(
+                "TransactionPaymentApi_query_length_to_fee",
+                Some(&REFERENCE_LENGTH.encode()),
+            )
+            .await?;
+
+        Ok(base_fee_per_gas(
+            FixedU128::from_inner(multiplier.0),
+            weight_fee,
+            length_fee,
+            self.units(),
+        ))
+    }
+
+    /// Maximum gas of a block, from the maximum block weight
+    fn block_gas_limit(&self) -> Result<u64, SubEthError> {
+        let block_weights = self
+            .api
+            .constants()
+            .at(&constants().system().block_weights())?;
+
+        Ok(weight_to_gas(block_weights.max_block.ref_time))
+    }
+
+    /// Read the storage of a pallet at the given block
+    ///
+    /// Storage entries are read either as ABI view functions (see [`crate::storage_view`]), or
+    /// with a JSON [`StorageKey`] input, which returns the raw SCALE-encoded value. Views of the
+    /// token facades are answered from the state too, see [`crate::erc20`] and
+    /// [`crate::erc721`]. Requests that aren't storage reads or views are writes, dry-run with
+    /// `EvmAdapterApi` if the runtime implements it: a failed call is returned as a revert error,
+    /// and a successful one has no output.
+    pub async fn call(
+        &self,
+        request: TransactionRequest,
+        block: Option<BlockId>,
+    ) -> Result<Option<Vec<u8>>, SubEthError> {
         let dest = match request.to {
             Some(TxKind::Call(dest)) => dest,
             _ => return Ok(None),
         };
 
-        let pallet_name = match PalletContractMapping::pallet_name(dest) {
-            Some(name) => name,
-            None => return Ok(None),
-        };
+        let erc20_view = request
+            .input
+            .input()
+            .and_then(|input| Some((Token::from_address(dest)?, Erc20View::decode(input)?)));
+        if let Some((token, view)) = erc20_view {
+            return self.erc20_view(token, view, block).await;
+        }
+        let erc721_view = request.input.input().and_then(|input| {
+            Some((
+                PalletContractMapping::collection_id(dest)?,
+                Erc721View::decode(input)?,
+            ))
+        });
+        if let Some((collection_id, view)) = erc721_view {
+            let state = self.storage_at(block).await?;
+            return collection_view(collection_id, view, &state, &self.api.metadata()).await;
+        }
+        let storage_view = request.input.input().and_then(|input| {
+            let view = StorageView::find(&self.api.metadata(), dest, input.get(..4)?)?;
+            Some((view, input[4..].to_vec()))
+        });
+        if let Some((view, args)) = storage_view {
+            return self.storage_view(view, &args, block).await;
+        }
 
         // Parse storage key from input
-        let storage_key: StorageKey = match request
-            .input
-            .input
-            .and_then(|input| serde_json::from_slice(&input).ok())
-        {
-            Some(key) => key,
-            None => return Ok(None),
+        let storage_read = PalletContractMapping::pallet_name(dest).zip(
+            request
+                .input
+                .input()
+                .and_then(|input| serde_json::from_slice::<StorageKey>(input).ok()),
+        );
+        let Some((pallet_name, storage_key)) = storage_read else {
+            let hash = self
+                .block_hash_by_id(block.unwrap_or_default())
+                .await?
+                .ok_or(SubEthError::BlockNotFound)?;
+            if let Some(EvmDryRunResult { result: Err(e), .. }) =
+                self.evm_dry_run(&request, hash).await?
+            {
+                return Err(SubEthError::Reverted(dispatch_error_message(
+                    e,
+                    &self.api.metadata(),
+                )));
+            }
+            return Ok(None);
         };
 
         let metadata = self.api.metadata();
@@ -383,88 +807,783 @@ impl SubLightClient {
         }
 
         let value = self
-            .api
-            .storage()
-            .at_latest()
+            .storage_at(block)
             .await?
             .fetch_raw(final_key)
-            .await?;
+            .await
+            .map_err(state_error)?;
 
         Ok(value)
     }
 
-    /// Submit an EVM transaction to the chain via the evm-adapter pallet
+    /// ABI-encoded result of a view of an ERC-20 facade, see [`crate::erc20`]
     ///
-    /// This method creates and submits a transaction that calls pallet_evm_adapter::transact
-    pub async fn submit_evm_transaction(
+    /// `None` if the address has no token: the asset doesn't exist, or the runtime has no assets.
+    async fn erc20_view(
         &self,
-        transaction: subeth_primitives::EthereumTransaction,
-    ) -> Result<alloy_primitives::B256, SubEthError> {
-        use parity_scale_codec::Encode;
-
-        // Encode the transaction
-        let tx_encoded = transaction.encode();
-
-        // Create the extrinsic call dynamically
-        // Note: This is a simplified version. In production, you would:
-        // 1. Use a proper keypair for signing
-        // 2. Handle nonce management
-        // 3. Calculate proper fees
-
-        // For now, we'll use author_submitExtrinsic which submits unsigned
-        // In production, you'd use system.submit_transaction or similar
-        let call_data = {
-            // Pallet index 6, call index 0 (transact)
-            let mut data = vec![6u8, 0u8];
-            data.extend_from_slice(&tx_encoded);
-            data
+        token: Token,
+        view: Erc20View,
+        block: Option<BlockId>,
+    ) -> Result<Option<Vec<u8>>, SubEthError> {
+        let state = self.storage_at(block).await?;
+        let metadata = self.api.metadata();
+        match token {
+            Token::Native => self
+                .native_token_view(view, &state, &metadata)
+                .await
+                .map(Some),
+            Token::Asset(asset_id) => asset_view(asset_id, view, &state, &metadata).await,
+        }
+    }
+
+    /// ABI-encoded value of a storage entry called as a view, see [`crate::storage_view`]
+    ///
+    /// `None` if the entry is optional and isn't set.
+    async fn storage_view(
+        &self,
+        view: StorageView,
+        args: &[u8],
+        block: Option<BlockId>,
+    ) -> Result<Option<Vec<u8>>, SubEthError> {
+        let keys = view.decode_keys(args).ok_or_else(|| {
+            SubEthError::InvalidParams(format!("invalid arguments of {}", view.signature()))
+        })?;
+        let state = self.storage_at(block).await?;
+        let metadata = self.api.metadata();
+
+        let mut encoded_keys = Vec::with_capacity(keys.len());
+        for key in keys {
+            encoded_keys.push(match key {
+                ViewKey::Encoded(key) => key,
+                ViewKey::Account(address) => AddressMapping::account_of(&state, &metadata, address)
+                    .await
+                    .map_err(state_error)?
+                    .0
+                    .to_vec(),
+            });
+        }
+        let value = state
+            .fetch_raw(view.storage_key(&encoded_keys))
+            .await
+            .map_err(state_error)?;
+        let Some(value) = value.or_else(|| view.default_value().map(<[u8]>::to_vec)) else {
+            return Ok(None);
         };
 
-        // Submit the extrinsic
-        let hex_data = format!("0x{}", hex::encode(&call_data));
-        let tx_hash: subxt::utils::H256 = self
+        let fields = view.value_fields(&value, metadata.types()).ok_or_else(|| {
+            SubEthError::AdapterError {
+                message: format!("Failed to decode the value of {}", view.signature()),
+            }
+        })?;
+        let addresses = AddressMapping::addresses_of(&state, &metadata, output_accounts(&fields))
+            .await
+            .map_err(state_error)?;
+        Ok(Some(encode_output(&fields, &addresses)))
+    }
+
+    async fn native_token_view(
+        &self,
+        view: Erc20View,
+        state: &Storage<ChainConfig, OnlineClient<ChainConfig>>,
+        metadata: &Metadata,
+    ) -> Result<Vec<u8>, SubEthError> {
+        let properties = self.properties();
+        let amount = match view {
+            // the chain has no name for its token
+            Erc20View::Name | Erc20View::Symbol => return Ok(encode_string(&properties.symbol)),
+            Erc20View::Decimals => U256::from(properties.decimals),
+            Erc20View::TotalSupply => {
+                let query = storage().balances().total_issuance();
+                U256::from(state.fetch_or_default(&query).await.map_err(state_error)?)
+            }
+            Erc20View::BalanceOf(address) => {
+                let account_id = AddressMapping::account_of(state, metadata, address)
+                    .await
+                    .map_err(state_error)?;
+                let query = storage().system().account(&account_id);
+                let account = state.fetch(&query).await.map_err(state_error)?;
+                U256::from(account.map_or(0, |account| account.data.free))
+            }
+            Erc20View::Allowance { owner, spender } => {
+                let owner = AddressMapping::account_of(state, metadata, owner)
+                    .await
+                    .map_err(state_error)?;
+                let spender = AddressMapping::account_of(state, metadata, spender)
+                    .await
+                    .map_err(state_error)?;
+                let allowance = AddressMapping::registry_entry(
+                    state,
+                    metadata,
+                    "Allowances",
+                    &[owner.as_ref(), spender.as_ref()],
+                )
+                .await
+                .map_err(state_error)?;
+                allowance
+                    .and_then(|value| RuntimeBalance::decode(&mut &value[..]).ok())
+                    .map_or(U256::ZERO, |balance| U256::from(balance.0))
+            }
+        };
+
+        Ok(encode_uint(amount))
+    }
+
+    /// Estimate the gas used by the transaction, see [`crate::gas`]
+    pub async fn estimate_gas(
+        &self,
+        request: TransactionRequest,
+        block: Option<BlockId>,
+    ) -> Result<U256, SubEthError> {
+        let hash = self
+            .block_hash_by_id(block.unwrap_or_default())
+            .await?
+            .ok_or(SubEthError::BlockNotFound)?;
+        let metadata = self.api.metadata();
+        if let Some(dry_run) = self.evm_dry_run(&request, hash).await? {
+            log::debug!(
+                "Dry-run weight {:?}, gas required {}",
+                dry_run.weight,
+                dry_run.gas_required
+            );
+            return match dry_run.result {
+                Ok(()) => Ok(U256::from(dry_run.gas_required)),
+                Err(e) => Err(SubEthError::Reverted(dispatch_error_message(e, &metadata))),
+            };
+        }
+
+        let account = AddressMapping::account_of(
+            &self.api.storage().at(hash),
+            &metadata,
+            request.from.unwrap_or_default(),
+        )
+        .await
+        .map_err(state_error)?;
+        // the native call the pallet dispatches transfers the value in planck
+        let value = self.units().to_planck(request.value.unwrap_or_default());
+        let call = dispatched_call(&request, value, &metadata)?;
+        if let Some(error) = self.dry_run(&call, &account, hash).await? {
+            return Err(SubEthError::Reverted(error));
+        }
+
+        let transaction = self.unsigned_transaction(&request);
+        let transact = transact_call(&transaction, &metadata)?;
+
+        let call_info = self.query_info(call, &account, hash).await?;
+        let transact_info = self.query_info(transact, &account, hash).await?;
+        log::debug!(
+            "Estimated call weight {:?} ({:?}), transact weight {:?}, fee {}",
+            call_info.weight,
+            call_info.class,
+            transact_info.weight,
+            transact_info.partial_fee.0
+        );
+
+        let ref_time = call_info
+            .weight
+            .ref_time()
+            .saturating_add(transact_info.weight.ref_time());
+        Ok(U256::from(weight_to_gas(ref_time)))
+    }
+
+    /// Unsigned transaction to the adapter pallet built from the request, with `value` in wei like
+    /// the transactions of wallets
+    fn unsigned_transaction(&self, request: &TransactionRequest) -> EthereumTransaction {
+        EthereumTransaction {
+            transaction_type: TransactionType::Eip1559,
+            chain_id: self.chain_id,
+            nonce: request.nonce.unwrap_or_default(),
+            max_priority_fee_per_gas: Default::default(),
+            max_fee_per_gas: Default::default(),
+            gas_limit: request.gas.unwrap_or_default(),
+            to: alloy_address_to_h160(
+                request
+                    .to
+                    .and_then(|to| to.to().copied())
+                    .unwrap_or_default(),
+            ),
+            value: alloy_u256_to_sp_u256(request.value.unwrap_or_default()),
+            data: request
+                .input
+                .input()
+                .map(|input| input.to_vec())
+                .unwrap_or_default(),
+            access_list: vec![],
+            v: 0,
+            r: Default::default(),
+            s: Default::default(),
+        }
+    }
+
+    /// Dry-run the request with `EvmAdapterApi_dry_run`, `None` if the runtime doesn't implement
+    /// `EvmAdapterApi`
+    async fn evm_dry_run(
+        &self,
+        request: &TransactionRequest,
+        at: H256,
+    ) -> Result<Option<EvmDryRunResult>, SubEthError> {
+        if self
+            .runtime_api_version(EVM_ADAPTER_API, at)
+            .await?
+            .is_none()
+        {
+            return Ok(None);
+        }
+
+        let from = alloy_address_to_h160(request.from.unwrap_or_default());
+        let params = (from, self.unsigned_transaction(request)).encode();
+
+        Ok(Some(
+            self.api
+                .runtime_api()
+                .at(at)
+                .call_raw("EvmAdapterApi_dry_run", Some(&params))
+                .await?,
+        ))
+    }
+
+    /// Weight and fee of an extrinsic dispatching the call, with `TransactionPaymentApi_query_info`
+    async fn query_info(
+        &self,
+        call: Vec<u8>,
+        account: &AccountId32,
+        at: H256,
+    ) -> Result<RuntimeDispatchInfo, SubEthError> {
+        // the signature isn't checked when querying the fee
+        let extrinsic = self
+            .api
+            .tx()
+            .create_partial_signed_offline(&RawCall(call), Default::default())?
+            .sign_with_address_and_signature(
+                &MultiAddress::Id(account.clone()),
+                &MultiSignature::Sr25519([0u8; 64]),
+            );
+
+        let mut params = extrinsic.encoded().to_vec();
+        (extrinsic.encoded().len() as u32).encode_to(&mut params);
+
+        Ok(self
+            .api
+            .runtime_api()
+            .at(at)
+            .call_raw("TransactionPaymentApi_query_info", Some(&params))
+            .await?)
+    }
+
+    /// Dry-run the call with the account as origin, returning the dispatch error if it fails
+    ///
+    /// Nothing is checked if the runtime doesn't implement `DryRunApi`.
+    async fn dry_run(
+        &self,
+        call: &[u8],
+        account: &AccountId32,
+        at: H256,
+    ) -> Result<Option<String>, SubEthError> {
+        let Some(version) = self.runtime_api_version("DryRunApi", at).await? else {
+            return Ok(None);
+        };
+        let metadata = self.api.metadata();
+        let system = metadata
+            .pallet_by_name("System")
+            .ok_or(SubEthError::Unsupported)?;
+
+        // `OriginCaller::system(RawOrigin::Signed(account))`
+        let mut params = (system.index(), 1u8, account).encode();
+        params.extend_from_slice(call);
+        if version >= 2 {
+            DRY_RUN_XCM_VERSION.encode_to(&mut params);
+        }
+
+        let result: Result<CallDryRunEffects, DryRunError> = self
+            .api
+            .runtime_api()
+            .at(at)
+            .call_raw("DryRunApi_dry_run_call", Some(&params))
+            .await?;
+
+        match result {
+            Ok(effects) => Ok(effects
+                .execution_result
+                .err()
+                .map(|e| dispatch_error_message(e.error, &metadata))),
+            Err(e) => {
+                log::debug!("Dry-run unavailable: {:?}", e);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Version of a runtime API at the given block, `None` if the runtime doesn't implement it
+    async fn runtime_api_version(&self, name: &str, at: H256) -> Result<Option<u32>, SubEthError> {
+        #[derive(serde::Deserialize)]
+        struct RuntimeVersion {
+            apis: Vec<(String, u32)>,
+        }
+
+        let runtime_version: RuntimeVersion = self
             .rpc_client
-            .request("author_submitExtrinsic", rpc_params![hex_data])
+            .request("state_getRuntimeVersion", rpc_params![at])
             .await?;
+        let id = format!("0x{}", hex::encode(sp_core::blake2_64(name.as_bytes())));
 
-        Ok(alloy_primitives::B256::from_slice(tx_hash.as_bytes()))
+        Ok(runtime_version
+            .apis
+            .into_iter()
+            .find(|(api, _)| *api == id)
+            .map(|(_, version)| version))
     }
 
-    /// Subscribe new blocks
+    /// Submit an EVM transaction to the chain via the evm-adapter pallet
     ///
-    /// the extracted block and ethereum transactions
-    async fn subscribe_new_blocks(
+    /// With a relayer, the transaction is wrapped in `EvmAdapter.transact`, signed and paid for by
+    /// the relayer. Without one, it's submitted unsigned in `EvmAdapter.transact_unsigned`, and
+    /// validated by the pallet. Returns the Ethereum hash of the transaction, the one wallets
+    /// compute, under which the extrinsic is indexed (see [`Self::index_block`]).
+    pub async fn submit_evm_transaction(
         &self,
-        subscription_kind: SubscriptionKind,
-    ) -> Result<impl Stream<Item = Result<BlockNotification, SubEthError>>, SubEthError> {
-        let block_stream = match subscription_kind {
-            SubscriptionKind::NewHeads => self.api.blocks().subscribe_finalized().await,
-            _ => return Err(SubEthError::Unsupported),
-        }?;
-
-        Ok(block_stream.filter_map(|block| async {
+        transaction: EthereumTransaction,
+    ) -> Result<alloy_primitives::B256, SubEthError> {
+        let metadata = self.api.metadata();
+        let ext_hash = match &self.relayer {
+            Some(relayer) => {
+                let call = transact_call(&transaction, &metadata)?;
+                relayer
+                    .submit(&self.api, &self.rpc_client, &RawCall(call))
+                    .await?
+            }
+            None => {
+                let call = transact_unsigned_call(&transaction, &metadata)?;
+                self.api
+                    .tx()
+                    .create_unsigned(&RawCall(call))?
+                    .submit()
+                    .await?
+            }
+        };
+        log::debug!(
+            "Submitted transaction {:?} in extrinsic {:?}",
+            transaction.hash(),
+            ext_hash
+        );
+
+        Ok(alloy_primitives::B256::from(transaction.hash().0))
+    }
+
+    /// Add all extrinsics of the block to the transaction index
+    ///
+    /// Extrinsics carrying an Ethereum transaction are indexed under its hash too.
+    async fn index_block(&self, block: &SubstrateBlock) -> Result<(), SubEthError> {
+        let metadata = self.api.metadata();
+        let extrinsics = block.extrinsics().await?;
+        let tx_hashes = extrinsics.iter().map(|ext| ext.hash()).collect();
+        let eth_hashes = extrinsics
+            .iter()
+            .filter_map(|ext| Some((ethereum_hash(&ext, &metadata)?, ext.index())))
+            .collect();
+
+        self.tx_index
+            .insert_block(block.number().into(), block.hash(), tx_hashes, eth_hashes)?;
+
+        Ok(())
+    }
+
+    /// Index recent blocks that are missing from the transaction index
+    ///
+    /// Walks back from the best block at most [`BACKFILL_DEPTH`] blocks. If `until` is given,
+    /// stops as soon as that extrinsic is indexed.
+    async fn backfill_recent(&self, until: Option<H256>) -> Result<(), SubEthError> {
+        let best = self.block_number().await?;
+        let lowest = best.saturating_sub(BACKFILL_DEPTH);
+
+        for number in (lowest..=best).rev() {
+            if self.tx_index.block_hash(number)?.is_some() {
+                continue;
+            }
+
+            let block_hash = self
+                .rpc_client
+                .request::<Option<H256>>("chain_getBlockHash", rpc_params![number])
+                .await?;
+            let Some(block_hash) = block_hash else {
+                continue;
+            };
+
+            let block = self.api.blocks().at(block_hash).await?;
+            self.index_block(&block).await?;
+
+            if let Some(tx_hash) = until {
+                if self.tx_index.get(&tx_hash)?.is_some() {
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Keep the transaction index up to date
+    ///
+    /// Backfills recent blocks, then indexes every new best block as it arrives.
+    pub async fn run_indexer(self) -> Result<(), SubEthError> {
+        if let Some(last) = self.tx_index.last_indexed()? {
+            log::info!("Resuming transaction index from block #{}", last);
+        }
+        self.backfill_recent(None).await?;
+
+        let mut blocks = self.api.blocks().subscribe_best().await?;
+        while let Some(block) = blocks.next().await {
             match block {
-                Ok(block) => Some(Ok(BlockNotification {
-                    hash: block.hash().0.into(),
-                    is_new_best: false,
-                })),
-                Err(_) => Some(Err(SubEthError::AdapterError {
-                    message: "Error in block stream".to_string(),
-                })),
+                Ok(block) => {
+                    if let Err(e) = self.index_block(&block).await {
+                        log::warn!("Failed to index block #{}: {:?}", block.number(), e);
+                    }
+                }
+                Err(e) => {
+                    log::warn!("Block stream error in indexer: {:?}", e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Subscribe headers of new blocks
+    ///
+    /// When following best blocks and the best chain switches to another fork, the headers of the
+    /// new fork are emitted (oldest first) before the new best header.
+    async fn subscribe_new_heads(
+        &self,
+        blocks: BlockStream,
+    ) -> Result<impl Stream<Item = Result<SubscriptionResult, SubEthError>>, SubEthError> {
+        let block_stream = match blocks {
+            BlockStream::Best => self.api.blocks().subscribe_best().await?,
+            BlockStream::Finalized => self.api.blocks().subscribe_finalized().await?,
+        };
+
+        let state = (block_stream, self.clone(), HeadTracker::default());
+        Ok(stream::unfold(
+            state,
+            |(mut block_stream, client, mut tracker)| async move {
+                let headers = match block_stream.next().await? {
+                    Ok(block) => client.new_heads(block, &mut tracker).await,
+                    Err(e) => Err(e.into()),
+                };
+
+                Some((headers, (block_stream, client, tracker)))
+            },
+        )
+        .flat_map(flatten))
+    }
+
+    /// Headers to emit for a new block, including the blocks of a new fork that weren't emitted
+    async fn new_heads(
+        &self,
+        block: SubstrateBlock,
+        tracker: &mut HeadTracker,
+    ) -> Result<Vec<SubscriptionResult>, SubEthError> {
+        let mut route = vec![convert_header(&block).await?];
+
+        // walk back until the parent is a header that was already emitted
+        let mut number = u64::from(block.number());
+        let mut parent_hash = block.header().parent_hash;
+        while tracker.is_reorg(number.checked_sub(1), parent_hash) && route.len() <= MAX_REORG_DEPTH
+        {
+            let parent = self.api.blocks().at(parent_hash).await?;
+            route.push(convert_header(&parent).await?);
+            number -= 1;
+            parent_hash = parent.header().parent_hash;
+        }
+
+        Ok(route
+            .into_iter()
+            .rev()
+            .map(|header| {
+                tracker.record(header.number, H256::from(header.hash.0));
+                SubscriptionResult::Header(Box::new(header.into()))
+            })
+            .collect())
+    }
+
+    /// Subscribe logs matching the filter, from each new block
+    async fn subscribe_logs(
+        &self,
+        filter: Filter,
+        blocks: BlockStream,
+    ) -> Result<impl Stream<Item = Result<SubscriptionResult, SubEthError>>, SubEthError> {
+        let client = self.clone();
+        let block_hashes = self.block_hashes(blocks).await?;
+
+        Ok(block_hashes
+            .then(move |hash| {
+                let client = client.clone();
+                let filter = filter.clone();
+                async move {
+                    let logs = client.get_block_logs(BlockId::hash(hash)).await?;
+                    Ok(filter_logs(&filter, logs)
+                        .into_iter()
+                        .map(|log| SubscriptionResult::Log(Box::new(log)))
+                        .collect())
+                }
+            })
+            .flat_map(flatten))
+    }
+
+    /// Subscribe hashes of extrinsics entering the transaction pool
+    ///
+    /// The pool is polled with `author_pendingExtrinsics`, and every extrinsic is reported once.
+    fn subscribe_pending_transactions(
+        &self,
+    ) -> impl Stream<Item = Result<SubscriptionResult, SubEthError>> {
+        stream::unfold(
+            (self.clone(), HashSet::new()),
+            |(client, mut seen)| async move {
+                tokio::time::sleep(PENDING_POLL_INTERVAL).await;
+
+                let new = client.pending_transaction_hashes().await.map(|pending| {
+                    let new = pending
+                        .iter()
+                        .filter(|hash| !seen.contains(*hash))
+                        .map(|hash| SubscriptionResult::TransactionHash(*hash))
+                        .collect();
+                    seen = pending.into_iter().collect();
+                    new
+                });
+
+                Some((new, (client, seen)))
+            },
+        )
+        .flat_map(flatten)
+    }
+}
+
+/// Fetch the chain properties with `system_properties`
+async fn fetch_properties(rpc: &RpcClient) -> Result<Properties, SubEthError> {
+    let properties = rpc
+        .request::<serde_json::Map<String, serde_json::Value>>("system_properties", rpc_params![])
+        .await?;
+
+    Ok(Properties::from_system_properties(&properties))
+}
+
+/// ABI-encoded result of a view of an asset's ERC-20 facade, `None` if the asset doesn't exist
+async fn asset_view(
+    asset_id: u128,
+    view: Erc20View,
+    state: &Storage<ChainConfig, OnlineClient<ChainConfig>>,
+    metadata: &Metadata,
+) -> Result<Option<Vec<u8>>, SubEthError> {
+    if metadata.pallet_by_name(ASSETS_PALLET).is_none() {
+        return Ok(None);
+    }
+    let id = Value::u128(asset_id);
+    let Some(details) = pallet_entry(state, ASSETS_PALLET, "Asset", vec![id.clone()]).await? else {
+        return Ok(None);
+    };
+
+    let output = match view {
+        Erc20View::Name | Erc20View::Symbol | Erc20View::Decimals => {
+            let asset_metadata = pallet_entry(state, ASSETS_PALLET, "Metadata", vec![id]).await?;
+            let field = |name| asset_metadata.as_ref().and_then(|value| value.at(name));
+            match view {
+                Erc20View::Name => encode_string(&value_string(field("name"))),
+                Erc20View::Symbol => encode_string(&value_string(field("symbol"))),
+                _ => encode_uint(value_amount(field("decimals"))),
+            }
+        }
+        Erc20View::TotalSupply => encode_uint(value_amount(details.at("supply"))),
+        Erc20View::BalanceOf(address) => {
+            let account = AddressMapping::account_of(state, metadata, address)
+                .await
+                .map_err(state_error)?;
+            let entry = pallet_entry(
+                state,
+                ASSETS_PALLET,
+                "Account",
+                vec![id, Value::from_bytes(account)],
+            )
+            .await?;
+            encode_uint(value_amount(
+                entry.as_ref().and_then(|value| value.at("balance")),
+            ))
+        }
+        Erc20View::Allowance { owner, spender } => {
+            let owner = AddressMapping::account_of(state, metadata, owner)
+                .await
+                .map_err(state_error)?;
+            let spender = AddressMapping::account_of(state, metadata, spender)
+                .await
+                .map_err(state_error)?;
+            let keys = vec![id, Value::from_bytes(owner), Value::from_bytes(spender)];
+            let entry = pallet_entry(state, ASSETS_PALLET, "Approvals", keys).await?;
+            encode_uint(value_amount(
+                entry.as_ref().and_then(|value| value.at("amount")),
+            ))
+        }
+    };
+
+    Ok(Some(output))
+}
+
+/// ABI-encoded result of a view of a collection's ERC-721 facade, see [`crate::erc721`]
+///
+/// `None` if the collection, or the item of `ownerOf` and `tokenURI`, doesn't exist.
+async fn collection_view(
+    collection_id: u128,
+    view: Erc721View,
+    state: &Storage<ChainConfig, OnlineClient<ChainConfig>>,
+    metadata: &Metadata,
+) -> Result<Option<Vec<u8>>, SubEthError> {
+    if metadata.pallet_by_name(NFTS_PALLET).is_none() {
+        return Ok(None);
+    }
+    let collection = Value::u128(collection_id);
+    let details = pallet_entry(state, NFTS_PALLET, "Collection", vec![collection.clone()]).await?;
+    if details.is_none() {
+        return Ok(None);
+    }
+
+    let token_id = match view {
+        Erc721View::BalanceOf(address) => {
+            let account = AddressMapping::account_of(state, metadata, address)
+                .await
+                .map_err(state_error)?;
+            // the owner's items of the collection
+            let keys = vec![Value::from_bytes(account), collection];
+            let query = subxt::dynamic::storage(NFTS_PALLET, "Account", keys);
+            let mut items = state.iter(query).await.map_err(state_error)?;
+            let mut balance = 0u64;
+            while let Some(item) = items.next().await {
+                item.map_err(state_error)?;
+                balance += 1;
+            }
+            return Ok(Some(encode_uint(U256::from(balance))));
+        }
+        Erc721View::OwnerOf(token_id) | Erc721View::TokenUri(token_id) => token_id,
+    };
+    let Ok(item) = u128::try_from(token_id) else {
+        return Ok(None);
+    };
+    let keys = vec![collection, Value::u128(item)];
+    let Some(details) = pallet_entry(state, NFTS_PALLET, "Item", keys.clone()).await? else {
+        return Ok(None);
+    };
+
+    let output = match view {
+        Erc721View::TokenUri(_) => {
+            let item_metadata = pallet_entry(state, NFTS_PALLET, "ItemMetadataOf", keys).await?;
+            encode_string(&value_string(
+                item_metadata.as_ref().and_then(|value| value.at("data")),
+            ))
+        }
+        _ => {
+            let Some(owner) = value_account(details.at("owner")) else {
+                return Ok(None);
+            };
+            let address = AddressMapping::address_of(state, metadata, &owner)
+                .await
+                .map_err(state_error)?;
+            encode_address(address)
+        }
+    };
+
+    Ok(Some(output))
+}
+
+/// Value of an entry of a pallet's storage, `None` if it isn't set
+async fn pallet_entry(
+    state: &Storage<ChainConfig, OnlineClient<ChainConfig>>,
+    pallet: &str,
+    name: &str,
+    keys: Vec<Value>,
+) -> Result<Option<Value<u32>>, SubEthError> {
+    let query = subxt::dynamic::storage(pallet, name, keys);
+    match state.fetch(&query).await.map_err(state_error)? {
+        Some(value) => Ok(Some(value.to_value().map_err(subxt::Error::from)?)),
+        None => Ok(None),
+    }
+}
+
+/// Turn errors about discarded state into a clear error
+fn state_error(e: subxt::Error) -> SubEthError {
+    let message = e.to_string().to_lowercase();
+    if message.contains("discarded") || message.contains("pruned") {
+        return SubEthError::StateUnavailable;
+    }
+
+    e.into()
+}
+
+/// Recently emitted `newHeads`, used to detect reorgs
+#[derive(Debug, Default)]
+struct HeadTracker {
+    emitted: BTreeMap<u64, H256>,
+}
+
+impl HeadTracker {
+    /// Whether the parent of a new block differs from the emitted header at that height
+    ///
+    /// Blocks below the tracked range (or before anything was emitted) are assumed canonical.
+    fn is_reorg(&self, parent_number: Option<u64>, parent_hash: H256) -> bool {
+        let Some(parent_number) = parent_number else {
+            return false;
+        };
+
+        match self.emitted.first_key_value() {
+            Some((lowest, _)) if parent_number >= *lowest => {
+                self.emitted.get(&parent_number) != Some(&parent_hash)
             }
-        }))
+            _ => false,
+        }
     }
+
+    /// Record an emitted header, dropping headers of the replaced fork above it
+    fn record(&mut self, number: u64, hash: H256) {
+        self.emitted.split_off(&number);
+        self.emitted.insert(number, hash);
+
+        while self.emitted.len() > MAX_REORG_DEPTH {
+            self.emitted.pop_first();
+        }
+    }
+}
+
+/// Flatten a batch of subscription items into a stream
+fn flatten<T>(batch: Result<Vec<T>, SubEthError>) -> impl Stream<Item = Result<T, SubEthError>> {
+    let items = match batch {
+        Ok(items) => items.into_iter().map(Ok).collect(),
+        Err(e) => vec![Err(e)],
+    };
+
+    stream::iter(items)
 }
 
 /// Handle accepted subscription
 ///
-/// Pipes the block stream to the subscription sink
+/// Pipes the stream of the subscription to the subscription sink
 pub async fn handle_accepted_subscription(
     client: SubLightClient,
-    kind: SubscriptionKind,
+    subscription: EthSubscription,
+    blocks: BlockStream,
     sink: SubscriptionSink,
 ) -> Result<(), SubEthError> {
-    let mut stream = Box::pin(client.subscribe_new_blocks(kind).await?);
+    match subscription {
+        EthSubscription::NewHeads => {
+            pipe_to_sink(client.subscribe_new_heads(blocks).await?, sink).await
+        }
+        EthSubscription::Logs(filter) => {
+            pipe_to_sink(client.subscribe_logs(*filter, blocks).await?, sink).await
+        }
+        EthSubscription::NewPendingTransactions => {
+            pipe_to_sink(client.subscribe_pending_transactions(), sink).await
+        }
+    }
+}
+
+/// Send stream items to the sink until either of them is closed
+async fn pipe_to_sink<T: serde::Serialize>(
+    stream: impl Stream<Item = Result<T, SubEthError>>,
+    sink: SubscriptionSink,
+) -> Result<(), SubEthError> {
+    let mut stream = Box::pin(stream);
 
     loop {
         tokio::select! {
@@ -472,19 +1591,14 @@ pub async fn handle_accepted_subscription(
                 break;
             },
             maybe_notification = stream.next() => {
-                let notif = if let Some(notification) = maybe_notification {
-                    if let Ok(notif) = notification {
-                        notif
-                    } else {
-                        break ();
-                    }
+                let notif = if let Some(Ok(notif)) = maybe_notification {
+                    notif
                 } else {
-                    break ();
+                    break;
                 };
 
-
                 if sink.send(SubscriptionMessage::from_json(&notif)?).await.is_err() {
-                    break ();
+                    break;
                 }
             }
         }
@@ -494,7 +1608,23 @@ pub async fn handle_accepted_subscription(
 }
 
 /// Given a substrate block, convert it to an Ethereum block
-async fn convert_block(block: SubstrateBlock, decimals: u32) -> Result<EthBlock, SubEthError> {
+async fn convert_block(
+    block: SubstrateBlock,
+    metadata: &Metadata,
+    units: Units,
+) -> Result<EthBlock, SubEthError> {
+    let eth_header = convert_header(&block).await?;
+    let block_transactions = extract_transactions(block.hash().0, block, metadata, units).await?;
+
+    Ok(EthBlock {
+        header: eth_header,
+        transactions: alloy_rpc_types_eth::BlockTransactions::Full(block_transactions),
+        ..Default::default()
+    })
+}
+
+/// Given a substrate block, convert its header to an Ethereum header
+async fn convert_header(block: &SubstrateBlock) -> Result<EthHeader, SubEthError> {
     let block_hash = block.hash().0;
 
     let header = block.header().clone();
@@ -513,25 +1643,17 @@ async fn convert_block(block: SubstrateBlock, decimals: u32) -> Result<EthBlock,
         .now
         / 1000;
 
-    let block_transactions = extract_transactions(block_hash, block, decimals).await?;
-
-    let eth_header = EthHeader {
+    Ok(EthHeader {
         hash: block_hash.into(),
         inner: alloy_consensus::Header {
             parent_hash: parent_hash.into(),
             state_root: state_root.into(),
-            timestamp: timestamp.into(),
+            timestamp,
             number: header.number.into(),
             transactions_root: txs_root.into(),
             ..Default::default()
         },
         ..Default::default()
-    };
-
-    Ok(EthBlock {
-        header: eth_header,
-        transactions: alloy_rpc_types_eth::BlockTransactions::Full(block_transactions),
-        ..Default::default()
     })
 }
 
@@ -539,12 +1661,21 @@ async fn convert_block(block: SubstrateBlock, decimals: u32) -> Result<EthBlock,
 async fn extract_transactions(
     block_hash: [u8; 32],
     block: SubstrateBlock,
-    decimals: u32,
+    metadata: &Metadata,
+    units: Units,
 ) -> Result<Vec<EthTransaction>, SubEthError> {
     let mut transactions = vec![];
+    let storage = block.storage();
 
     for ext in block.extrinsics().await?.iter() {
-        let eth_tx = convert_extrinsic((block.number().into(), block_hash), ext, decimals).await?;
+        let eth_tx = convert_extrinsic(
+            (block.number().into(), block_hash),
+            ext,
+            &storage,
+            metadata,
+            units,
+        )
+        .await?;
 
         transactions.push(eth_tx);
     }
@@ -552,6 +1683,15 @@ async fn extract_transactions(
     Ok(transactions)
 }
 
+/// Ethereum hash of the transaction carried by an `EvmAdapter` extrinsic, `None` for other
+/// extrinsics
+fn ethereum_hash(
+    ext: &ExtrinsicDetails<ChainConfig, OnlineClient<ChainConfig>>,
+    metadata: &Metadata,
+) -> Option<H256> {
+    decode_transact(ext.call_bytes(), metadata).map(|transaction| transaction.hash())
+}
+
 /// Converts an extrinsic to eth-like transaction
 ///
 /// ### Notes
@@ -567,12 +1707,16 @@ async fn extract_transactions(
 /// - call data is the encoded call bytes
 /// - value is the transferred value
 /// - gas limit is hard coded to 21000000
+///
+/// Accounts are mapped to the addresses bound to them at the block, see [`AddressMapping`].
 async fn convert_extrinsic(
     (block_number, block_hash): (u64, [u8; 32]),
     ext: ExtrinsicDetails<ChainConfig, OnlineClient<ChainConfig>>,
-    decimals: u32,
+    storage: &Storage<ChainConfig, OnlineClient<ChainConfig>>,
+    metadata: &Metadata,
+    units: Units,
 ) -> Result<EthTransaction, SubEthError> {
-    let tx_hash = ext.hash();
+    let tx_hash = ethereum_hash(&ext, metadata).unwrap_or_else(|| ext.hash());
     let tx_index = ext.index();
     let from: [u8; 32] = match ext.address_bytes() {
         Some(addr) => addr.try_into().unwrap_or_default(), // TODO: investigate why this fails sometimes
@@ -581,7 +1725,9 @@ async fn convert_extrinsic(
     };
     // let is_unsigned = !ext.is_signed();
 
-    let from = AddressMapping::to_address(AccountId32::from(from));
+    let from = AddressMapping::address_of(storage, metadata, &AccountId32::from(from))
+        .await
+        .map_err(state_error)?;
 
     let (dest, value) = {
         // TODO: handle `TransferAll` as well
@@ -598,15 +1744,15 @@ async fn convert_extrinsic(
         };
 
         if let Some((account_id, value)) = vals {
-            match account_id {
-                MultiAddress::Id(id) => {
-                    (AddressMapping::to_address(id.0.into()), U256::from(value))
-                }
-                MultiAddress::Address32(id) => {
-                    (AddressMapping::to_address(id.into()), U256::from(value))
-                }
+            let account = match account_id {
+                MultiAddress::Id(id) => AccountId32::from(id.0),
+                MultiAddress::Address32(id) => AccountId32::from(id),
                 _ => unreachable!("Unsupported account type; qed"),
-            }
+            };
+            let dest = AddressMapping::address_of(storage, metadata, &account)
+                .await
+                .map_err(state_error)?;
+            (dest, U256::from(value))
         } else {
             let pallet_name = ext.pallet_name().map_err(|_| SubEthError::AdapterError {
                 message: "Could not fetch pallet name from extrinsic".to_string(),
@@ -632,7 +1778,7 @@ async fn convert_extrinsic(
             max_fee_per_gas: u128::MAX,
             max_priority_fee_per_gas: 0,
             to: dest.into(),
-            value: to_wei(value, decimals),
+            value: units.to_wei(value),
             input: input.into(),
             ..Default::default()
         },
@@ -644,7 +1790,7 @@ async fn convert_extrinsic(
 
     let eth_tx = EthTransaction {
         block_hash: Some(block_hash.into()),
-        block_number: Some(block_number).into(),
+        block_number: Some(block_number),
         from,
         transaction_index: Some(tx_index.into()),
         inner,
@@ -653,7 +1799,41 @@ async fn convert_extrinsic(
     Ok(eth_tx)
 }
 
-/// Convert value from chain's native token to wei
-fn to_wei(value: U256, decimals: u32) -> U256 {
-    value * U256::from(10).pow(U256::from(decimals))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_head_tracker_detects_reorg() {
+        let mut tracker = HeadTracker::default();
+        // nothing emitted yet
+        assert!(!tracker.is_reorg(Some(9), H256::repeat_byte(9)));
+
+        tracker.record(10, H256::repeat_byte(10));
+        tracker.record(11, H256::repeat_byte(11));
+
+        // extends the emitted chain
+        assert!(!tracker.is_reorg(Some(11), H256::repeat_byte(11)));
+        // sibling of the emitted #11
+        assert!(tracker.is_reorg(Some(10), H256::repeat_byte(0xaa)));
+        // below the tracked range
+        assert!(!tracker.is_reorg(Some(5), H256::repeat_byte(5)));
+        // genesis has no parent
+        assert!(!tracker.is_reorg(None, H256::zero()));
+    }
+
+    #[test]
+    fn test_head_tracker_replaces_fork() {
+        let mut tracker = HeadTracker::default();
+        for n in 1..=3u8 {
+            tracker.record(n as u64, H256::repeat_byte(n));
+        }
+
+        // the new fork forks off #1
+        tracker.record(2, H256::repeat_byte(0xbb));
+
+        assert_eq!(tracker.emitted.len(), 2);
+        assert!(!tracker.is_reorg(Some(2), H256::repeat_byte(0xbb)));
+        assert!(tracker.is_reorg(Some(2), H256::repeat_byte(2)));
+    }
 }
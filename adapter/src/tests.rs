@@ -1,4 +1,6 @@
-use alloy_primitives::{hex, Address, Bytes, TxKind, U256, U64};
+use alloy_consensus::{SignableTransaction, TxEip1559, TxEnvelope};
+use alloy_eips::eip2718::Encodable2718;
+use alloy_primitives::{hex, Address, Bytes, PrimitiveSignature, TxKind, U256, U64};
 use alloy_rpc_types_eth::{
     Block, BlockId, Index, Transaction, TransactionInput, TransactionRequest,
 };
@@ -12,7 +14,7 @@ use jsonrpsee::{
 use parity_scale_codec::Encode;
 use sp_core::{Pair, H160, H256, U256 as SpU256};
 use std::{path::PathBuf, time::Duration};
-use subeth_primitives::EthereumTransaction;
+use subeth_primitives::{EthereumTransaction, TransactionType};
 use tokio::process::Command;
 
 use crate::adapter::StorageKey;
@@ -58,8 +60,15 @@ async fn spawn_client(use_light_client: bool, url: Option<&str>) -> Result<tokio
             .arg("3")
             .arg("--rpc-port")
             .arg("8546");
+    } else if let Some(url) = url {
+        // local dev node, Alice relays the transactions
+        command
+            .arg("--url")
+            .arg(url)
+            .arg("--relayer-key")
+            .arg("//Alice");
     } else {
-        command.arg("--url").arg(url.unwrap_or(POLKADOT_RPC));
+        command.arg("--url").arg(POLKADOT_RPC);
     }
     command.kill_on_drop(true);
 
@@ -367,6 +376,7 @@ fn test_ethereum_transaction_encoding() {
 
     // Create the transaction
     let eth_tx = EthereumTransaction {
+        transaction_type: TransactionType::Eip1559,
         chain_id: 1,
         nonce: 0,
         max_priority_fee_per_gas: SpU256::zero(),
@@ -436,6 +446,7 @@ fn test_transfer_transaction_construction() {
 
     // Step 4: Create the EthereumTransaction
     let eth_tx = EthereumTransaction {
+        transaction_type: TransactionType::Eip1559,
         chain_id: 1,
         nonce: 0,
         max_priority_fee_per_gas: SpU256::zero(),
@@ -515,6 +526,7 @@ fn test_generic_scale_encoded_call_dispatch() {
     let generic_dispatch_address = H160::from(GENERIC_CALL_ADDRESS.0 .0);
 
     let eth_tx = EthereumTransaction {
+        transaction_type: TransactionType::Eip1559,
         chain_id: 1,
         nonce: 0,
         max_priority_fee_per_gas: SpU256::zero(),
@@ -601,6 +613,7 @@ fn test_call_data_encoding_various_amounts() {
 #[test]
 fn test_transaction_message_hash() {
     let eth_tx = EthereumTransaction {
+        transaction_type: TransactionType::Eip1559,
         chain_id: 1,
         nonce: 5,
         max_priority_fee_per_gas: SpU256::from(100u128),
@@ -630,6 +643,7 @@ fn test_transaction_message_hash() {
 #[test]
 fn test_signature_format() {
     let eth_tx = EthereumTransaction {
+        transaction_type: TransactionType::Eip1559,
         chain_id: 1,
         nonce: 0,
         max_priority_fee_per_gas: SpU256::zero(),
@@ -656,6 +670,7 @@ fn test_signature_format() {
 
     // Test with v=28
     let eth_tx2 = EthereumTransaction {
+        transaction_type: TransactionType::Eip1559,
         v: 28,
         ..eth_tx.clone()
     };
@@ -664,6 +679,7 @@ fn test_signature_format() {
 
     // Test with invalid recovery ID
     let eth_tx_invalid = EthereumTransaction {
+        transaction_type: TransactionType::Eip1559,
         v: 30, // Invalid
         ..eth_tx
     };
@@ -784,103 +800,54 @@ async fn test_e2e_balance_transfer() -> Result<()> {
 
     let ws_client = WsClientBuilder::default().build(url).await?;
 
-    // Alice's private key (standard dev key)
-    // 0xe5be9a5092b81bca64be81d212e7f2f9eba183bb7a90954f7b76361f6edb5c0a
-    let alice_priv_key_bytes =
-        hex::decode("e5be9a5092b81bca64be81d212e7f2f9eba183bb7a90954f7b76361f6edb5c0a").unwrap();
-    let alice_pair = sp_core::ecdsa::Pair::from_seed_slice(&alice_priv_key_bytes).unwrap();
+    // First dev account of Hardhat and Anvil, endowed in the dev chain's genesis
+    let signer_key =
+        hex::decode("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80").unwrap();
+    let signer_pair = sp_core::ecdsa::Pair::from_seed_slice(&signer_key).unwrap();
+    let signer_address = H160::from(hex!("f39fd6e51aad88f6f4ce6ab8827279cfffb92266"));
 
     // Recipient (Bob)
-    // 0x8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48
-    let bob_account =
-        hex::decode("8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48").unwrap();
+    let bob_account = hex!("8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48");
 
-    // Amount: 1 Unit (10^12)
+    // Amount: 1 token of the dev chain (12 decimals)
     let amount: u128 = 1_000_000_000_000;
 
-    // SCALE encode the call: Balances::transfer_allow_death
-    // Pallet index 10 (Balances in standard polkadot/substrate node usually, but might be 5)
-    // We should probably check metadata or try both?
-    // In `kitchensink-runtime` (substrate-node-template), Balances is usually index 10.
-    // In `polkadot`, it's 5.
-    // The user didn't specify the runtime. I'll try 10 first as it's common for dev nodes.
-    // Actually, let's use 10.
-    let pallet_index: u8 = 10;
+    // SCALE encode the call: Balances::transfer_allow_death (pallet 2 in the dev runtime)
+    let pallet_index: u8 = 2;
     let call_index: u8 = 0;
 
-    let mut call_data = Vec::new();
-    call_data.push(pallet_index);
-    call_data.push(call_index);
-    // Dest: MultiAddress::Id (0) + AccountId (32 bytes)
-    // Wait, `transfer_allow_death` takes `MultiAddress`.
-    // MultiAddress::Id is variant 0.
-    call_data.push(0u8); // MultiAddress::Id
+    let mut call_data = vec![pallet_index, call_index];
+    // Dest: MultiAddress::Id (variant 0) + AccountId (32 bytes)
+    call_data.push(0u8);
     call_data.extend_from_slice(&bob_account);
     call_data.extend_from_slice(&parity_scale_codec::Compact(amount).encode());
 
-    // Get nonce
-    // Get nonce
-    let alice_address_bytes: [u8; 20] = hex::decode("f24FF3a9CF04c71Dbc94D0b566f7A27B94566cac")
-        .unwrap()
-        .try_into()
-        .unwrap();
-    let alice_address_h160 = H160::from(alice_address_bytes);
-    // We can use eth_getTransactionCount
     let nonce_u256: U256 = ws_client
         .request(
             "eth_getTransactionCount",
-            rpc_params![format!("0x{:x}", alice_address_h160), "latest"],
+            rpc_params![format!("0x{:x}", signer_address), "latest"],
         )
         .await?;
     let nonce = nonce_u256.to::<u64>();
 
-    println!("Alice nonce: {}", nonce);
+    println!("Signer nonce: {}", nonce);
 
-    // Create EthereumTransaction
-    let mut eth_tx = EthereumTransaction {
-        chain_id: 1, // Default chain ID
+    // Create and sign an EIP-1559 transaction, as a wallet would
+    let tx = TxEip1559 {
+        chain_id: 42, // Default chain ID of the adapter and dev runtime
         nonce,
-        max_priority_fee_per_gas: SpU256::from(1_000_000_000u128), // 1 Gwei
-        max_fee_per_gas: SpU256::from(10_000_000_000u128),         // 10 Gwei
+        max_priority_fee_per_gas: 1_000_000_000, // 1 Gwei
+        max_fee_per_gas: 10_000_000_000,         // 10 Gwei
         gas_limit: 100000,
-        to: H160::zero(), // Generic dispatch
-        value: SpU256::zero(),
-        data: call_data,
-        access_list: vec![],
-        v: 0,
-        r: H256::zero(),
-        s: H256::zero(),
+        to: TxKind::Call(Address::ZERO), // Generic dispatch
+        value: U256::ZERO,
+        access_list: Default::default(),
+        input: call_data.into(),
     };
+    let signature = signer_pair.sign_prehashed(&tx.signature_hash().0);
+    let signature = PrimitiveSignature::try_from(&signature.0[..]).unwrap();
 
-    // Sign
-    let message_hash = eth_tx.message_hash();
-    let signature = alice_pair.sign_prehashed(&message_hash);
-
-    // Convert signature to v, r, s
-    // ecdsa signature is 64 bytes (r, s) + 1 byte (v/recovery_id)
-    // sp_core::ecdsa::Signature is [u8; 65]
-    let sig_bytes = signature.0;
-    let r = H256::from_slice(&sig_bytes[0..32]);
-    let s = H256::from_slice(&sig_bytes[32..64]);
-    let v = sig_bytes[64];
-
-    eth_tx.r = r;
-    eth_tx.s = s;
-    eth_tx.v = v as u64 + 27; // Legacy EIP-155? Or just 27/28?
-                              // EthereumTransaction encoding handles EIP-155 if chain_id is set?
-                              // Wait, `EthereumTransaction` struct in `subeth-primitives` has `chain_id`.
-                              // If we use EIP-155, v should be `recovery_id + 35 + chain_id * 2`.
-                              // If legacy, `recovery_id + 27`.
-                              // Let's stick to legacy 27/28 for simplicity if supported, or EIP-155.
-                              // The `signature()` method in `lib.rs` handles `v` conversion.
-                              // Let's try EIP-155.
-                              // v = recovery_id + 35 + chain_id * 2
-                              // v = v + 35 + 1 * 2 = v + 37.
-
-    // Let's try simple 27/28 first.
-    eth_tx.v = v as u64 + 27;
-
-    let encoded_tx = eth_tx.encode();
+    let encoded_tx = TxEnvelope::from(tx.into_signed(signature)).encoded_2718();
     let tx_hex = format!("0x{}", hex::encode(&encoded_tx));
 
     println!("Sending transaction: {}", tx_hex);
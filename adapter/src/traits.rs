@@ -1,10 +1,13 @@
 use alloy_primitives::{Address, Bytes, B256, U256, U64};
 use alloy_rpc_types_eth::{
     pubsub, state::StateOverride, Block, BlockId, BlockNumberOrTag, BlockOverrides, FeeHistory,
-    Filter, FilterChanges, Index, Log, Receipt, SyncStatus, Transaction, TransactionRequest, Work,
+    Filter, FilterChanges, Index, Log, SyncStatus, Transaction, TransactionReceipt,
+    TransactionRequest, Work,
 };
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 
+use crate::properties::Properties;
+
 /// Eth RPC interface.
 ///
 /// Follows [ETH-RPC](https://ethereum.org/en/developers/docs/apis/json-rpc/)
@@ -113,7 +116,11 @@ pub trait EthApi {
 
     /// Returns transaction receipt by transaction hash.
     #[method(name = "eth_getTransactionReceipt")]
-    async fn transaction_receipt(&self, hash: B256) -> RpcResult<Option<Receipt>>;
+    async fn transaction_receipt(&self, hash: B256) -> RpcResult<Option<TransactionReceipt>>;
+
+    /// Returns the receipts of all transactions in a block.
+    #[method(name = "eth_getBlockReceipts")]
+    async fn block_receipts(&self, block: BlockId) -> RpcResult<Option<Vec<TransactionReceipt>>>;
 
     // ########################################################################
     // State
@@ -137,16 +144,12 @@ pub trait EthApi {
     async fn transaction_count(
         &self,
         address: Address,
-        number_or_tag: Option<BlockNumberOrTag>,
+        number_or_tag: Option<BlockId>,
     ) -> RpcResult<U256>;
 
     /// Returns the code at given address at given time (block number).
     #[method(name = "eth_getCode")]
-    async fn code_at(
-        &self,
-        address: Address,
-        number_or_tag: Option<BlockNumberOrTag>,
-    ) -> RpcResult<Bytes>;
+    async fn code_at(&self, address: Address, number_or_tag: Option<BlockId>) -> RpcResult<Bytes>;
 
     // ########################################################################
     // Execute
@@ -177,21 +180,21 @@ pub trait EthApi {
 
     /// Returns current gas_price.
     #[method(name = "eth_gasPrice")]
-    fn gas_price(&self) -> RpcResult<U256>;
+    async fn gas_price(&self) -> RpcResult<U256>;
 
     /// Introduced in EIP-1159 for getting information on the appropriate priority fee to use.
     #[method(name = "eth_feeHistory")]
     async fn fee_history(
         &self,
-        block_count: U256,
-        newest_block: U256,
+        block_count: U64,
+        newest_block: BlockNumberOrTag,
         reward_percentiles: Option<Vec<f64>>,
     ) -> RpcResult<FeeHistory>;
 
     /// Introduced in EIP-1159, a Geth-specific and simplified priority fee oracle.
     /// Leverages the already existing fee history cache.
     #[method(name = "eth_maxPriorityFeePerGas")]
-    fn max_priority_fee_per_gas(&self) -> RpcResult<U256>;
+    async fn max_priority_fee_per_gas(&self) -> RpcResult<U256>;
 
     // ########################################################################
     // Mining
@@ -236,7 +239,7 @@ pub trait EthApi {
 pub trait EthFilterApi {
     /// Returns id of new filter.
     #[method(name = "eth_newFilter")]
-    fn new_filter(&self, filter: Filter) -> RpcResult<U256>;
+    async fn new_filter(&self, filter: Filter) -> RpcResult<U256>;
 
     /// Returns id of new block filter.
     #[method(name = "eth_newBlockFilter")]
@@ -244,7 +247,7 @@ pub trait EthFilterApi {
 
     /// Returns id of new block filter.
     #[method(name = "eth_newPendingTransactionFilter")]
-    fn new_pending_transaction_filter(&self) -> RpcResult<U256>;
+    async fn new_pending_transaction_filter(&self) -> RpcResult<U256>;
 
     /// Returns filter changes since last poll.
     #[method(name = "eth_getFilterChanges")]
@@ -278,3 +281,11 @@ pub trait EthPubSubApi {
         params: Option<pubsub::Params>,
     ) -> jsonrpsee::core::SubscriptionResult;
 }
+
+/// Subeth specific rpc interface.
+#[rpc(server)]
+pub trait SubEthApi {
+    /// Returns the chain properties (token decimals and symbol, SS58 prefix).
+    #[method(name = "subeth_chainProperties")]
+    fn chain_properties(&self) -> RpcResult<Properties>;
+}
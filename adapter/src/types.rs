@@ -1,13 +1,14 @@
 //! Primitive types used in the library.
 
 use alloy_rpc_types_eth::Transaction;
-use jsonrpsee::types::ErrorObject;
+use jsonrpsee::types::{error::INVALID_PARAMS_CODE, ErrorObject};
+use parity_scale_codec::{Decode, Input};
 use serde::{Deserialize, Serialize};
 use subxt::blocks::Block;
 use subxt::OnlineClient;
 use subxt::{
-    config::substrate::{BlakeTwo256, SubstrateExtrinsicParams, SubstrateHeader},
-    Config, PolkadotConfig, SubstrateConfig,
+    config::substrate::{BlakeTwo256, SubstrateHeader},
+    Config, PolkadotConfig,
 };
 
 pub type SubstrateBlock = Block<ChainConfig, OnlineClient<ChainConfig>>;
@@ -42,6 +43,23 @@ impl Config for ChainConfig {
     >;
 }
 
+/// Balance returned by a runtime API
+///
+/// The runtime's balance type is usually `u64` or `u128`, so the balance is decoded from all the
+/// remaining bytes (at most 16) and must come last.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RuntimeBalance(pub u128);
+
+impl Decode for RuntimeBalance {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, parity_scale_codec::Error> {
+        let mut bytes = [0u8; 16];
+        let len = input.remaining_len()?.unwrap_or_default().min(bytes.len());
+        input.read(&mut bytes[..len])?;
+
+        Ok(Self(u128::from_le_bytes(bytes)))
+    }
+}
+
 /// General error type for the Subeth library.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SubEthError {
@@ -55,6 +73,20 @@ pub enum SubEthError {
     AdapterError { message: String },
     /// Not supported yet
     Unsupported,
+    /// Filter doesn't exist or has expired
+    FilterNotFound,
+    /// Query exceeds the configured limits
+    LimitExceeded(String),
+    /// Requested block is unknown
+    BlockNotFound,
+    /// State of the requested block was pruned by the node
+    StateUnavailable,
+    /// Execution of the call failed
+    Reverted(String),
+    /// Invalid request params
+    InvalidParams(String),
+    /// Transaction rejected before submission
+    InvalidTransaction(String),
 }
 
 impl From<&'static str> for SubEthError {
@@ -94,6 +126,21 @@ impl From<subxt::Error> for SubEthError {
     }
 }
 
+impl From<subxt::ext::subxt_core::Error> for SubEthError {
+    fn from(e: subxt::ext::subxt_core::Error) -> Self {
+        subxt::Error::from(e).into()
+    }
+}
+
+impl From<redb::Error> for SubEthError {
+    fn from(e: redb::Error) -> Self {
+        log::error!("tx index error: {:?}", e);
+        SubEthError::AdapterError {
+            message: format!("Index err: {:?}", e),
+        }
+    }
+}
+
 impl From<SubEthError> for ErrorObject<'_> {
     fn from(error: SubEthError) -> Self {
         match error {
@@ -104,6 +151,27 @@ impl From<SubEthError> for ErrorObject<'_> {
                 ErrorObject::owned(500, "Adapter error", Some(message))
             }
             SubEthError::Unsupported => ErrorObject::owned(500, "Unsupported", None::<()>),
+            SubEthError::FilterNotFound => {
+                ErrorObject::owned(-32000, "filter not found", None::<()>)
+            }
+            SubEthError::LimitExceeded(message) => ErrorObject::owned(-32005, message, None::<()>),
+            SubEthError::BlockNotFound => ErrorObject::owned(-32000, "header not found", None::<()>),
+            SubEthError::StateUnavailable => ErrorObject::owned(
+                -32000,
+                "missing trie node: state of the requested block is not available (pruned), use an archive node",
+                None::<()>,
+            ),
+            SubEthError::Reverted(reason) => ErrorObject::owned(
+                3,
+                format!("execution reverted: {}", reason),
+                None::<()>,
+            ),
+            SubEthError::InvalidParams(message) => {
+                ErrorObject::owned(INVALID_PARAMS_CODE, message, None::<()>)
+            }
+            SubEthError::InvalidTransaction(message) => {
+                ErrorObject::owned(-32000, message, None::<()>)
+            }
         }
     }
 }
@@ -0,0 +1,139 @@
+//! Native token units
+//!
+//! Ethereum tooling expects balances and values in wei (18 decimals), while Substrate chains use
+//! the smallest unit of their native token (planck), with any number of decimals. Every amount
+//! returned over RPC (balances, transaction values, fees) goes through [`Units`].
+
+use alloy_primitives::U256;
+
+/// Decimals of wei
+pub const WEI_DECIMALS: u32 = 18;
+
+/// Unit of the amounts returned over RPC
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum BalanceUnit {
+    /// Amounts scaled to 18 decimals
+    #[default]
+    Wei,
+    /// Raw amounts in the chain's smallest unit, not scaled
+    Planck,
+}
+
+/// Converts amounts between planck and the unit returned over RPC
+///
+/// Chains with less than 18 decimals are scaled up, so planck -> wei -> planck is exact. Chains
+/// with more than 18 decimals are scaled down, rounding towards zero: amounts smaller than 1 wei
+/// are dropped, and wei -> planck -> wei is exact instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Units {
+    decimals: u32,
+    unit: BalanceUnit,
+}
+
+impl Units {
+    pub fn new(decimals: u32, unit: BalanceUnit) -> Self {
+        Self { decimals, unit }
+    }
+
+    /// Convert an amount of planck to wei, saturating on overflow
+    pub fn to_wei(self, planck: U256) -> U256 {
+        match self.unit {
+            BalanceUnit::Planck => planck,
+            BalanceUnit::Wei if self.decimals <= WEI_DECIMALS => {
+                pow10(WEI_DECIMALS - self.decimals)
+                    .map_or(U256::MAX, |factor| planck.saturating_mul(factor))
+            }
+            BalanceUnit::Wei => {
+                pow10(self.decimals - WEI_DECIMALS).map_or(U256::ZERO, |factor| planck / factor)
+            }
+        }
+    }
+
+    /// Convert an amount of wei to planck, saturating on overflow
+    pub fn to_planck(self, wei: U256) -> U256 {
+        match self.unit {
+            BalanceUnit::Planck => wei,
+            BalanceUnit::Wei if self.decimals <= WEI_DECIMALS => {
+                pow10(WEI_DECIMALS - self.decimals).map_or(U256::ZERO, |factor| wei / factor)
+            }
+            BalanceUnit::Wei => pow10(self.decimals - WEI_DECIMALS)
+                .map_or(U256::MAX, |factor| wei.saturating_mul(factor)),
+        }
+    }
+}
+
+/// `10^exp`, `None` if it doesn't fit in a `U256`
+fn pow10(exp: u32) -> Option<U256> {
+    U256::from(10).checked_pow(U256::from(exp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE_DOT: u64 = 10_000_000_000;
+    const ONE_ETHER: u128 = 1_000_000_000_000_000_000;
+
+    #[test]
+    fn test_to_wei() {
+        let dot = Units::new(10, BalanceUnit::Wei);
+        assert_eq!(dot.to_wei(U256::from(ONE_DOT)), U256::from(ONE_ETHER));
+        assert_eq!(dot.to_wei(U256::from(1)), U256::from(100_000_000u64));
+
+        // dev chain
+        let mini = Units::new(0, BalanceUnit::Wei);
+        assert_eq!(mini.to_wei(U256::from(1)), U256::from(ONE_ETHER));
+
+        let eth = Units::new(18, BalanceUnit::Wei);
+        assert_eq!(eth.to_wei(U256::from(ONE_ETHER)), U256::from(ONE_ETHER));
+    }
+
+    #[test]
+    fn test_more_than_18_decimals_rounds_down() {
+        let units = Units::new(20, BalanceUnit::Wei);
+        assert_eq!(units.to_wei(U256::from(100)), U256::from(1));
+        assert_eq!(units.to_wei(U256::from(199)), U256::from(1));
+        assert_eq!(units.to_wei(U256::from(99)), U256::ZERO);
+        assert_eq!(units.to_planck(U256::from(1)), U256::from(100));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let amounts = [0u128, 1, 42, ONE_DOT as u128, u64::MAX as u128, u128::MAX];
+
+        for decimals in [0, 6, 10, 12, 18] {
+            let units = Units::new(decimals, BalanceUnit::Wei);
+            for amount in amounts {
+                let planck = U256::from(amount);
+                assert_eq!(units.to_planck(units.to_wei(planck)), planck);
+            }
+        }
+
+        for decimals in [19, 24, 36] {
+            let units = Units::new(decimals, BalanceUnit::Wei);
+            for amount in amounts {
+                let wei = U256::from(amount);
+                assert_eq!(units.to_wei(units.to_planck(wei)), wei);
+            }
+        }
+    }
+
+    #[test]
+    fn test_saturates() {
+        let units = Units::new(0, BalanceUnit::Wei);
+        assert_eq!(units.to_wei(U256::MAX), U256::MAX);
+        assert_eq!(units.to_wei(U256::MAX / U256::from(10)), U256::MAX);
+
+        // `10^82` doesn't fit in a `U256`
+        let units = Units::new(100, BalanceUnit::Wei);
+        assert_eq!(units.to_wei(U256::MAX), U256::ZERO);
+        assert_eq!(units.to_planck(U256::from(1)), U256::MAX);
+    }
+
+    #[test]
+    fn test_planck() {
+        let units = Units::new(10, BalanceUnit::Planck);
+        assert_eq!(units.to_wei(U256::from(ONE_DOT)), U256::from(ONE_DOT));
+        assert_eq!(units.to_planck(U256::from(ONE_DOT)), U256::from(ONE_DOT));
+    }
+}
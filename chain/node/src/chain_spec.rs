@@ -26,7 +26,7 @@ pub type ChainSpec = sc_service::GenericChainSpec;
 
 fn props() -> Properties {
 	let mut properties = Properties::new();
-	properties.insert("tokenDecimals".to_string(), 0.into());
+	properties.insert("tokenDecimals".to_string(), 12.into());
 	properties.insert("tokenSymbol".to_string(), "MINI".into());
 	properties
 }
@@ -1,7 +1,7 @@
 //! # EVM Adapter Pallet
 //!
 //! This pallet provides EVM compatibility for Substrate chains by:
-//! - Accepting Ethereum-style transactions (EIP-1559)
+//! - Accepting Ethereum-style transactions (legacy with EIP-155, EIP-2930 and EIP-1559)
 //! - Verifying ECDSA signatures
 //! - Mapping EVM addresses (H160/AccountId20) to Substrate accounts (AccountId32)
 //! - Decoding SCALE-encoded RuntimeCall from transaction data
@@ -12,17 +12,86 @@
 //! The pallet acts as a bridge between Ethereum-style transactions and Substrate FRAME calls.
 //!
 //! **Transaction Structure:**
-//! - `to`: Can be any address (currently not validated, reserved for future use)
-//! - `data`: SCALE-encoded RuntimeCall (pallet_index + call_index + params)
-//! - Signature fields (`v`, `r`, `s`): ECDSA signature over the transaction
+//! - `to`: The zero address dispatches `data` as a SCALE-encoded RuntimeCall, the `Balances`
+//!   pallet address an ERC-20 `transfer`, `approve` or `transferFrom` of the native token, and the
+//!   address of an asset (see `PalletContractMapping::asset_address`) the same ERC-20 calls of the
+//!   `pallet_assets` asset, and the address of a collection (see
+//!   `PalletContractMapping::collection_address`) an ERC-721 `approve`, `transferFrom` or
+//!   `safeTransferFrom` of a `pallet_nfts` item. Any other address that isn't a pallet address is
+//!   an account receiving `value`
+//! - `data`: SCALE-encoded RuntimeCall (pallet_index + call_index + params), empty for transfers of
+//!   `value`
+//! - `value`: In wei, like wallets sign it, and transferred to the account mapped from `to` with
+//!   `Balances::transfer_keep_alive` once divided by `WeiPerPlanck`, rounding down. Transactions to
+//!   pallet addresses must have a zero value, pallets don't hold funds
+//! - Signature fields (`v`, `r`, `s`): ECDSA signature over the transaction's signing payload, the
+//!   same RLP payload wallets sign (see `EthereumTransaction::signing_payload`)
 //!
 //! **Flow:**
 //! 1. Verify ECDSA signature and recover signer (H160 address)
-//! 2. Map H160 → AccountId32 using Blake2-256 hash
+//! 2. Map H160 → AccountId32: the account bound to the address, or its Blake2-256 hash
 //! 3. Decode `data` field as SCALE-encoded RuntimeCall
 //! 4. Dispatch call with mapped account as signed origin
 //!
 //! This works with **any** runtime call - Balances, Staking, Governance, Democracy, Utility, etc.
+//!
+//! **Fees:**
+//!
+//! The weight declared for a transaction is the weight its `gas_limit` pays for through
+//! `GasWeightMapping`, with a proof size of `MaxProofSize`, and transactions whose call needs more
+//! are rejected. The actual weight of the call is refunded to the block once dispatched. The
+//! signer's mapped account pays `gas_limit` at the effective gas price (`BaseFeePerGas` plus
+//! `max_priority_fee_per_gas`, up to `max_fee_per_gas`) before the call is dispatched, and the
+//! gas the call didn't use is refunded. The fee goes to `OnChargeFee`, and the gas used and the
+//! effective gas price are deposited in `TransactionExecuted` or `TransactionFailed`. Gas prices
+//! are in wei and divided by `WeiPerPlanck`, rounding down, while `BaseFeePerGas` is in the native
+//! token's smallest unit.
+//!
+//! **Unsigned transactions:**
+//!
+//! `transact` is signed by a relayer account paying the extrinsic fees. `transact_unsigned` is
+//! submitted without a Substrate signature and validated in `ValidateUnsigned`: the signer is
+//! recovered, the transaction must be signed for the configured `ChainId`, its nonce can't be
+//! lower than the `frame_system` nonce of the mapped account, its call must decode and fit its
+//! gas limit, and the mapped account must exist and be able to pay `gas_limit * max_fee_per_gas`
+//! while staying alive. Transactions are tagged with `(signer, nonce)`, so the pool orders
+//! transactions of the same signer and only includes them once the previous nonce is. Executing
+//! the transaction bumps the mapped account's nonce and charges the gas used, even if the call
+//! fails, and a transaction that would fail before its fee is charged is invalid.
+//!
+//! **Address mapping:**
+//!
+//! Blake2-256 hashes can't be mapped back to addresses, so the pallet keeps a registry binding
+//! addresses to accounts both ways ([`Accounts`] and [`Addresses`]). An address is bound to its
+//! hashed account when it first signs a transaction. Receiving funds doesn't bind it, so no one
+//! can bind another's address. A native account can instead bind an address that isn't in use with
+//! `claim_account`, signed by the address, and the address's transactions then run as the native
+//! account.
+//!
+//! **ERC-20:**
+//!
+//! The native token's allowances of `approve` and `transferFrom` are kept in [`Allowances`], by
+//! account, and can also be managed natively with [`Pallet::approve`] and
+//! [`Pallet::transfer_from`]. Amounts are in the token's smallest unit.
+//!
+//! The ERC-20 calls of an asset are dispatched as `pallet_assets` calls, which keep the approvals:
+//! `approve` adds to the spender's approval (and reserves `ApprovalDeposit`), unless its amount is
+//! zero, which cancels it. The asset id is the `u128` in the asset's address, converted with
+//! `AssetIdConversion`.
+//!
+//! **ERC-721:**
+//!
+//! The ERC-721 calls of a collection are dispatched as `pallet_nfts` calls. `transferFrom` and
+//! `safeTransferFrom` are `transfer`s of the item, by its owner or an approved delegate, and `from`
+//! must be the item's owner. `approve` adds a delegate to the item's approvals, without deadline,
+//! unless it's the zero address, which clears them. The collection id is the `u128` in the
+//! collection's address, converted with `CollectionIdConversion`, and the item id is the token id,
+//! converted with `ItemIdConversion`.
+//!
+//! **Runtime API:**
+//!
+//! [`runtime_api::EvmAdapterApi`] exposes the address mapping, call decoding, nonces and chain id,
+//! and dry-runs transactions for `eth_call` and `eth_estimateGas`.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
@@ -34,53 +103,206 @@ mod mock;
 #[cfg(test)]
 mod tests;
 
+pub mod runtime_api;
+
 use alloc::vec::Vec;
-use codec::Decode;
+use codec::{Decode, Encode};
+use core::marker::PhantomData;
+use polkadot_sdk::frame_support::{traits::Get, weights::Weight};
 use polkadot_sdk::sp_io::crypto::secp256k1_ecdsa_recover;
+use polkadot_sdk::sp_runtime::traits::TryConvert;
 use polkadot_sdk::{
     polkadot_sdk_frame as frame,
-    sp_core::{H160, H256, U256},
+    sp_core::{hexdisplay::HexDisplay, H160, H256, U256},
 };
-use subeth_primitives::{EthereumTransaction, PalletContractMapping};
+use runtime_api::DryRunResult;
+use subeth_primitives::{erc20, erc721, EthereumTransaction, PalletContractMapping};
 
 pub use pallet::*;
 
+/// ERC-20 transaction to the native token or an asset, with the accounts mapped from the addresses
+enum Erc20Call<AccountId, Balance> {
+    /// `transfer(address,uint256)`
+    Transfer { dest: AccountId, amount: Balance },
+    /// `approve(address,uint256)`
+    Approve { spender: AccountId, amount: Balance },
+    /// `transferFrom(address,address,uint256)`
+    TransferFrom {
+        owner: AccountId,
+        dest: AccountId,
+        amount: Balance,
+    },
+}
+
+/// Asset, collection or item ids that are unsigned integers, like `u32`, converted from the numbers
+/// that fit
+pub struct NumericId<Id>(PhantomData<Id>);
+
+impl<Id: TryFrom<u128>> TryConvert<u128, Id> for NumericId<Id> {
+    fn try_convert(id: u128) -> Result<Id, u128> {
+        Id::try_from(id).map_err(|_| id)
+    }
+}
+
+/// Conversion between the gas of Ethereum transactions and the weight of the calls they dispatch
+pub trait GasWeightMapping {
+    /// Weight that the gas pays for
+    fn gas_to_weight(gas: u64) -> Weight;
+    /// Gas paying for the weight, rounded up
+    fn weight_to_gas(weight: Weight) -> u64;
+}
+
+/// `WeightPerGas` of `ref_time` per unit of gas
+///
+/// The proof size isn't priced in gas, the pallet bounds it by `MaxProofSize`.
+pub struct FixedGasWeightMapping<WeightPerGas>(PhantomData<WeightPerGas>);
+
+impl<WeightPerGas: Get<u64>> GasWeightMapping for FixedGasWeightMapping<WeightPerGas> {
+    fn gas_to_weight(gas: u64) -> Weight {
+        Weight::from_parts(gas.saturating_mul(WeightPerGas::get()), u64::MAX)
+    }
+
+    fn weight_to_gas(weight: Weight) -> u64 {
+        weight.ref_time().div_ceil(WeightPerGas::get().max(1))
+    }
+}
+
 #[frame::pallet]
 pub mod pallet {
     use super::*;
     use frame::prelude::*;
+    use polkadot_sdk::frame_support::dispatch::{GetDispatchInfo, Pays, PostDispatchInfo};
+    use polkadot_sdk::frame_support::traits::{
+        fungible::{self, Balanced},
+        tokens::{nonfungibles_v2, Fortitude, Precision, Preservation},
+        Imbalance, OnUnbalanced,
+    };
+    use polkadot_sdk::pallet_balances::WeightInfo as _;
+    use polkadot_sdk::sp_runtime::traits::{
+        CheckedSub, SaturatedConversion, UniqueSaturatedFrom, Zero,
+    };
 
     #[pallet::pallet]
     pub struct Pallet<T>(_);
 
-    use polkadot_sdk::pallet_balances;
+    use polkadot_sdk::{pallet_assets, pallet_balances, pallet_nfts};
 
     #[pallet::config]
-    pub trait Config: polkadot_sdk::frame_system::Config + pallet_balances::Config {
+    pub trait Config:
+        polkadot_sdk::frame_system::Config
+        + pallet_balances::Config
+        + pallet_assets::Config
+        + pallet_nfts::Config
+    {
         #[allow(deprecated)]
         type RuntimeEvent: From<Event<Self>>
             + IsType<<Self as polkadot_sdk::frame_system::Config>::RuntimeEvent>;
         /// The overarching call type that can be dispatched.
         /// Must be SCALE-decodable.
-        type RuntimeCall: Dispatchable<RuntimeOrigin = Self::RuntimeOrigin>
+        type RuntimeCall: Dispatchable<RuntimeOrigin = Self::RuntimeOrigin, PostInfo = PostDispatchInfo>
+            + GetDispatchInfo
             + Decode
-            + From<pallet_balances::Call<Self>>;
+            + From<pallet_balances::Call<Self>>
+            + From<pallet_assets::Call<Self>>
+            + From<pallet_nfts::Call<Self>>
+            + From<Call<Self>>;
+        /// Chain id that Ethereum transactions must be signed for (EIP-155)
+        #[pallet::constant]
+        type ChainId: Get<u64>;
+        /// Conversion between the gas of transactions and the weight of their calls
+        type GasWeightMapping: GasWeightMapping;
+        /// Proof size a transaction's call may use, declared in the transaction's weight since it
+        /// isn't priced in gas
+        #[pallet::constant]
+        type MaxProofSize: Get<u64>;
+        /// Wei per unit of the native token's smallest unit (planck), `10^(18 - decimals)`
+        ///
+        /// The value and gas prices of transactions are in wei and divided by it.
+        #[pallet::constant]
+        type WeiPerPlanck: Get<U256>;
+        /// Base fee per gas, in the native token's smallest unit
+        #[pallet::constant]
+        type BaseFeePerGas: Get<U256>;
+        /// Handler of the fees charged to the signers, `()` burns them
+        type OnChargeFee: OnUnbalanced<CreditOf<Self>>;
+        /// Conversion of the number in the address of an asset to its `pallet_assets` asset id
+        type AssetIdConversion: TryConvert<u128, <Self as pallet_assets::Config>::AssetId>;
+        /// Conversion of the number in the address of a collection to its `pallet_nfts` collection
+        /// id
+        type CollectionIdConversion: TryConvert<u128, <Self as pallet_nfts::Config>::CollectionId>;
+        /// Conversion of an ERC-721 token id to the `pallet_nfts` item id
+        type ItemIdConversion: TryConvert<u128, <Self as pallet_nfts::Config>::ItemId>;
     }
 
+    /// Fee withdrawn from the signer's account
+    pub type CreditOf<T> =
+        fungible::Credit<<T as frame_system::Config>::AccountId, pallet_balances::Pallet<T>>;
+
+    /// Balance of the native token
+    pub type BalanceOf<T> = <T as pallet_balances::Config>::Balance;
+
+    /// Balance of an asset
+    pub type AssetBalanceOf<T> = <T as pallet_assets::Config>::Balance;
+
+    /// Number of blocks an unsigned transaction stays in the pool when its nonce is the next one
+    ///
+    /// Each nonce missing before the transaction's shortens it by one block, so transactions far
+    /// ahead of the account's nonce don't linger.
+    pub const UNSIGNED_LONGEVITY: TransactionLongevity = 256;
+
+    /// Accounts bound to Ethereum addresses, by `claim_account` or when the address first transacts
+    #[pallet::storage]
+    pub type Accounts<T: Config> = StorageMap<_, Blake2_128Concat, H160, T::AccountId>;
+
+    /// Ethereum addresses bound to accounts, the reverse of [`Accounts`]
+    #[pallet::storage]
+    pub type Addresses<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, H160>;
+
+    /// Amounts of the owner's funds that spenders may transfer with `transfer_from`, by owner and
+    /// spender
+    #[pallet::storage]
+    pub type Allowances<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        Blake2_128Concat,
+        T::AccountId,
+        BalanceOf<T>,
+        ValueQuery,
+    >;
+
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
     pub enum Event<T: Config> {
         /// An EVM transaction was successfully executed
+        ///
+        /// `gas_used` was charged at `gas_price`, the effective gas price in the native token's
+        /// smallest unit.
         TransactionExecuted {
             from: H160,
             to: H160,
             transaction_hash: H256,
+            gas_used: u64,
+            gas_price: U256,
         },
-        /// An EVM transaction failed
+        /// An EVM transaction failed, its `gas_used` was still charged at `gas_price`
         TransactionFailed {
             from: H160,
             to: H160,
             error: Vec<u8>,
+            gas_used: u64,
+            gas_price: U256,
+        },
+        /// An Ethereum address was bound to an account
+        AddressBound {
+            address: H160,
+            account: T::AccountId,
+        },
+        /// The allowance of a spender was set by the owner
+        Approval {
+            owner: T::AccountId,
+            spender: T::AccountId,
+            amount: BalanceOf<T>,
         },
     }
 
@@ -99,6 +321,38 @@ pub mod pallet {
         UnsupportedPallet,
         /// Invalid transaction data
         InvalidTransactionData,
+        /// Transaction signed for another chain id
+        WrongChainId,
+        /// Transaction nonce isn't the next nonce of the signer's account
+        InvalidNonce,
+        /// The gas limit, or `MaxProofSize`, doesn't cover the weight of the call
+        GasLimitTooLow,
+        /// The max fee per gas is lower than the base fee per gas
+        MaxFeePerGasTooLow,
+        /// The signer's account can't pay `gas_limit` at the transaction's gas price
+        InsufficientBalanceForFee,
+        /// Non-zero value sent to a pallet address, only transfers to accounts carry value
+        ValueNotAccepted,
+        /// The claim isn't signed by the address
+        InvalidClaimSignature,
+        /// The address is already bound, or its hashed account is in use
+        AddressInUse,
+        /// The account is already bound to an address
+        AccountAlreadyBound,
+        /// The amount exceeds the spender's allowance
+        InsufficientAllowance,
+        /// The asset id of the address isn't a valid `pallet_assets` asset id
+        InvalidAssetId,
+        /// The collection id of the address isn't a valid `pallet_nfts` collection id
+        InvalidCollectionId,
+        /// The token id isn't a valid `pallet_nfts` item id
+        InvalidItemId,
+        /// The item doesn't exist or isn't owned by the `from` of the transfer
+        NotItemOwner,
+        /// The signature's `s` is above secp256k1n/2, which EIP-2 forbids
+        MalleableSignature,
+        /// The signer's mapped account doesn't exist, it must be funded before transacting
+        UnknownSender,
     }
 
     #[pallet::call]
@@ -108,51 +362,405 @@ pub mod pallet {
         /// This function:
         /// 1. Verifies the transaction signature
         /// 2. Maps the EVM address to a Substrate account
-        /// 3. Decodes the transaction data into a FRAME call
-        /// 4. Dispatches the call
+        /// 3. Checks the chain id and nonce, and bumps the account's nonce
+        /// 4. Decodes the transaction data into a FRAME call
+        /// 5. Charges the fee for `gas_limit`, dispatches the call and refunds the unused gas
+        ///
+        /// If the call fails, the nonce is still used and the gas the call used is charged, like in
+        /// Ethereum, so the transaction can't be replayed through the relayer.
         ///
         /// # Parameters
         /// - `origin`: Should be signed (for MVP)
         /// - `transaction`: The Ethereum transaction to execute
         #[pallet::call_index(0)]
-        #[pallet::weight(Weight::from_parts(10_000, 0) + T::DbWeight::get().writes(1))]
-        pub fn transact(origin: OriginFor<T>, transaction: EthereumTransaction) -> DispatchResult {
-            // For MVP, we accept transactions from any signed origin
-            // In production, this should be an unsigned transaction with proper validation
+        #[pallet::weight(Pallet::<T>::transact_weight(transaction))]
+        pub fn transact(
+            origin: OriginFor<T>,
+            transaction: EthereumTransaction,
+        ) -> DispatchResultWithPostInfo {
+            // The relayer signing the extrinsic pays its fees
             let _ = ensure_signed(origin)?;
 
             // Verify signature and recover signer
             let from = Self::verify_and_recover_signer(&transaction)?;
 
-            // Decode the call from transaction data
-            let call = Self::decode_call(&transaction)?;
+            // Replay protection
+            Self::use_nonce(&transaction, &Self::map_address_to_account(from))?;
+            Self::bind_address(from);
+
+            Self::execute(from, &transaction)
+        }
+
+        /// Submit an Ethereum transaction without a Substrate signature.
+        ///
+        /// The transaction is validated by `ValidateUnsigned` before being included. The nonce of
+        /// the mapped account is bumped and the gas used is charged even if the call fails, like
+        /// in Ethereum, so the transaction can't be replayed.
+        ///
+        /// # Parameters
+        /// - `origin`: Must be none
+        /// - `transaction`: The Ethereum transaction to execute
+        #[pallet::call_index(1)]
+        #[pallet::weight(Pallet::<T>::transact_weight(transaction))]
+        pub fn transact_unsigned(
+            origin: OriginFor<T>,
+            transaction: EthereumTransaction,
+        ) -> DispatchResultWithPostInfo {
+            ensure_none(origin)?;
+
+            let from = Self::verify_and_recover_signer(&transaction)?;
+            Self::use_nonce(&transaction, &Self::map_address_to_account(from))?;
+            Self::bind_address(from);
+
+            // the failures before the fee is charged are rejected by `validate_unsigned`
+            Self::execute(from, &transaction)
+        }
+
+        /// Bind an Ethereum address to the signed account
+        ///
+        /// `signature` is the address's EIP-191 (`personal_sign`) signature of
+        /// [`Pallet::claim_message`]. The transactions of the address then dispatch their calls
+        /// with the account as origin. Neither the address nor the account can be bound already,
+        /// and the address's hashed account must not exist, so no funds are left behind.
+        ///
+        /// # Parameters
+        /// - `origin`: The account to bind
+        /// - `address`: The Ethereum address to bind
+        /// - `signature`: The address's signature, `r ++ s ++ v`
+        #[pallet::call_index(2)]
+        #[pallet::weight(Weight::from_parts(10_000, 0) + T::DbWeight::get().reads_writes(3, 2))]
+        pub fn claim_account(
+            origin: OriginFor<T>,
+            address: H160,
+            signature: [u8; 65],
+        ) -> DispatchResult {
+            let account = ensure_signed(origin)?;
+            ensure!(
+                !Accounts::<T>::contains_key(address)
+                    && !frame_system::Pallet::<T>::account_exists(&Self::hashed_account(address)),
+                Error::<T>::AddressInUse
+            );
+            ensure!(
+                !Addresses::<T>::contains_key(&account),
+                Error::<T>::AccountAlreadyBound
+            );
+
+            let signer = Self::recover_personal_sign(&Self::claim_message(&account), &signature)?;
+            ensure!(signer == address, Error::<T>::InvalidClaimSignature);
+
+            Self::bind(address, account);
+            Ok(())
+        }
+
+        /// Allow `spender` to transfer up to `amount` of the signed account's funds with
+        /// `transfer_from`, replacing its previous allowance
+        ///
+        /// Backs the ERC-20 `approve` of the `Balances` address.
+        ///
+        /// # Parameters
+        /// - `origin`: The owner of the funds
+        /// - `spender`: The account allowed to transfer them
+        /// - `amount`: The new allowance
+        #[pallet::call_index(3)]
+        #[pallet::weight(Weight::from_parts(10_000, 0) + T::DbWeight::get().writes(1))]
+        pub fn approve(
+            origin: OriginFor<T>,
+            spender: T::AccountId,
+            amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            let owner = ensure_signed(origin)?;
+            Self::set_allowance(&owner, &spender, amount);
+            Self::deposit_event(Event::Approval {
+                owner,
+                spender,
+                amount,
+            });
+            Ok(())
+        }
 
-            // Map EVM address to Substrate account
-            let substrate_account = Self::map_address_to_account(from);
+        /// Transfer `amount` of `owner`'s funds to `dest`, out of the signed account's allowance
+        ///
+        /// Backs the ERC-20 `transferFrom` of the `Balances` address. The transfer may reap
+        /// `owner`'s account, like `Balances::transfer_allow_death`.
+        ///
+        /// # Parameters
+        /// - `origin`: The spender
+        /// - `owner`: The owner of the funds
+        /// - `dest`: The recipient
+        /// - `amount`: The amount to transfer
+        #[pallet::call_index(4)]
+        #[pallet::weight(
+            <T as pallet_balances::Config>::WeightInfo::transfer_allow_death()
+                + T::DbWeight::get().reads_writes(1, 1)
+        )]
+        pub fn transfer_from(
+            origin: OriginFor<T>,
+            owner: T::AccountId,
+            dest: T::AccountId,
+            amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            let spender = ensure_signed(origin)?;
+            let allowance = Allowances::<T>::get(&owner, &spender)
+                .checked_sub(&amount)
+                .ok_or(Error::<T>::InsufficientAllowance)?;
+
+            <pallet_balances::Pallet<T> as fungible::Mutate<T::AccountId>>::transfer(
+                &owner,
+                &dest,
+                amount,
+                Preservation::Expendable,
+            )?;
+            Self::set_allowance(&owner, &spender, allowance);
+            Ok(())
+        }
+    }
+
+    #[pallet::validate_unsigned]
+    impl<T: Config> ValidateUnsigned for Pallet<T> {
+        type Call = Call<T>;
+
+        fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+            let Call::transact_unsigned { transaction } = call else {
+                return InvalidTransaction::Call.into();
+            };
+
+            let (from, account_nonce) = Self::validate_transaction(transaction)?;
+            if transaction.nonce < account_nonce {
+                return InvalidTransaction::Stale.into();
+            }
+
+            let gap = transaction.nonce - account_nonce;
+            let mut valid = ValidTransaction::with_tag_prefix("EvmAdapter")
+                .priority(Self::priority(transaction))
+                .longevity(UNSIGNED_LONGEVITY.saturating_sub(gap).max(1))
+                .and_provides((from, transaction.nonce))
+                .propagate(true);
+            if gap > 0 {
+                valid = valid.and_requires((from, transaction.nonce - 1));
+            }
 
-            // Dispatch the call with the mapped account as origin
-            let origin = frame_system::RawOrigin::Signed(substrate_account).into();
+            valid.build()
+        }
+
+        fn pre_dispatch(call: &Self::Call) -> Result<(), TransactionValidityError> {
+            let Call::transact_unsigned { transaction } = call else {
+                return Err(InvalidTransaction::Call.into());
+            };
+
+            let (_, account_nonce) = Self::validate_transaction(transaction)?;
+            match transaction.nonce.cmp(&account_nonce) {
+                core::cmp::Ordering::Less => Err(InvalidTransaction::Stale.into()),
+                core::cmp::Ordering::Greater => Err(InvalidTransaction::Future.into()),
+                core::cmp::Ordering::Equal => Ok(()),
+            }
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        /// Weight of the pallet's own work: signature recovery, nonce, fee payment and binding the
+        /// signer's address
+        fn base_weight() -> Weight {
+            Weight::from_parts(10_000, 0) + T::DbWeight::get().reads_writes(3, 4)
+        }
+
+        /// Weight declared for the transaction: the weight its gas limit pays for
+        ///
+        /// Computed without decoding the call, which reads storage.
+        pub fn transact_weight(transaction: &EthereumTransaction) -> Weight {
+            Self::base_weight().saturating_add(Self::gas_weight(transaction))
+        }
+
+        /// Most weight the transaction's call may use: the weight of its gas limit, with a proof
+        /// size of at most `MaxProofSize`
+        fn gas_weight(transaction: &EthereumTransaction) -> Weight {
+            T::GasWeightMapping::gas_to_weight(transaction.gas_limit)
+                .min(Weight::from_parts(u64::MAX, T::MaxProofSize::get()))
+        }
+
+        /// Gas price paid by the transaction: the base fee plus the tip, up to `max_fee_per_gas`,
+        /// in the native token's smallest unit
+        pub fn effective_gas_price(transaction: &EthereumTransaction) -> Result<U256, Error<T>> {
+            let base_fee = T::BaseFeePerGas::get();
+            let max_fee_per_gas = Self::to_planck(transaction.max_fee_per_gas);
+            ensure!(max_fee_per_gas >= base_fee, Error::<T>::MaxFeePerGasTooLow);
+
+            Ok(base_fee
+                .saturating_add(Self::to_planck(transaction.max_priority_fee_per_gas))
+                .min(max_fee_per_gas))
+        }
+
+        /// Charge the fee for the gas limit, dispatch the call with the account mapped from `from`
+        /// as origin, refund the unused gas and deposit the outcome
+        ///
+        /// If the call fails, its changes are reverted, the gas it used is still charged, and
+        /// `TransactionFailed` is deposited. An error is only returned before the fee is charged.
+        fn execute(from: H160, transaction: &EthereumTransaction) -> DispatchResultWithPostInfo {
+            let call = Self::decode_call(transaction)?;
+            let info = call.get_dispatch_info();
+            ensure!(
+                info.call_weight.all_lte(Self::gas_weight(transaction)),
+                Error::<T>::GasLimitTooLow
+            );
+
+            let account = Self::map_address_to_account(from);
+            let gas_price = Self::effective_gas_price(transaction)?;
+            let max_fee = gas_price.saturating_mul(U256::from(transaction.gas_limit));
+            let fee = <pallet_balances::Pallet<T> as Balanced<T::AccountId>>::withdraw(
+                &account,
+                Self::to_balance(max_fee),
+                Precision::Exact,
+                Preservation::Preserve,
+                Fortitude::Polite,
+            )
+            .map_err(|_| Error::<T>::InsufficientBalanceForFee)?;
+
+            let origin = frame_system::RawOrigin::Signed(account.clone()).into();
             let result = call.dispatch(origin);
+            let call_weight = match &result {
+                Ok(post_info) => post_info.calc_actual_weight(&info),
+                Err(e) => e.post_info.calc_actual_weight(&info),
+            };
+
+            let gas_used =
+                T::GasWeightMapping::weight_to_gas(call_weight).min(transaction.gas_limit);
+            let (fee, refund) = fee.split(Self::to_balance(
+                gas_price.saturating_mul(U256::from(gas_used)),
+            ));
+            // only fails if the refund is below the existential deposit of a reaped account
+            let _ =
+                <pallet_balances::Pallet<T> as Balanced<T::AccountId>>::resolve(&account, refund);
+            T::OnChargeFee::on_unbalanced(fee);
 
+            let post_info = PostDispatchInfo {
+                actual_weight: Some(Self::base_weight().saturating_add(call_weight)),
+                pays_fee: Pays::Yes,
+            };
             match result {
                 Ok(_) => {
                     Self::deposit_event(Event::TransactionExecuted {
                         from,
                         to: transaction.to,
                         transaction_hash: transaction.hash(),
+                        gas_used,
+                        gas_price,
                     });
-                    Ok(())
+                    Ok(post_info)
                 }
                 Err(e) => {
                     Self::deposit_event(Event::TransactionFailed {
                         from,
                         to: transaction.to,
                         error: alloc::format!("{:?}", e.error).into_bytes(),
+                        gas_used,
+                        gas_price,
                     });
-                    Err(Error::<T>::DispatchFailed.into())
+                    Ok(post_info)
                 }
             }
         }
+
+        /// Check that the transaction is signed for this chain with the account's next nonce, and
+        /// bump the nonce
+        ///
+        /// The account must exist, bumping the nonce of an account that doesn't would create it
+        /// without a deposit.
+        fn use_nonce(
+            transaction: &EthereumTransaction,
+            account: &T::AccountId,
+        ) -> Result<(), Error<T>> {
+            ensure!(
+                transaction.chain_id == T::ChainId::get(),
+                Error::<T>::WrongChainId
+            );
+            ensure!(Self::account_exists(account), Error::<T>::UnknownSender);
+            let nonce = frame_system::Pallet::<T>::account_nonce(account);
+            ensure!(
+                transaction.nonce == nonce.saturated_into::<u64>(),
+                Error::<T>::InvalidNonce
+            );
+
+            frame_system::Pallet::<T>::inc_account_nonce(account);
+            Ok(())
+        }
+
+        /// Checks of an unsigned transaction shared by the pool and block validation
+        ///
+        /// Returns the signer and the current nonce of its mapped account.
+        fn validate_transaction(
+            transaction: &EthereumTransaction,
+        ) -> Result<(H160, u64), TransactionValidityError> {
+            // a transaction signed for another chain doesn't carry a valid signature for this one
+            if transaction.chain_id != T::ChainId::get() {
+                return Err(InvalidTransaction::BadProof.into());
+            }
+            let from = Self::verify_and_recover_signer(transaction)
+                .map_err(|_| InvalidTransaction::BadProof)?;
+            let call = Self::decode_call(transaction).map_err(|_| InvalidTransaction::Call)?;
+            if !call
+                .get_dispatch_info()
+                .call_weight
+                .all_lte(Self::gas_weight(transaction))
+            {
+                return Err(InvalidTransaction::ExhaustsResources.into());
+            }
+            Self::effective_gas_price(transaction).map_err(|_| InvalidTransaction::Payment)?;
+
+            let account = Self::map_address_to_account(from);
+            // like `CheckNonce`, a sender that doesn't exist can't pay, even a zero fee
+            if !Self::account_exists(&account) {
+                return Err(InvalidTransaction::Payment.into());
+            }
+            let max_fee = U256::from(transaction.gas_limit)
+                .saturating_mul(Self::to_planck(transaction.max_fee_per_gas));
+            // like the withdrawal of the fee, which keeps the account alive
+            let reducible =
+                <pallet_balances::Pallet<T> as fungible::Inspect<T::AccountId>>::reducible_balance(
+                    &account,
+                    Preservation::Preserve,
+                    Fortitude::Polite,
+                );
+            if reducible < Self::to_balance::<BalanceOf<T>>(max_fee) {
+                return Err(InvalidTransaction::Payment.into());
+            }
+
+            let account_nonce = frame_system::Pallet::<T>::account_nonce(&account);
+            Ok((from, account_nonce.saturated_into()))
+        }
+
+        /// Whether the account is referenced by a provider or is sufficient, as `CheckNonce`
+        /// requires of signers
+        fn account_exists(account: &T::AccountId) -> bool {
+            frame_system::Pallet::<T>::providers(account) > 0
+                || frame_system::Pallet::<T>::sufficients(account) > 0
+        }
+
+        /// Priority of an unsigned transaction: the most it may tip
+        fn priority(transaction: &EthereumTransaction) -> TransactionPriority {
+            let base_fee = T::BaseFeePerGas::get();
+            let tip_per_gas = Self::effective_gas_price(transaction)
+                .unwrap_or(base_fee)
+                .saturating_sub(base_fee);
+            let tip = tip_per_gas.saturating_mul(U256::from(transaction.gas_limit));
+            if tip > U256::from(TransactionPriority::MAX) {
+                TransactionPriority::MAX
+            } else {
+                tip.low_u64()
+            }
+        }
+
+        /// Convert an amount of wei to the native token's smallest unit, rounding down
+        fn to_planck(wei: U256) -> U256 {
+            wei / T::WeiPerPlanck::get().max(U256::one())
+        }
+
+        /// Convert an amount to a balance, saturating
+        fn to_balance<Balance: UniqueSaturatedFrom<u128>>(amount: U256) -> Balance {
+            if amount > U256::from(u128::MAX) {
+                u128::MAX.saturated_into()
+            } else {
+                amount.low_u128().saturated_into()
+            }
+        }
     }
 
     impl<T: Config> Pallet<T> {
@@ -160,6 +768,8 @@ pub mod pallet {
         pub fn verify_and_recover_signer(
             transaction: &EthereumTransaction,
         ) -> Result<H160, Error<T>> {
+            // `n - s` would be another valid signature of the transaction, under another hash
+            ensure!(transaction.has_low_s(), Error::<T>::MalleableSignature);
             let message_hash = transaction.message_hash();
             let signature = transaction
                 .signature()
@@ -178,16 +788,118 @@ pub mod pallet {
             Ok(H160::from(address))
         }
 
+        /// Nonce of the next transaction of the address
+        pub fn nonce(address: H160) -> u64 {
+            let account = Self::map_address_to_account(address);
+            frame_system::Pallet::<T>::account_nonce(&account).saturated_into()
+        }
+
+        /// Dispatch the transaction's call with the account mapped from `from` as origin, without
+        /// checking the transaction or charging fees
+        ///
+        /// The events deposited before are cleared, so only the call's are returned. Meant for
+        /// runtime API calls, whose changes are discarded.
+        pub fn dry_run(
+            from: H160,
+            transaction: &EthereumTransaction,
+        ) -> DryRunResult<<T as frame_system::Config>::RuntimeEvent> {
+            frame_system::Pallet::<T>::reset_events();
+
+            let (result, call_weight, gas_required) = match Self::decode_call(transaction) {
+                Ok(call) => {
+                    let info = call.get_dispatch_info();
+                    let origin =
+                        frame_system::RawOrigin::Signed(Self::map_address_to_account(from)).into();
+                    let (result, post_info) = match call.dispatch(origin) {
+                        Ok(post_info) => (Ok(()), post_info),
+                        Err(e) => (Err(e.error), e.post_info),
+                    };
+                    (
+                        result,
+                        post_info.calc_actual_weight(&info),
+                        T::GasWeightMapping::weight_to_gas(info.call_weight),
+                    )
+                }
+                Err(e) => (Err(e.into()), Weight::zero(), 0),
+            };
+
+            DryRunResult {
+                result,
+                weight: Self::base_weight().saturating_add(call_weight),
+                gas_required,
+                events: frame_system::Pallet::<T>::read_events_no_consensus()
+                    .map(|record| record.event)
+                    .collect(),
+            }
+        }
+
         /// Map an EVM address (H160/AccountId20) to a Substrate account (AccountId32)
         ///
-        /// This uses the same logic as in the adapter: hash the address to get AccountId32
+        /// The account bound to the address in [`Accounts`], or else its hashed account
         pub fn map_address_to_account(address: H160) -> T::AccountId {
+            Accounts::<T>::get(address).unwrap_or_else(|| Self::hashed_account(address))
+        }
+
+        /// Account of an address that isn't bound
+        ///
+        /// This uses the same logic as in the adapter: hash the address to get AccountId32
+        pub fn hashed_account(address: H160) -> T::AccountId {
             let mut input = [0u8; 32];
             input[..20].copy_from_slice(address.as_bytes());
             let hash = polkadot_sdk::sp_io::hashing::blake2_256(&input);
             T::AccountId::decode(&mut &hash[..]).expect("32 bytes can always decode to AccountId")
         }
 
+        /// Address bound to the account, if any
+        pub fn address_of(account: &T::AccountId) -> Option<H160> {
+            Addresses::<T>::get(account)
+        }
+
+        /// Message an address signs to be bound to the account with `claim_account`
+        pub fn claim_message(account: &T::AccountId) -> Vec<u8> {
+            alloc::format!(
+                "Claim account 0x{} on chain {}",
+                HexDisplay::from(&account.encode()),
+                T::ChainId::get()
+            )
+            .into_bytes()
+        }
+
+        /// Recover the address that signed the message with EIP-191 (`personal_sign`)
+        fn recover_personal_sign(message: &[u8], signature: &[u8; 65]) -> Result<H160, Error<T>> {
+            let mut prefixed =
+                alloc::format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+            prefixed.extend_from_slice(message);
+            let message_hash = polkadot_sdk::sp_io::hashing::keccak_256(&prefixed);
+
+            let pubkey = secp256k1_ecdsa_recover(signature, &message_hash)
+                .map_err(|_| Error::<T>::InvalidClaimSignature)?;
+            let address_hash = polkadot_sdk::sp_io::hashing::keccak_256(&pubkey);
+            Ok(H160::from_slice(&address_hash[12..]))
+        }
+
+        /// Bind the address to its hashed account, unless it's already bound
+        fn bind_address(address: H160) {
+            if !Accounts::<T>::contains_key(address) {
+                Self::bind(address, Self::hashed_account(address));
+            }
+        }
+
+        fn bind(address: H160, account: T::AccountId) {
+            Accounts::<T>::insert(address, &account);
+            Addresses::<T>::insert(&account, address);
+            Self::deposit_event(Event::AddressBound { address, account });
+        }
+
+        /// Set the allowance of `spender` over `owner`'s funds, removing it if it's zero
+        fn set_allowance(owner: &T::AccountId, spender: &T::AccountId, amount: BalanceOf<T>) {
+            if amount.is_zero() {
+                Allowances::<T>::remove(owner, spender);
+            } else {
+                Allowances::<T>::insert(owner, spender, amount);
+            }
+        }
+
         /// Decode the transaction data into a runtime call
         ///
         /// The transaction's `data` field contains a SCALE-encoded RuntimeCall:
@@ -199,8 +911,19 @@ pub mod pallet {
         pub fn decode_call(
             transaction: &EthereumTransaction,
         ) -> Result<<T as Config>::RuntimeCall, Error<T>> {
-            let pallet_name = Self::pallet_name_from_address(transaction.to)
-                .ok_or(Error::<T>::UnsupportedPallet)?;
+            if let Some(asset_id) = Self::asset_id_from_address(transaction.to) {
+                ensure!(transaction.value.is_zero(), Error::<T>::ValueNotAccepted);
+                return Self::decode_asset_call(asset_id, transaction);
+            }
+            if let Some(collection_id) = Self::collection_id_from_address(transaction.to) {
+                ensure!(transaction.value.is_zero(), Error::<T>::ValueNotAccepted);
+                return Self::decode_collection_call(collection_id, transaction);
+            }
+            let Some(pallet_name) = Self::pallet_name_from_address(transaction.to) else {
+                return Self::value_transfer(transaction);
+            };
+            // pallets don't hold funds, the value would be lost
+            ensure!(transaction.value.is_zero(), Error::<T>::ValueNotAccepted);
 
             match pallet_name.as_str() {
                 "Balances" => Self::decode_balances_call(transaction),
@@ -210,65 +933,234 @@ pub mod pallet {
             }
         }
 
-        fn decode_balances_call(
+        /// Transfer of the transaction's value to the account mapped from `to`
+        ///
+        /// The sender is kept alive, so its nonce isn't reset.
+        fn value_transfer(
             transaction: &EthereumTransaction,
         ) -> Result<<T as Config>::RuntimeCall, Error<T>> {
-            // Check selector: transfer(address,uint256) -> 0xa9059cbb
-            if transaction.data.len() < 4 || transaction.data[0..4] != [0xa9, 0x05, 0x9c, 0xbb] {
-                return Err(Error::<T>::CallDecodeFailed);
-            }
-
-            // Check data length: 4 (selector) + 32 (address) + 32 (value) = 68
-            if transaction.data.len() < 68 {
-                return Err(Error::<T>::InvalidTransactionData);
-            }
+            ensure!(
+                transaction.data.is_empty(),
+                Error::<T>::InvalidTransactionData
+            );
 
-            // Decode address (last 20 bytes of the first 32-byte word)
-            let mut address_bytes = [0u8; 20];
-            address_bytes.copy_from_slice(&transaction.data[16..36]);
-            let address = H160::from(address_bytes);
             use polkadot_sdk::sp_runtime::traits::StaticLookup;
-            let dest_account = Self::map_address_to_account(address);
-            let dest = T::Lookup::unlookup(dest_account);
-
-            // Decode value (second 32-byte word)
-            let mut value_bytes = [0u8; 32];
-            value_bytes.copy_from_slice(&transaction.data[36..68]);
-            let value = U256::from_big_endian(&value_bytes);
-
-            // Convert U256 to T::Balance
-            // For MVP, we assume T::Balance is u64 or u128 and fits
-            // We'll try to convert to u128 first
-            let amount_u128 = value.low_u128();
-            // Then convert to T::Balance. This assumes T::Balance can be created from u128
-            // or we just cast it. Since we can't easily do generic conversion here without more bounds,
-            // we'll limit to what fits in u128 and use `try_into` if possible, or just `saturated_into` if available.
-            // But `saturated_into` is for `SaturatedConversion`.
-            // Let's assume T::Balance is at least u64.
-
-            // A safer way for generic T::Balance is using `AtLeast32BitUnsigned` which `Balance` usually implements.
-            // But we don't have that bound here easily.
-            // Let's use `unique_saturated_into` from `sp_runtime::traits::UniqueSaturatedInto`?
-            // Or just `try_into`.
-
-            // For now, let's assume we can convert via Encode/Decode or similar hack,
-            // OR just add `From<u128>` or `From<u64>` bound to Balance.
-            // `pallet_balances::Config::Balance` has `Member + Parameter + AtLeast32BitUnsigned + Default + Copy + MaxEncodedLen`.
-            // `AtLeast32BitUnsigned` implies `From<u32>`.
-
-            // Let's try to decode it as T::Balance from the bytes directly? No, it's U256 BE.
-
-            // We will use `TryInto` if we add the bound, or `sp_runtime::traits::Bounded::max_value()` check.
-            // Actually, let's just use `value.low_u128()` and cast to `T::Balance` using `sp_runtime::traits::SaturatedConversion`.
-            use polkadot_sdk::sp_runtime::traits::SaturatedConversion;
-            let amount: <T as pallet_balances::Config>::Balance = amount_u128.saturated_into();
-
-            Ok(pallet_balances::Call::<T>::transfer_allow_death {
+            let dest = T::Lookup::unlookup(Self::map_address_to_account(transaction.to));
+
+            Ok(pallet_balances::Call::<T>::transfer_keep_alive {
                 dest,
-                value: amount,
+                value: Self::to_balance(Self::to_planck(transaction.value)),
             }
             .into())
         }
+
+        /// Call of an ERC-20 transaction to the `Balances` address
+        ///
+        /// `transfer` is a `Balances::transfer_allow_death`, `approve` and `transferFrom` dispatch
+        /// [`Pallet::approve`] and [`Pallet::transfer_from`].
+        fn decode_balances_call(
+            transaction: &EthereumTransaction,
+        ) -> Result<<T as Config>::RuntimeCall, Error<T>> {
+            use polkadot_sdk::sp_runtime::traits::StaticLookup;
+            Ok(
+                match Self::decode_erc20_call::<BalanceOf<T>>(&transaction.data)? {
+                    Erc20Call::Transfer { dest, amount } => {
+                        pallet_balances::Call::<T>::transfer_allow_death {
+                            dest: T::Lookup::unlookup(dest),
+                            value: amount,
+                        }
+                        .into()
+                    }
+                    Erc20Call::Approve { spender, amount } => {
+                        Call::<T>::approve { spender, amount }.into()
+                    }
+                    Erc20Call::TransferFrom {
+                        owner,
+                        dest,
+                        amount,
+                    } => Call::<T>::transfer_from {
+                        owner,
+                        dest,
+                        amount,
+                    }
+                    .into(),
+                },
+            )
+        }
+
+        /// Call of an ERC-20 transaction to the address of an asset
+        ///
+        /// `transfer` is an `Assets::transfer`, `approve` an `Assets::approve_transfer`, which adds
+        /// to the spender's approval, or an `Assets::cancel_approval` for a zero amount, and
+        /// `transferFrom` an `Assets::transfer_approved`.
+        fn decode_asset_call(
+            asset_id: u128,
+            transaction: &EthereumTransaction,
+        ) -> Result<<T as Config>::RuntimeCall, Error<T>> {
+            use polkadot_sdk::sp_runtime::traits::StaticLookup;
+            let id: <T as pallet_assets::Config>::AssetIdParameter =
+                T::AssetIdConversion::try_convert(asset_id)
+                    .map_err(|_| Error::<T>::InvalidAssetId)?
+                    .into();
+
+            Ok(
+                match Self::decode_erc20_call::<AssetBalanceOf<T>>(&transaction.data)? {
+                    Erc20Call::Transfer { dest, amount } => pallet_assets::Call::<T>::transfer {
+                        id,
+                        target: T::Lookup::unlookup(dest),
+                        amount,
+                    }
+                    .into(),
+                    Erc20Call::Approve { spender, amount } if amount.is_zero() => {
+                        pallet_assets::Call::<T>::cancel_approval {
+                            id,
+                            delegate: T::Lookup::unlookup(spender),
+                        }
+                        .into()
+                    }
+                    Erc20Call::Approve { spender, amount } => {
+                        pallet_assets::Call::<T>::approve_transfer {
+                            id,
+                            delegate: T::Lookup::unlookup(spender),
+                            amount,
+                        }
+                        .into()
+                    }
+                    Erc20Call::TransferFrom {
+                        owner,
+                        dest,
+                        amount,
+                    } => pallet_assets::Call::<T>::transfer_approved {
+                        id,
+                        owner: T::Lookup::unlookup(owner),
+                        destination: T::Lookup::unlookup(dest),
+                        amount,
+                    }
+                    .into(),
+                },
+            )
+        }
+
+        /// Call of an ERC-721 transaction to the address of a collection
+        ///
+        /// `transferFrom` and `safeTransferFrom` are an `Nfts::transfer` of the item, and `from`
+        /// must be its owner. `approve` is an `Nfts::approve_transfer` without deadline, or an
+        /// `Nfts::clear_all_transfer_approvals` for the zero address.
+        fn decode_collection_call(
+            collection_id: u128,
+            transaction: &EthereumTransaction,
+        ) -> Result<<T as Config>::RuntimeCall, Error<T>> {
+            use polkadot_sdk::sp_runtime::traits::StaticLookup;
+            let collection = T::CollectionIdConversion::try_convert(collection_id)
+                .map_err(|_| Error::<T>::InvalidCollectionId)?;
+            let data = &transaction.data;
+            let address =
+                |index| Self::abi_address(data, index).ok_or(Error::<T>::InvalidTransactionData);
+            let item = |index| -> Result<<T as pallet_nfts::Config>::ItemId, Error<T>> {
+                let token_id = Self::abi_word(data, index)
+                    .map(U256::from_big_endian)
+                    .ok_or(Error::<T>::InvalidTransactionData)?;
+                ensure!(token_id <= U256::from(u128::MAX), Error::<T>::InvalidItemId);
+                T::ItemIdConversion::try_convert(token_id.low_u128())
+                    .map_err(|_| Error::<T>::InvalidItemId)
+            };
+
+            match Self::selector(data)? {
+                erc721::APPROVE => {
+                    let delegate = address(0)?;
+                    let item = item(1)?;
+                    if delegate.is_zero() {
+                        return Ok(pallet_nfts::Call::<T>::clear_all_transfer_approvals {
+                            collection,
+                            item,
+                        }
+                        .into());
+                    }
+
+                    Ok(pallet_nfts::Call::<T>::approve_transfer {
+                        collection,
+                        item,
+                        delegate: T::Lookup::unlookup(Self::map_address_to_account(delegate)),
+                        maybe_deadline: None,
+                    }
+                    .into())
+                }
+                erc721::TRANSFER_FROM
+                | erc721::SAFE_TRANSFER_FROM
+                | erc721::SAFE_TRANSFER_FROM_WITH_DATA => {
+                    let owner = Self::map_address_to_account(address(0)?);
+                    let dest = Self::map_address_to_account(address(1)?);
+                    let item = item(2)?;
+                    ensure!(
+                        <pallet_nfts::Pallet<T> as nonfungibles_v2::Inspect<T::AccountId>>::owner(
+                            &collection,
+                            &item
+                        ) == Some(owner),
+                        Error::<T>::NotItemOwner
+                    );
+
+                    Ok(pallet_nfts::Call::<T>::transfer {
+                        collection,
+                        item,
+                        dest: T::Lookup::unlookup(dest),
+                    }
+                    .into())
+                }
+                _ => Err(Error::<T>::CallDecodeFailed),
+            }
+        }
+
+        /// Decode the ERC-20 call of the transaction data, with the accounts mapped from the
+        /// addresses
+        fn decode_erc20_call<Balance: UniqueSaturatedFrom<u128>>(
+            data: &[u8],
+        ) -> Result<Erc20Call<T::AccountId, Balance>, Error<T>> {
+            let account = |index| {
+                Self::abi_address(data, index)
+                    .map(Self::map_address_to_account)
+                    .ok_or(Error::<T>::InvalidTransactionData)
+            };
+            let amount = |index| {
+                Self::abi_word(data, index)
+                    .map(|word| Self::to_balance(U256::from_big_endian(word)))
+                    .ok_or(Error::<T>::InvalidTransactionData)
+            };
+
+            match Self::selector(data)? {
+                erc20::TRANSFER => Ok(Erc20Call::Transfer {
+                    dest: account(0)?,
+                    amount: amount(1)?,
+                }),
+                erc20::APPROVE => Ok(Erc20Call::Approve {
+                    spender: account(0)?,
+                    amount: amount(1)?,
+                }),
+                erc20::TRANSFER_FROM => Ok(Erc20Call::TransferFrom {
+                    owner: account(0)?,
+                    dest: account(1)?,
+                    amount: amount(2)?,
+                }),
+                _ => Err(Error::<T>::CallDecodeFailed),
+            }
+        }
+
+        /// Function selector of the call data
+        fn selector(data: &[u8]) -> Result<[u8; 4], Error<T>> {
+            data.get(..4)
+                .and_then(|selector| selector.try_into().ok())
+                .ok_or(Error::<T>::CallDecodeFailed)
+        }
+
+        /// 32-byte ABI word of the `index`th argument of the call data
+        fn abi_word(data: &[u8], index: usize) -> Option<&[u8]> {
+            let start = 4 + 32 * index;
+            data.get(start..start + 32)
+        }
+
+        /// Address in the `index`th argument of the call data: the last 20 bytes of the word
+        fn abi_address(data: &[u8], index: usize) -> Option<H160> {
+            Self::abi_word(data, index).map(|word| H160::from_slice(&word[12..]))
+        }
     }
 
     impl<T: Config> Pallet<T> {
@@ -278,5 +1170,15 @@ pub mod pallet {
             let alloy_address = alloy_primitives::Address::from_slice(address_bytes);
             PalletContractMapping::pallet_name(alloy_address)
         }
+
+        /// Get the asset id from the address, if it's the address of an asset
+        pub fn asset_id_from_address(address: H160) -> Option<u128> {
+            PalletContractMapping::asset_id(alloy_primitives::Address::from(address.0))
+        }
+
+        /// Get the collection id from the address, if it's the address of a collection
+        pub fn collection_id_from_address(address: H160) -> Option<u128> {
+            PalletContractMapping::collection_id(alloy_primitives::Address::from(address.0))
+        }
     }
 }
@@ -2,7 +2,10 @@
 
 use crate as pallet_evm_adapter;
 use frame::testing_prelude::*;
-use polkadot_sdk::{polkadot_sdk_frame as frame, *};
+use polkadot_sdk::{
+	frame_support::traits::{AsEnsureOriginWithArg, ConstU32, ConstU64},
+	polkadot_sdk_frame as frame, *,
+};
 
 type Block = frame_system::mocking::MockBlock<Test>;
 
@@ -12,6 +15,8 @@ frame_support::construct_runtime!(
 	{
 		System: frame_system,
 		Balances: pallet_balances,
+		Assets: pallet_assets,
+		Nfts: pallet_nfts,
 		EvmAdapter: pallet_evm_adapter,
 	}
 );
@@ -27,9 +32,65 @@ impl pallet_balances::Config for Test {
 	type AccountStore = System;
 }
 
+#[derive_impl(pallet_assets::config_preludes::TestDefaultConfig)]
+impl pallet_assets::Config for Test {
+	type Currency = Balances;
+	type CreateOrigin = AsEnsureOriginWithArg<frame_system::EnsureSigned<u64>>;
+	type ForceOrigin = frame_system::EnsureRoot<u64>;
+	type Freezer = ();
+}
+
+parameter_types! {
+	pub NftsFeatures: pallet_nfts::PalletFeatures = pallet_nfts::PalletFeatures::all_enabled();
+}
+
+impl pallet_nfts::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type CollectionId = u32;
+	type ItemId = u32;
+	type Currency = Balances;
+	type CreateOrigin = AsEnsureOriginWithArg<frame_system::EnsureSigned<u64>>;
+	type ForceOrigin = frame_system::EnsureRoot<u64>;
+	type Locker = ();
+	type CollectionDeposit = ConstU64<2>;
+	type ItemDeposit = ConstU64<1>;
+	type MetadataDepositBase = ConstU64<1>;
+	type AttributeDepositBase = ConstU64<1>;
+	type DepositPerByte = ConstU64<1>;
+	type StringLimit = ConstU32<50>;
+	type KeyLimit = ConstU32<50>;
+	type ValueLimit = ConstU32<50>;
+	type ApprovalsLimit = ConstU32<10>;
+	type ItemAttributesApprovalsLimit = ConstU32<2>;
+	type MaxTips = ConstU32<10>;
+	type MaxDeadlineDuration = ConstU64<10_000>;
+	type MaxAttributesPerCall = ConstU32<2>;
+	type Features = NftsFeatures;
+	type OffchainSignature = sp_runtime::testing::TestSignature;
+	type OffchainPublic = sp_runtime::testing::UintAuthorityId;
+	type WeightInfo = ();
+	type BlockNumberProvider = System;
+}
+
+parameter_types! {
+	pub const WeightPerGas: u64 = 20_000;
+	pub const MaxProofSize: u64 = 64 * 1024;
+	pub storage WeiPerPlanck: sp_core::U256 = sp_core::U256::from(1);
+	pub storage BaseFeePerGas: sp_core::U256 = sp_core::U256::from(1);
+}
+
 impl pallet_evm_adapter::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type RuntimeCall = RuntimeCall;
+	type ChainId = frame_support::traits::ConstU64<1>;
+	type GasWeightMapping = pallet_evm_adapter::FixedGasWeightMapping<WeightPerGas>;
+	type MaxProofSize = MaxProofSize;
+	type WeiPerPlanck = WeiPerPlanck;
+	type BaseFeePerGas = BaseFeePerGas;
+	type OnChargeFee = ();
+	type AssetIdConversion = pallet_evm_adapter::NumericId<u32>;
+	type CollectionIdConversion = pallet_evm_adapter::NumericId<u32>;
+	type ItemIdConversion = pallet_evm_adapter::NumericId<u32>;
 }
 
 // Build genesis storage according to the mock runtime.
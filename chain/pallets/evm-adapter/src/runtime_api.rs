@@ -0,0 +1,54 @@
+//! Runtime API of the EVM adapter
+//!
+//! Lets Ethereum RPC adapters see transactions the way the pallet does, instead of mirroring its
+//! address mapping and call decoding.
+
+use alloc::vec::Vec;
+use codec::{Codec, Decode, Encode};
+use polkadot_sdk::{
+    frame_support::weights::Weight,
+    sp_core::H160,
+    sp_runtime::{DispatchError, DispatchResult, RuntimeDebug},
+};
+use scale_info::TypeInfo;
+use subeth_primitives::EthereumTransaction;
+
+/// Outcome of a dry-run transaction
+#[derive(Clone, PartialEq, Eq, Encode, Decode, TypeInfo, RuntimeDebug)]
+pub struct DryRunResult<Event> {
+    /// Result of the dispatched call
+    pub result: DispatchResult,
+    /// Weight used by the transaction, including the pallet's own work
+    pub weight: Weight,
+    /// Gas limit the transaction needs: the gas paying for the weight declared by its call
+    pub gas_required: u64,
+    /// Events deposited by the call
+    pub events: Vec<Event>,
+}
+
+polkadot_sdk::sp_api::decl_runtime_apis! {
+    /// Ethereum view of the runtime
+    pub trait EvmAdapterApi<AccountId, RuntimeCall, RuntimeEvent> where
+        AccountId: Codec,
+        RuntimeCall: Codec,
+        RuntimeEvent: Codec,
+    {
+        /// Account mapped from the Ethereum address
+        fn map_address(address: H160) -> AccountId;
+
+        /// Call dispatched by the transaction
+        fn decode_call(transaction: EthereumTransaction) -> Result<RuntimeCall, DispatchError>;
+
+        /// Dispatch the transaction's call with the account mapped from `from` as origin
+        ///
+        /// The signature, chain id, nonce and gas limit aren't checked and no fee is charged, so
+        /// unsigned `eth_call` and `eth_estimateGas` requests can be dry-run.
+        fn dry_run(from: H160, transaction: EthereumTransaction) -> DryRunResult<RuntimeEvent>;
+
+        /// Nonce of the next transaction of the Ethereum address
+        fn nonce(address: H160) -> u64;
+
+        /// Chain id that transactions must be signed for
+        fn chain_id() -> u64;
+    }
+}
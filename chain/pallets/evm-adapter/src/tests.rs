@@ -2,11 +2,19 @@
 
 use crate::{mock::*, Error};
 use codec::Encode;
+use polkadot_sdk::frame_support::dispatch::GetDispatchInfo;
+use polkadot_sdk::frame_support::weights::Weight;
 use polkadot_sdk::frame_support::*;
-use polkadot_sdk::pallet_balances;
 use polkadot_sdk::polkadot_sdk_frame::prelude::Dispatchable;
 use polkadot_sdk::sp_core::{H160, H256, U256};
-use subeth_primitives::EthereumTransaction;
+use polkadot_sdk::sp_runtime::traits::ValidateUnsigned;
+use polkadot_sdk::sp_runtime::transaction_validity::{
+    InvalidTransaction, TransactionSource, TransactionValidity,
+};
+use polkadot_sdk::{pallet_balances, pallet_nfts};
+use subeth_primitives::{
+    erc20, erc721, EthereumTransaction, PalletContractMapping, TransactionType,
+};
 
 #[test]
 fn test_address_mapping() {
@@ -82,6 +90,7 @@ fn test_decode_transfer_call() {
         to_address[..8].copy_from_slice(b"Balances");
 
         let transaction = EthereumTransaction {
+            transaction_type: TransactionType::Eip1559,
             chain_id: 1,
             nonce: 0,
             max_priority_fee_per_gas: U256::from(0),
@@ -112,6 +121,7 @@ fn test_decode_transfer_with_invalid_selector() {
         to_address[..8].copy_from_slice(b"Balances");
 
         let transaction = EthereumTransaction {
+            transaction_type: TransactionType::Eip1559,
             chain_id: 1,
             nonce: 0,
             max_priority_fee_per_gas: U256::from(0),
@@ -142,6 +152,7 @@ fn test_decode_transfer_with_insufficient_data() {
         to_address[..8].copy_from_slice(b"Balances");
 
         let transaction = EthereumTransaction {
+            transaction_type: TransactionType::Eip1559,
             chain_id: 1,
             nonce: 0,
             max_priority_fee_per_gas: U256::from(0),
@@ -170,6 +181,7 @@ fn test_unsupported_pallet() {
         to_address[..8].copy_from_slice(b"Staking\0");
 
         let transaction = EthereumTransaction {
+            transaction_type: TransactionType::Eip1559,
             chain_id: 1,
             nonce: 0,
             max_priority_fee_per_gas: U256::from(0),
@@ -195,6 +207,7 @@ fn test_signature_recovery_format() {
     new_test_ext().execute_with(|| {
         // Test that signature format is correct
         let transaction = EthereumTransaction {
+            transaction_type: TransactionType::Eip1559,
             chain_id: 1,
             nonce: 0,
             max_priority_fee_per_gas: U256::from(0),
@@ -229,6 +242,7 @@ fn test_signature_invalid_recovery_id() {
     new_test_ext().execute_with(|| {
         // Test with invalid recovery id
         let transaction = EthereumTransaction {
+            transaction_type: TransactionType::Eip1559,
             chain_id: 1,
             nonce: 0,
             max_priority_fee_per_gas: U256::from(0),
@@ -252,6 +266,7 @@ fn test_signature_invalid_recovery_id() {
 fn test_transaction_hash() {
     new_test_ext().execute_with(|| {
         let transaction = EthereumTransaction {
+            transaction_type: TransactionType::Eip1559,
             chain_id: 1,
             nonce: 0,
             max_priority_fee_per_gas: U256::from(0),
@@ -304,6 +319,7 @@ fn test_u256_conversion() {
             to_address[..8].copy_from_slice(b"Balances");
 
             let transaction = EthereumTransaction {
+                transaction_type: TransactionType::Eip1559,
                 chain_id: 1,
                 nonce: 0,
                 max_priority_fee_per_gas: U256::from(0),
@@ -351,6 +367,7 @@ fn test_dispatch_balance_transfer() {
 
         // Create transaction with SCALE-encoded call data
         let transaction = EthereumTransaction {
+            transaction_type: TransactionType::Eip1559,
             chain_id: 1,
             nonce: 0,
             max_priority_fee_per_gas: U256::from(0),
@@ -412,6 +429,7 @@ fn test_dispatch_balance_transfer_insufficient_funds() {
         let call_data = call.encode();
 
         let transaction = EthereumTransaction {
+            transaction_type: TransactionType::Eip1559,
             chain_id: 1,
             nonce: 0,
             max_priority_fee_per_gas: U256::from(0),
@@ -459,6 +477,7 @@ fn test_dispatch_force_transfer_as_root() {
         let call_data = call.encode();
 
         let transaction = EthereumTransaction {
+            transaction_type: TransactionType::Eip1559,
             chain_id: 1,
             nonce: 0,
             max_priority_fee_per_gas: U256::from(0),
@@ -525,6 +544,7 @@ fn test_dispatch_multiple_transfers() {
             let call_data = call.encode();
 
             let transaction = EthereumTransaction {
+                transaction_type: TransactionType::Eip1559,
                 chain_id: 1,
                 nonce: i as u64,
                 max_priority_fee_per_gas: U256::from(0),
@@ -581,6 +601,7 @@ fn test_transact_extrinsic_success() {
         });
 
         let transaction = EthereumTransaction {
+            transaction_type: TransactionType::Eip1559,
             chain_id: 1,
             nonce: 0,
             max_priority_fee_per_gas: U256::from(0),
@@ -608,3 +629,1219 @@ fn test_transact_extrinsic_success() {
         assert!(result.is_err());
     });
 }
+
+/// Transfer of `value` to `dest`, with 21000 gas at 2 per gas (1 of base fee, 1 of tip)
+fn transfer_transaction(nonce: u64, dest: H160, value: u64) -> EthereumTransaction {
+    let call = RuntimeCall::Balances(pallet_balances::Call::transfer_allow_death {
+        dest: crate::Pallet::<Test>::map_address_to_account(dest),
+        value,
+    });
+
+    EthereumTransaction {
+        transaction_type: TransactionType::Eip1559,
+        chain_id: 1,
+        nonce,
+        max_priority_fee_per_gas: U256::from(1),
+        max_fee_per_gas: U256::from(2),
+        gas_limit: 21000,
+        to: H160::zero(),
+        value: U256::from(0),
+        data: call.encode(),
+        access_list: vec![],
+        v: 0,
+        r: Default::default(),
+        s: Default::default(),
+    }
+}
+
+/// Sign the transaction with the key of `seed`, returning the signed transaction and the signer
+fn sign(mut transaction: EthereumTransaction, seed: u8) -> (EthereumTransaction, H160) {
+    use polkadot_sdk::sp_core::{ecdsa, Pair};
+
+    let pair = ecdsa::Pair::from_seed(&[seed; 32]);
+    let signature = pair.sign_prehashed(&transaction.message_hash());
+    transaction.r = H256::from_slice(&signature.0[..32]);
+    transaction.s = H256::from_slice(&signature.0[32..64]);
+    transaction.v = signature.0[64] as u64;
+
+    let signer = crate::Pallet::<Test>::verify_and_recover_signer(&transaction).unwrap();
+    (transaction, signer)
+}
+
+fn fund(address: H160, amount: u64) {
+    assert_ok!(pallet_balances::Pallet::<Test>::force_set_balance(
+        RuntimeOrigin::root(),
+        crate::Pallet::<Test>::map_address_to_account(address),
+        amount,
+    ));
+}
+
+/// Fee paid for the gas used by the call of a transaction priced 2 per gas
+fn gas_fee(transaction: &EthereumTransaction) -> u64 {
+    let call = crate::Pallet::<Test>::decode_call(transaction).unwrap();
+    let weight = call.get_dispatch_info().call_weight;
+    crate::FixedGasWeightMapping::<WeightPerGas>::weight_to_gas(weight) * 2
+}
+
+fn validate_unsigned(transaction: EthereumTransaction) -> TransactionValidity {
+    crate::Pallet::<Test>::validate_unsigned(
+        TransactionSource::External,
+        &crate::Call::transact_unsigned { transaction },
+    )
+}
+
+#[test]
+fn test_high_s_signature_rejected() {
+    new_test_ext().execute_with(|| {
+        let dest = H160::from([2u8; 20]);
+        let (transaction, from) = sign(transfer_transaction(0, dest, 1000), 1);
+        fund(from, 1_000_000);
+
+        // the same signature with `n - s` and the other parity
+        let n = U256::from_str_radix(
+            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+            16,
+        )
+        .unwrap();
+        let s = U256::from_big_endian(transaction.s.as_bytes());
+        let malleated = EthereumTransaction {
+            s: H256((n - s).to_big_endian()),
+            v: 1 - transaction.v,
+            ..transaction.clone()
+        };
+        assert_eq!(
+            crate::Pallet::<Test>::verify_and_recover_signer(&malleated),
+            Err(Error::<Test>::MalleableSignature)
+        );
+        assert_eq!(
+            validate_unsigned(malleated),
+            InvalidTransaction::BadProof.into()
+        );
+        assert_ok!(validate_unsigned(transaction));
+    });
+}
+
+#[test]
+fn test_validate_unsigned_tags() {
+    new_test_ext().execute_with(|| {
+        let dest = H160::from([2u8; 20]);
+        let (transaction, from) = sign(transfer_transaction(0, dest, 1000), 1);
+        fund(from, 1_000_000);
+
+        let valid = validate_unsigned(transaction).unwrap();
+        assert_eq!(valid.priority, 21000);
+        assert_eq!(valid.longevity, crate::UNSIGNED_LONGEVITY);
+        assert_eq!(valid.provides, vec![("EvmAdapter", from, 0u64).encode()]);
+        assert!(valid.requires.is_empty());
+
+        // a future transaction requires the previous nonce, and expires sooner
+        let (transaction, _) = sign(transfer_transaction(2, dest, 1000), 1);
+        let valid = validate_unsigned(transaction).unwrap();
+        assert_eq!(valid.longevity, crate::UNSIGNED_LONGEVITY - 2);
+        assert_eq!(valid.provides, vec![("EvmAdapter", from, 2u64).encode()]);
+        assert_eq!(valid.requires, vec![("EvmAdapter", from, 1u64).encode()]);
+    });
+}
+
+#[test]
+fn test_validate_unsigned_rejects_invalid_transactions() {
+    new_test_ext().execute_with(|| {
+        let dest = H160::from([2u8; 20]);
+        let (transaction, from) = sign(transfer_transaction(0, dest, 1000), 1);
+
+        // can't pay for 21000 gas at 2 per gas
+        fund(from, 41_999);
+        assert_eq!(
+            validate_unsigned(transaction.clone()),
+            InvalidTransaction::Payment.into()
+        );
+        // paying it would reap the account, which the withdrawal of the fee doesn't allow
+        fund(from, 42_000);
+        assert_eq!(
+            validate_unsigned(transaction.clone()),
+            InvalidTransaction::Payment.into()
+        );
+        assert_noop!(
+            crate::Pallet::<Test>::transact_unsigned(RuntimeOrigin::none(), transaction.clone()),
+            Error::<Test>::InsufficientBalanceForFee
+        );
+        fund(from, 1_000_000);
+
+        // signed for another chain
+        let (other_chain, _) = sign(
+            EthereumTransaction {
+                chain_id: 2,
+                ..transfer_transaction(0, dest, 1000)
+            },
+            1,
+        );
+        assert_eq!(
+            validate_unsigned(other_chain),
+            InvalidTransaction::BadProof.into()
+        );
+
+        // tampered with after signing, recovers another signer which can't pay
+        let tampered = EthereumTransaction {
+            value: U256::from(1),
+            ..transaction.clone()
+        };
+        assert_eq!(
+            validate_unsigned(tampered),
+            InvalidTransaction::Payment.into()
+        );
+
+        // undecodable call
+        let (undecodable, _) = sign(
+            EthereumTransaction {
+                data: vec![0xff, 0xff],
+                ..transfer_transaction(0, dest, 1000)
+            },
+            1,
+        );
+        assert_eq!(
+            validate_unsigned(undecodable),
+            InvalidTransaction::Call.into()
+        );
+
+        // nonce already used
+        System::inc_account_nonce(crate::Pallet::<Test>::map_address_to_account(from));
+        assert_eq!(
+            validate_unsigned(transaction),
+            InvalidTransaction::Stale.into()
+        );
+    });
+}
+
+#[test]
+fn test_unknown_sender_rejected() {
+    new_test_ext().execute_with(|| {
+        // free transactions don't need funds, but the sender's account must exist
+        BaseFeePerGas::set(&U256::zero());
+        let relayer = crate::Pallet::<Test>::map_address_to_account(H160::from([9u8; 20]));
+        let (transaction, from) = sign(
+            EthereumTransaction {
+                max_priority_fee_per_gas: U256::zero(),
+                max_fee_per_gas: U256::zero(),
+                ..transfer_transaction(0, H160::from([2u8; 20]), 0)
+            },
+            1,
+        );
+        let account = crate::Pallet::<Test>::map_address_to_account(from);
+
+        assert_eq!(
+            validate_unsigned(transaction.clone()),
+            InvalidTransaction::Payment.into()
+        );
+        assert_noop!(
+            crate::Pallet::<Test>::transact_unsigned(RuntimeOrigin::none(), transaction.clone()),
+            Error::<Test>::UnknownSender
+        );
+        assert_noop!(
+            crate::Pallet::<Test>::transact(RuntimeOrigin::signed(relayer), transaction.clone()),
+            Error::<Test>::UnknownSender
+        );
+        assert!(!System::account_exists(&account));
+
+        fund(from, 1_000);
+        assert_ok!(validate_unsigned(transaction));
+    });
+}
+
+#[test]
+fn test_transact_unsigned() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let dest = H160::from([2u8; 20]);
+        let (transaction, from) = sign(transfer_transaction(0, dest, 1000), 1);
+        fund(from, 1_000_000);
+        let account = crate::Pallet::<Test>::map_address_to_account(from);
+        let call = crate::Call::<Test>::transact_unsigned {
+            transaction: transaction.clone(),
+        };
+
+        // only the next nonce can be included
+        let (future, _) = sign(transfer_transaction(1, dest, 1000), 1);
+        assert_eq!(
+            crate::Pallet::<Test>::pre_dispatch(&crate::Call::transact_unsigned {
+                transaction: future
+            }),
+            Err(InvalidTransaction::Future.into())
+        );
+        assert_ok!(crate::Pallet::<Test>::pre_dispatch(&call));
+
+        assert_noop!(
+            crate::Pallet::<Test>::transact_unsigned(
+                RuntimeOrigin::signed(account.clone()),
+                transaction.clone()
+            ),
+            polkadot_sdk::sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(crate::Pallet::<Test>::transact_unsigned(
+            RuntimeOrigin::none(),
+            transaction.clone()
+        ));
+
+        assert_eq!(
+            pallet_balances::Pallet::<Test>::free_balance(
+                crate::Pallet::<Test>::map_address_to_account(dest)
+            ),
+            1000
+        );
+        assert_eq!(System::account_nonce(&account), 1);
+        System::assert_last_event(
+            crate::Event::TransactionExecuted {
+                from,
+                to: H160::zero(),
+                transaction_hash: transaction.hash(),
+                // the base fee plus the tip
+                gas_used: gas_fee(&transaction) / 2,
+                gas_price: U256::from(2),
+            }
+            .into(),
+        );
+
+        // can't be replayed
+        assert_eq!(
+            crate::Pallet::<Test>::pre_dispatch(&call),
+            Err(InvalidTransaction::Stale.into())
+        );
+    });
+}
+
+#[test]
+fn test_transact_unsigned_failed_call_uses_nonce() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (transaction, from) =
+            sign(transfer_transaction(0, H160::from([2u8; 20]), 2_000_000), 1);
+        fund(from, 1_000_000);
+
+        assert_ok!(crate::Pallet::<Test>::transact_unsigned(
+            RuntimeOrigin::none(),
+            transaction.clone()
+        ));
+
+        let account = crate::Pallet::<Test>::map_address_to_account(from);
+        assert_eq!(System::account_nonce(&account), 1);
+        // the gas used is charged
+        assert_eq!(
+            pallet_balances::Pallet::<Test>::free_balance(&account),
+            1_000_000 - gas_fee(&transaction)
+        );
+        assert!(matches!(
+            System::events().last().map(|record| &record.event),
+            Some(RuntimeEvent::EvmAdapter(
+                crate::Event::TransactionFailed { .. }
+            ))
+        ));
+    });
+}
+
+#[test]
+fn test_transact_wrong_chain_id() {
+    new_test_ext().execute_with(|| {
+        let relayer = crate::Pallet::<Test>::map_address_to_account(H160::from([9u8; 20]));
+        let (transaction, from) = sign(
+            EthereumTransaction {
+                chain_id: 2,
+                ..transfer_transaction(0, H160::from([2u8; 20]), 1000)
+            },
+            1,
+        );
+        fund(from, 1_000_000);
+
+        assert_noop!(
+            crate::Pallet::<Test>::transact(RuntimeOrigin::signed(relayer), transaction),
+            Error::<Test>::WrongChainId
+        );
+    });
+}
+
+#[test]
+fn test_transact_nonce() {
+    new_test_ext().execute_with(|| {
+        let relayer = crate::Pallet::<Test>::map_address_to_account(H160::from([9u8; 20]));
+        let dest = H160::from([2u8; 20]);
+        let (transaction, from) = sign(transfer_transaction(0, dest, 1000), 1);
+        fund(from, 1_000_000);
+        let account = crate::Pallet::<Test>::map_address_to_account(from);
+
+        // nonces can't be skipped
+        let (future, _) = sign(transfer_transaction(1, dest, 1000), 1);
+        assert_noop!(
+            crate::Pallet::<Test>::transact(RuntimeOrigin::signed(relayer.clone()), future.clone()),
+            Error::<Test>::InvalidNonce
+        );
+
+        assert_ok!(crate::Pallet::<Test>::transact(
+            RuntimeOrigin::signed(relayer.clone()),
+            transaction.clone()
+        ));
+        assert_eq!(System::account_nonce(&account), 1);
+
+        // can't be replayed
+        assert_noop!(
+            crate::Pallet::<Test>::transact(RuntimeOrigin::signed(relayer.clone()), transaction),
+            Error::<Test>::InvalidNonce
+        );
+
+        assert_ok!(crate::Pallet::<Test>::transact(
+            RuntimeOrigin::signed(relayer),
+            future
+        ));
+        assert_eq!(System::account_nonce(&account), 2);
+        assert_eq!(
+            pallet_balances::Pallet::<Test>::free_balance(
+                crate::Pallet::<Test>::map_address_to_account(dest)
+            ),
+            2000
+        );
+    });
+}
+
+#[test]
+fn test_transact_failed_call_uses_nonce() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let relayer = crate::Pallet::<Test>::map_address_to_account(H160::from([9u8; 20]));
+        let (transaction, from) =
+            sign(transfer_transaction(0, H160::from([2u8; 20]), 2_000_000), 1);
+        fund(from, 1_000_000);
+
+        assert_ok!(crate::Pallet::<Test>::transact(
+            RuntimeOrigin::signed(relayer.clone()),
+            transaction.clone()
+        ));
+        let account = crate::Pallet::<Test>::map_address_to_account(from);
+        assert_eq!(System::account_nonce(&account), 1);
+        // the gas used is charged
+        assert_eq!(
+            pallet_balances::Pallet::<Test>::free_balance(&account),
+            1_000_000 - gas_fee(&transaction)
+        );
+        assert!(matches!(
+            System::events().last().map(|record| &record.event),
+            Some(RuntimeEvent::EvmAdapter(
+                crate::Event::TransactionFailed { gas_used, gas_price, .. }
+            )) if *gas_used == gas_fee(&transaction) / 2 && *gas_price == U256::from(2)
+        ));
+
+        // can't be replayed
+        assert_noop!(
+            crate::Pallet::<Test>::transact(RuntimeOrigin::signed(relayer), transaction),
+            Error::<Test>::InvalidNonce
+        );
+    });
+}
+
+#[test]
+fn test_fixed_gas_weight_mapping() {
+    use crate::GasWeightMapping;
+    type Mapping = crate::FixedGasWeightMapping<WeightPerGas>;
+
+    assert_eq!(Mapping::gas_to_weight(21000).ref_time(), 21000 * 20_000);
+    assert_eq!(Mapping::weight_to_gas(Mapping::gas_to_weight(21000)), 21000);
+    // partially used gas is charged
+    assert_eq!(Mapping::weight_to_gas(Weight::from_parts(20_001, 0)), 2);
+    assert_eq!(Mapping::weight_to_gas(Weight::zero()), 0);
+}
+
+#[test]
+fn test_transact_weight() {
+    new_test_ext().execute_with(|| {
+        let transaction = transfer_transaction(0, H160::from([2u8; 20]), 1000);
+        let weight = crate::Pallet::<Test>::transact_weight(&transaction);
+        let call_weight = crate::Pallet::<Test>::decode_call(&transaction)
+            .unwrap()
+            .get_dispatch_info()
+            .call_weight;
+        assert!(call_weight.all_lte(weight));
+        assert_eq!(weight.proof_size(), MaxProofSize::get());
+
+        // the call isn't decoded
+        let undecodable = EthereumTransaction {
+            data: vec![0xff, 0xff],
+            ..transaction.clone()
+        };
+        assert_eq!(crate::Pallet::<Test>::transact_weight(&undecodable), weight);
+
+        // paid for by the gas limit
+        let more_gas = EthereumTransaction {
+            gas_limit: transaction.gas_limit + 1,
+            ..transaction
+        };
+        assert_eq!(
+            crate::Pallet::<Test>::transact_weight(&more_gas).ref_time(),
+            weight.ref_time() + 20_000
+        );
+    });
+}
+
+#[test]
+fn test_transact_charges_gas_used() {
+    new_test_ext().execute_with(|| {
+        let relayer = crate::Pallet::<Test>::map_address_to_account(H160::from([9u8; 20]));
+        let (transaction, from) = sign(transfer_transaction(0, H160::from([2u8; 20]), 1000), 1);
+        fund(from, 1_000_000);
+        let account = crate::Pallet::<Test>::map_address_to_account(from);
+        let issuance = pallet_balances::Pallet::<Test>::total_issuance();
+        let fee = gas_fee(&transaction);
+        // the call uses less than the gas limit
+        assert!(fee < 42000);
+
+        let post_info =
+            crate::Pallet::<Test>::transact(RuntimeOrigin::signed(relayer), transaction.clone())
+                .unwrap();
+
+        // the unused gas is refunded, and the fee burned
+        assert_eq!(
+            pallet_balances::Pallet::<Test>::free_balance(&account),
+            1_000_000 - 1000 - fee
+        );
+        assert_eq!(
+            pallet_balances::Pallet::<Test>::total_issuance(),
+            issuance - fee
+        );
+        assert!(post_info
+            .actual_weight
+            .unwrap()
+            .all_lte(crate::Pallet::<Test>::transact_weight(&transaction)));
+    });
+}
+
+#[test]
+fn test_transact_fee_errors() {
+    new_test_ext().execute_with(|| {
+        let relayer = RuntimeOrigin::signed(crate::Pallet::<Test>::map_address_to_account(
+            H160::from([9u8; 20]),
+        ));
+        let dest = H160::from([2u8; 20]);
+        let (transaction, from) = sign(transfer_transaction(0, dest, 1000), 1);
+
+        // can't pay for 21000 gas at 2 per gas
+        fund(from, 41_999);
+        assert_noop!(
+            crate::Pallet::<Test>::transact(relayer.clone(), transaction),
+            Error::<Test>::InsufficientBalanceForFee
+        );
+        fund(from, 1_000_000);
+
+        let (low_gas, _) = sign(
+            EthereumTransaction {
+                gas_limit: 1,
+                ..transfer_transaction(0, dest, 1000)
+            },
+            1,
+        );
+        assert_noop!(
+            crate::Pallet::<Test>::transact(relayer.clone(), low_gas.clone()),
+            Error::<Test>::GasLimitTooLow
+        );
+        assert_eq!(
+            validate_unsigned(low_gas),
+            InvalidTransaction::ExhaustsResources.into()
+        );
+
+        // below the base fee
+        let (underpriced, _) = sign(
+            EthereumTransaction {
+                max_fee_per_gas: U256::from(0),
+                max_priority_fee_per_gas: U256::from(0),
+                ..transfer_transaction(0, dest, 1000)
+            },
+            1,
+        );
+        assert_noop!(
+            crate::Pallet::<Test>::transact(relayer, underpriced.clone()),
+            Error::<Test>::MaxFeePerGasTooLow
+        );
+        assert_eq!(
+            validate_unsigned(underpriced),
+            InvalidTransaction::Payment.into()
+        );
+    });
+}
+
+/// Plain transfer of `value` to `to`, as sent by wallets
+fn value_transaction(nonce: u64, to: H160, value: u64) -> EthereumTransaction {
+    EthereumTransaction {
+        to,
+        value: U256::from(value),
+        data: vec![],
+        ..transfer_transaction(nonce, to, 0)
+    }
+}
+
+#[test]
+fn test_value_transfer() {
+    new_test_ext().execute_with(|| {
+        let relayer = crate::Pallet::<Test>::map_address_to_account(H160::from([9u8; 20]));
+        let dest = H160::from([2u8; 20]);
+        let (transaction, from) = sign(value_transaction(0, dest, 5000), 1);
+        fund(from, 1_000_000);
+
+        assert_eq!(
+            crate::Pallet::<Test>::decode_call(&transaction),
+            Ok(RuntimeCall::Balances(
+                pallet_balances::Call::transfer_keep_alive {
+                    dest: crate::Pallet::<Test>::map_address_to_account(dest),
+                    value: 5000,
+                }
+            ))
+        );
+        assert_ok!(crate::Pallet::<Test>::transact(
+            RuntimeOrigin::signed(relayer),
+            transaction.clone()
+        ));
+
+        assert_eq!(
+            pallet_balances::Pallet::<Test>::free_balance(
+                crate::Pallet::<Test>::map_address_to_account(dest)
+            ),
+            5000
+        );
+        assert_eq!(
+            pallet_balances::Pallet::<Test>::free_balance(
+                crate::Pallet::<Test>::map_address_to_account(from)
+            ),
+            1_000_000 - 5000 - gas_fee(&transaction)
+        );
+    });
+}
+
+#[test]
+fn test_value_transfer_in_wei() {
+    new_test_ext().execute_with(|| {
+        // 10 decimals
+        WeiPerPlanck::set(&U256::exp10(8));
+        let relayer = crate::Pallet::<Test>::map_address_to_account(H160::from([9u8; 20]));
+        let dest = H160::from([2u8; 20]);
+        // the value and gas prices as wallets sign them, less than a planck is dropped
+        let transaction = EthereumTransaction {
+            max_priority_fee_per_gas: U256::from(100_000_000),
+            max_fee_per_gas: U256::from(200_000_000),
+            ..value_transaction(0, dest, 5000 * 100_000_000 + 99)
+        };
+        let (transaction, from) = sign(transaction, 1);
+        fund(from, 1_000_000);
+
+        assert_eq!(
+            crate::Pallet::<Test>::decode_call(&transaction),
+            Ok(RuntimeCall::Balances(
+                pallet_balances::Call::transfer_keep_alive {
+                    dest: crate::Pallet::<Test>::map_address_to_account(dest),
+                    value: 5000,
+                }
+            ))
+        );
+        assert_eq!(
+            crate::Pallet::<Test>::effective_gas_price(&transaction),
+            Ok(U256::from(2))
+        );
+        assert_ok!(validate_unsigned(transaction.clone()));
+        assert_ok!(crate::Pallet::<Test>::transact(
+            RuntimeOrigin::signed(relayer),
+            transaction.clone()
+        ));
+
+        assert_eq!(
+            pallet_balances::Pallet::<Test>::free_balance(
+                crate::Pallet::<Test>::map_address_to_account(dest)
+            ),
+            5000
+        );
+        assert_eq!(
+            pallet_balances::Pallet::<Test>::free_balance(
+                crate::Pallet::<Test>::map_address_to_account(from)
+            ),
+            1_000_000 - 5000 - gas_fee(&transaction)
+        );
+
+        // gas prices below a planck per gas are below the base fee
+        let (unscaled, _) = sign(value_transaction(1, dest, 5000), 1);
+        assert_eq!(
+            crate::Pallet::<Test>::effective_gas_price(&unscaled),
+            Err(Error::<Test>::MaxFeePerGasTooLow)
+        );
+    });
+}
+
+#[test]
+fn test_value_transfer_with_data() {
+    new_test_ext().execute_with(|| {
+        let transaction = EthereumTransaction {
+            data: vec![0xa9, 0x05, 0x9c, 0xbb],
+            ..value_transaction(0, H160::from([2u8; 20]), 5000)
+        };
+
+        assert_eq!(
+            crate::Pallet::<Test>::decode_call(&transaction),
+            Err(Error::<Test>::InvalidTransactionData)
+        );
+    });
+}
+
+#[test]
+fn test_value_to_pallet_rejected() {
+    new_test_ext().execute_with(|| {
+        let mut balances = [0u8; 20];
+        balances[..8].copy_from_slice(b"Balances");
+
+        for to in [H160::zero(), H160::from(balances)] {
+            let transaction = EthereumTransaction {
+                to,
+                value: U256::from(1),
+                ..transfer_transaction(0, H160::from([2u8; 20]), 1000)
+            };
+
+            assert_eq!(
+                crate::Pallet::<Test>::decode_call(&transaction),
+                Err(Error::<Test>::ValueNotAccepted)
+            );
+        }
+    });
+}
+
+#[test]
+fn test_dry_run() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let from = H160::from([1u8; 20]);
+        let dest = H160::from([2u8; 20]);
+        fund(from, 1_000_000);
+        // unsigned, with another chain id and a future nonce
+        let transaction = EthereumTransaction {
+            chain_id: 5,
+            ..value_transaction(3, dest, 5000)
+        };
+
+        let result = crate::Pallet::<Test>::dry_run(from, &transaction);
+        assert_eq!(result.result, Ok(()));
+        assert_eq!(result.gas_required, gas_fee(&transaction) / 2);
+        assert!(result.weight.all_gt(Weight::zero()));
+        assert!(result.events.iter().any(|event| matches!(
+            event,
+            RuntimeEvent::Balances(pallet_balances::Event::Transfer { amount: 5000, .. })
+        )));
+        // no fee is charged
+        assert_eq!(
+            pallet_balances::Pallet::<Test>::free_balance(
+                crate::Pallet::<Test>::map_address_to_account(dest)
+            ),
+            5000
+        );
+        assert_eq!(
+            pallet_balances::Pallet::<Test>::free_balance(
+                crate::Pallet::<Test>::map_address_to_account(from)
+            ),
+            1_000_000 - 5000
+        );
+
+        // the call fails
+        let result = crate::Pallet::<Test>::dry_run(from, &value_transaction(0, dest, 2_000_000));
+        assert!(result.result.is_err());
+
+        // the call can't be decoded
+        let transaction = EthereumTransaction {
+            data: vec![1],
+            ..value_transaction(0, dest, 5000)
+        };
+        let result = crate::Pallet::<Test>::dry_run(from, &transaction);
+        assert_eq!(
+            result.result,
+            Err(Error::<Test>::InvalidTransactionData.into())
+        );
+        assert_eq!(result.gas_required, 0);
+        assert!(result.events.is_empty());
+    });
+}
+
+#[test]
+fn test_nonce() {
+    new_test_ext().execute_with(|| {
+        let relayer = crate::Pallet::<Test>::map_address_to_account(H160::from([9u8; 20]));
+        let (transaction, from) = sign(transfer_transaction(0, H160::from([2u8; 20]), 1000), 1);
+        fund(from, 1_000_000);
+        assert_eq!(crate::Pallet::<Test>::nonce(from), 0);
+
+        assert_ok!(crate::Pallet::<Test>::transact(
+            RuntimeOrigin::signed(relayer),
+            transaction
+        ));
+        assert_eq!(crate::Pallet::<Test>::nonce(from), 1);
+    });
+}
+
+/// EIP-191 signature of the claim message of `account` with the key of `seed`
+fn sign_claim(account: &u64, seed: u8) -> (H160, [u8; 65]) {
+    use polkadot_sdk::sp_core::{ecdsa, keccak_256, Pair};
+
+    let message = crate::Pallet::<Test>::claim_message(account);
+    let mut prefixed = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+    prefixed.extend_from_slice(&message);
+
+    let pair = ecdsa::Pair::from_seed(&[seed; 32]);
+    let mut signature = pair.sign_prehashed(&keccak_256(&prefixed)).0;
+    // wallets use 27 and 28
+    signature[64] += 27;
+
+    let (_, address) = sign(transfer_transaction(0, H160::zero(), 0), seed);
+    (address, signature)
+}
+
+#[test]
+fn test_transact_binds_addresses() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let relayer = crate::Pallet::<Test>::map_address_to_account(H160::from([9u8; 20]));
+        let dest = H160::from([2u8; 20]);
+        let (transaction, from) = sign(value_transaction(0, dest, 5000), 1);
+        fund(from, 1_000_000);
+        let account = crate::Pallet::<Test>::hashed_account(from);
+        assert_eq!(crate::Pallet::<Test>::address_of(&account), None);
+
+        assert_ok!(crate::Pallet::<Test>::transact(
+            RuntimeOrigin::signed(relayer),
+            transaction
+        ));
+
+        assert_eq!(crate::Accounts::<Test>::get(from), Some(account));
+        assert_eq!(crate::Pallet::<Test>::address_of(&account), Some(from));
+        System::assert_has_event(
+            crate::Event::AddressBound {
+                address: from,
+                account,
+            }
+            .into(),
+        );
+        // not the recipient, which can still be claimed
+        assert_eq!(crate::Accounts::<Test>::get(dest), None);
+
+        // the signer of a failed call too, its nonce is used
+        let (transaction, from) = sign(value_transaction(0, dest, 2_000_000), 2);
+        fund(from, 1_000_000);
+        assert_ok!(crate::Pallet::<Test>::transact(
+            RuntimeOrigin::signed(relayer),
+            transaction
+        ));
+        assert!(crate::Accounts::<Test>::get(from).is_some());
+    });
+}
+
+#[test]
+fn test_claim_account() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let relayer = crate::Pallet::<Test>::map_address_to_account(H160::from([9u8; 20]));
+        let native = 7;
+        let (address, signature) = sign_claim(&native, 1);
+
+        assert_ok!(crate::Pallet::<Test>::claim_account(
+            RuntimeOrigin::signed(native),
+            address,
+            signature
+        ));
+        assert_eq!(
+            crate::Pallet::<Test>::map_address_to_account(address),
+            native
+        );
+        assert_eq!(crate::Pallet::<Test>::address_of(&native), Some(address));
+        System::assert_last_event(
+            crate::Event::AddressBound {
+                address,
+                account: native,
+            }
+            .into(),
+        );
+
+        // transactions of the address run as the native account
+        let dest = H160::from([2u8; 20]);
+        assert_ok!(pallet_balances::Pallet::<Test>::force_set_balance(
+            RuntimeOrigin::root(),
+            native,
+            1_000_000
+        ));
+        let (transaction, _) = sign(value_transaction(0, dest, 5000), 1);
+        assert_ok!(crate::Pallet::<Test>::transact(
+            RuntimeOrigin::signed(relayer),
+            transaction.clone()
+        ));
+        assert_eq!(
+            pallet_balances::Pallet::<Test>::free_balance(&native),
+            1_000_000 - 5000 - gas_fee(&transaction)
+        );
+        assert_eq!(System::account_nonce(&native), 1);
+
+        // neither can be bound again
+        let other = 8;
+        let (_, signature) = sign_claim(&other, 1);
+        assert_noop!(
+            crate::Pallet::<Test>::claim_account(RuntimeOrigin::signed(other), address, signature),
+            Error::<Test>::AddressInUse
+        );
+        let (address, signature) = sign_claim(&native, 2);
+        assert_noop!(
+            crate::Pallet::<Test>::claim_account(RuntimeOrigin::signed(native), address, signature),
+            Error::<Test>::AccountAlreadyBound
+        );
+    });
+}
+
+#[test]
+fn test_claim_account_rejected() {
+    new_test_ext().execute_with(|| {
+        let native = 7;
+        let other = 8;
+
+        // signed for another account
+        let (address, signature) = sign_claim(&other, 1);
+        assert_noop!(
+            crate::Pallet::<Test>::claim_account(RuntimeOrigin::signed(native), address, signature),
+            Error::<Test>::InvalidClaimSignature
+        );
+
+        // the hashed account of the address holds funds
+        let (address, signature) = sign_claim(&native, 1);
+        fund(address, 1_000);
+        assert_noop!(
+            crate::Pallet::<Test>::claim_account(RuntimeOrigin::signed(native), address, signature),
+            Error::<Test>::AddressInUse
+        );
+    });
+}
+
+/// ERC-20 call to the `Balances` address, with its arguments as ABI words
+fn erc20_transaction(nonce: u64, selector: [u8; 4], args: &[U256]) -> EthereumTransaction {
+    let mut data = selector.to_vec();
+    for arg in args {
+        data.extend_from_slice(&arg.to_big_endian());
+    }
+
+    EthereumTransaction {
+        to: H160::from_slice(PalletContractMapping::contract_address("Balances").as_slice()),
+        data,
+        gas_limit: 100_000,
+        ..transfer_transaction(nonce, H160::zero(), 0)
+    }
+}
+
+fn abi_address(address: H160) -> U256 {
+    U256::from_big_endian(H256::from(address).as_bytes())
+}
+
+#[test]
+fn test_erc20_approve_and_transfer_from() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let relayer = crate::Pallet::<Test>::map_address_to_account(H160::from([9u8; 20]));
+        let dest = H160::from([2u8; 20]);
+        let (_, spender) = sign(transfer_transaction(0, H160::zero(), 0), 2);
+        fund(spender, 1_000_000);
+
+        let approve =
+            erc20_transaction(0, erc20::APPROVE, &[abi_address(spender), U256::from(5000)]);
+        let (approve, owner) = sign(approve, 1);
+        fund(owner, 1_000_000);
+        assert_ok!(crate::Pallet::<Test>::transact(
+            RuntimeOrigin::signed(relayer),
+            approve
+        ));
+
+        let owner_account = crate::Pallet::<Test>::map_address_to_account(owner);
+        let spender_account = crate::Pallet::<Test>::map_address_to_account(spender);
+        assert_eq!(
+            crate::Allowances::<Test>::get(owner_account, spender_account),
+            5000
+        );
+        System::assert_has_event(
+            crate::Event::Approval {
+                owner: owner_account,
+                spender: spender_account,
+                amount: 5000,
+            }
+            .into(),
+        );
+
+        let transfer_from = erc20_transaction(
+            0,
+            erc20::TRANSFER_FROM,
+            &[abi_address(owner), abi_address(dest), U256::from(3000)],
+        );
+        let owner_balance = Balances::free_balance(owner_account);
+        assert_ok!(crate::Pallet::<Test>::transact(
+            RuntimeOrigin::signed(relayer),
+            sign(transfer_from, 2).0
+        ));
+
+        let dest_account = crate::Pallet::<Test>::map_address_to_account(dest);
+        assert_eq!(Balances::free_balance(dest_account), 3000);
+        assert_eq!(Balances::free_balance(owner_account), owner_balance - 3000);
+        assert_eq!(
+            crate::Allowances::<Test>::get(owner_account, spender_account),
+            2000
+        );
+        // only signers are bound
+        assert_eq!(crate::Pallet::<Test>::address_of(&dest_account), None);
+
+        // beyond the allowance
+        let transfer_from = erc20_transaction(
+            1,
+            erc20::TRANSFER_FROM,
+            &[abi_address(owner), abi_address(dest), U256::from(3000)],
+        );
+        assert_ok!(crate::Pallet::<Test>::transact(
+            RuntimeOrigin::signed(relayer),
+            sign(transfer_from, 2).0
+        ));
+        assert!(matches!(
+            System::events().last().map(|record| &record.event),
+            Some(RuntimeEvent::EvmAdapter(
+                crate::Event::TransactionFailed { .. }
+            ))
+        ));
+        assert_eq!(Balances::free_balance(dest_account), 3000);
+    });
+}
+
+#[test]
+fn test_approve_and_transfer_from() {
+    new_test_ext().execute_with(|| {
+        let (owner, spender, dest) = (1u64, 2u64, 3u64);
+        assert_ok!(pallet_balances::Pallet::<Test>::force_set_balance(
+            RuntimeOrigin::root(),
+            owner,
+            10_000,
+        ));
+
+        assert_ok!(crate::Pallet::<Test>::approve(
+            RuntimeOrigin::signed(owner),
+            spender,
+            5000
+        ));
+        assert_noop!(
+            crate::Pallet::<Test>::transfer_from(RuntimeOrigin::signed(dest), owner, dest, 1),
+            Error::<Test>::InsufficientAllowance
+        );
+        assert_noop!(
+            crate::Pallet::<Test>::transfer_from(RuntimeOrigin::signed(spender), owner, dest, 5001),
+            Error::<Test>::InsufficientAllowance
+        );
+
+        assert_ok!(crate::Pallet::<Test>::transfer_from(
+            RuntimeOrigin::signed(spender),
+            owner,
+            dest,
+            5000
+        ));
+        assert_eq!(Balances::free_balance(owner), 5000);
+        assert_eq!(Balances::free_balance(dest), 5000);
+        // spent allowances are removed
+        assert!(!crate::Allowances::<Test>::contains_key(owner, spender));
+
+        // approving zero revokes the allowance
+        assert_ok!(crate::Pallet::<Test>::approve(
+            RuntimeOrigin::signed(owner),
+            spender,
+            100
+        ));
+        assert_ok!(crate::Pallet::<Test>::approve(
+            RuntimeOrigin::signed(owner),
+            spender,
+            0
+        ));
+        assert!(!crate::Allowances::<Test>::contains_key(owner, spender));
+    });
+}
+
+fn asset_address(asset_id: u128) -> H160 {
+    H160::from_slice(PalletContractMapping::asset_address(asset_id).as_slice())
+}
+
+#[test]
+fn test_erc20_asset_transactions() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let relayer = crate::Pallet::<Test>::map_address_to_account(H160::from([9u8; 20]));
+        let dest = H160::from([2u8; 20]);
+        let (_, spender) = sign(transfer_transaction(0, H160::zero(), 0), 2);
+        fund(spender, 1_000_000);
+
+        let transfer = EthereumTransaction {
+            to: asset_address(1),
+            ..erc20_transaction(0, erc20::TRANSFER, &[abi_address(dest), U256::from(400)])
+        };
+        let (transfer, owner) = sign(transfer, 1);
+        fund(owner, 1_000_000);
+        let owner_account = crate::Pallet::<Test>::map_address_to_account(owner);
+        assert_ok!(Assets::force_create(
+            RuntimeOrigin::root(),
+            1,
+            owner_account,
+            true,
+            1
+        ));
+        assert_ok!(Assets::mint(
+            RuntimeOrigin::signed(owner_account),
+            1,
+            owner_account,
+            1000
+        ));
+
+        assert_ok!(crate::Pallet::<Test>::transact(
+            RuntimeOrigin::signed(relayer),
+            transfer
+        ));
+        let dest_account = crate::Pallet::<Test>::map_address_to_account(dest);
+        assert_eq!(Assets::balance(1, dest_account), 400);
+        assert_eq!(crate::Pallet::<Test>::address_of(&dest_account), None);
+
+        let approve = EthereumTransaction {
+            to: asset_address(1),
+            ..erc20_transaction(1, erc20::APPROVE, &[abi_address(spender), U256::from(300)])
+        };
+        assert_ok!(crate::Pallet::<Test>::transact(
+            RuntimeOrigin::signed(relayer),
+            sign(approve, 1).0
+        ));
+
+        let transfer_from = EthereumTransaction {
+            to: asset_address(1),
+            ..erc20_transaction(
+                0,
+                erc20::TRANSFER_FROM,
+                &[abi_address(owner), abi_address(dest), U256::from(200)],
+            )
+        };
+        assert_ok!(crate::Pallet::<Test>::transact(
+            RuntimeOrigin::signed(relayer),
+            sign(transfer_from, 2).0
+        ));
+        assert_eq!(Assets::balance(1, dest_account), 600);
+        assert_eq!(Assets::balance(1, owner_account), 400);
+
+        // the asset id doesn't fit in the runtime's `u32`
+        let transaction = EthereumTransaction {
+            to: asset_address(u32::MAX as u128 + 1),
+            ..erc20_transaction(0, erc20::TRANSFER, &[abi_address(dest), U256::from(1)])
+        };
+        assert_eq!(
+            crate::Pallet::<Test>::decode_call(&transaction),
+            Err(Error::<Test>::InvalidAssetId)
+        );
+    });
+}
+
+fn collection_address(collection_id: u128) -> H160 {
+    H160::from_slice(PalletContractMapping::collection_address(collection_id).as_slice())
+}
+
+fn item_owner(collection: u32, item: u32) -> Option<u64> {
+    <Nfts as traits::tokens::nonfungibles_v2::Inspect<u64>>::owner(&collection, &item)
+}
+
+#[test]
+fn test_erc721_transactions() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let relayer = crate::Pallet::<Test>::map_address_to_account(H160::from([9u8; 20]));
+        let dest = H160::from([2u8; 20]);
+        let (_, spender) = sign(transfer_transaction(0, H160::zero(), 0), 2);
+        fund(spender, 1_000_000);
+
+        let approve = EthereumTransaction {
+            to: collection_address(0),
+            ..erc20_transaction(0, erc721::APPROVE, &[abi_address(spender), U256::from(7)])
+        };
+        let (approve, owner) = sign(approve, 1);
+        fund(owner, 1_000_000);
+        let owner_account = crate::Pallet::<Test>::map_address_to_account(owner);
+        assert_ok!(Nfts::force_create(
+            RuntimeOrigin::root(),
+            owner_account,
+            pallet_nfts::CollectionConfig {
+                settings: pallet_nfts::CollectionSettings::all_enabled(),
+                max_supply: None,
+                mint_settings: pallet_nfts::MintSettings::default(),
+            }
+        ));
+        assert_ok!(Nfts::force_mint(
+            RuntimeOrigin::root(),
+            0,
+            7,
+            owner_account,
+            pallet_nfts::ItemConfig::default()
+        ));
+
+        assert_ok!(crate::Pallet::<Test>::transact(
+            RuntimeOrigin::signed(relayer),
+            approve
+        ));
+
+        let transfer_from = EthereumTransaction {
+            to: collection_address(0),
+            ..erc20_transaction(
+                0,
+                erc721::SAFE_TRANSFER_FROM,
+                &[abi_address(owner), abi_address(dest), U256::from(7)],
+            )
+        };
+        assert_ok!(crate::Pallet::<Test>::transact(
+            RuntimeOrigin::signed(relayer),
+            sign(transfer_from, 2).0
+        ));
+        let dest_account = crate::Pallet::<Test>::map_address_to_account(dest);
+        assert_eq!(item_owner(0, 7), Some(dest_account));
+        assert_eq!(crate::Pallet::<Test>::address_of(&dest_account), None);
+
+        // `from` must be the item's owner
+        let transfer_from = EthereumTransaction {
+            to: collection_address(0),
+            ..erc20_transaction(
+                1,
+                erc721::TRANSFER_FROM,
+                &[abi_address(owner), abi_address(spender), U256::from(7)],
+            )
+        };
+        assert_eq!(
+            crate::Pallet::<Test>::decode_call(&transfer_from),
+            Err(Error::<Test>::NotItemOwner)
+        );
+
+        // approving the zero address clears the approvals
+        let approve = EthereumTransaction {
+            to: collection_address(0),
+            ..erc20_transaction(0, erc721::APPROVE, &[U256::zero(), U256::from(7)])
+        };
+        assert_eq!(
+            crate::Pallet::<Test>::decode_call(&approve),
+            Ok(RuntimeCall::Nfts(
+                pallet_nfts::Call::clear_all_transfer_approvals {
+                    collection: 0,
+                    item: 7
+                }
+            ))
+        );
+
+        // the ids don't fit in the runtime's `u32`
+        let approve = EthereumTransaction {
+            to: collection_address(u32::MAX as u128 + 1),
+            ..erc20_transaction(0, erc721::APPROVE, &[abi_address(spender), U256::from(7)])
+        };
+        assert_eq!(
+            crate::Pallet::<Test>::decode_call(&approve),
+            Err(Error::<Test>::InvalidCollectionId)
+        );
+        let approve = EthereumTransaction {
+            to: collection_address(0),
+            ..erc20_transaction(
+                0,
+                erc721::APPROVE,
+                &[abi_address(spender), U256::from(u32::MAX) + 1],
+            )
+        };
+        assert_eq!(
+            crate::Pallet::<Test>::decode_call(&approve),
+            Err(Error::<Test>::InvalidItemId)
+        );
+    });
+}
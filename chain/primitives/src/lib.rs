@@ -6,12 +6,19 @@
 
 extern crate alloc;
 
-use alloc::vec::Vec;
+use alloc::{vec, vec::Vec};
 use alloy_primitives::{Address, B256, U256 as AlloyU256};
+use alloy_rlp::{BufMut, Encodable, Header};
 use parity_scale_codec::{Decode, DecodeWithMemTracking, Encode};
 use scale_info::TypeInfo;
 use sp_core::{hashing::keccak_256, H160, H256, U256};
 
+/// Half of the order of secp256k1, the highest `s` of a valid signature (EIP-2)
+const SECP256K1N_HALF: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
+
 /// Helper function to convert U256 to little-endian bytes
 pub fn u256_to_le_bytes(value: &U256) -> [u8; 32] {
     let mut bytes = [0u8; 32];
@@ -22,11 +29,39 @@ pub fn u256_to_le_bytes(value: &U256) -> [u8; 32] {
     bytes
 }
 
+/// EIP-2718 type of an Ethereum transaction
+#[derive(
+    Clone, Copy, Default, Eq, PartialEq, Encode, Decode, DecodeWithMemTracking, Debug, TypeInfo,
+)]
+pub enum TransactionType {
+    /// Legacy transaction, signed with EIP-155 replay protection
+    Legacy,
+    /// EIP-2930 transaction with an access list
+    Eip2930,
+    /// EIP-1559 transaction
+    #[default]
+    Eip1559,
+}
+
+impl TransactionType {
+    /// Type byte prefixed to the signing payload, `None` for legacy transactions
+    pub fn type_byte(&self) -> Option<u8> {
+        match self {
+            TransactionType::Legacy => None,
+            TransactionType::Eip2930 => Some(0x01),
+            TransactionType::Eip1559 => Some(0x02),
+        }
+    }
+}
+
 /// Represents an Ethereum transaction for the pallet
 ///
-/// This is a simplified version supporting EIP-1559 transactions
+/// Legacy and EIP-2930 transactions have a single gas price, stored in both `max_fee_per_gas` and
+/// `max_priority_fee_per_gas`.
 #[derive(Clone, Eq, PartialEq, Encode, Decode, DecodeWithMemTracking, Debug, TypeInfo)]
 pub struct EthereumTransaction {
+    /// Transaction type
+    pub transaction_type: TransactionType,
     /// Chain ID
     pub chain_id: u64,
     /// Nonce
@@ -54,39 +89,128 @@ pub struct EthereumTransaction {
 }
 
 impl EthereumTransaction {
-    /// Calculate the transaction hash
+    /// Calculate the transaction hash, as computed by Ethereum: the keccak-256 of
+    /// [`Self::encoded_2718`]
     pub fn hash(&self) -> H256 {
-        let encoded = self.encode();
-        H256::from(keccak_256(&encoded))
+        H256::from(keccak_256(&self.encoded_2718()))
     }
 
     /// Get the message hash that was signed
     pub fn message_hash(&self) -> [u8; 32] {
-        let mut message = Vec::new();
+        keccak_256(&self.signing_payload())
+    }
 
-        // EIP-1559 transaction type
-        message.push(0x02);
+    /// The payload signed by the sender, as defined by the transaction type:
+    ///
+    /// - legacy (EIP-155): `rlp([nonce, gasPrice, gasLimit, to, value, data, chainId, 0, 0])`
+    /// - EIP-2930: `0x01 || rlp([chainId, nonce, gasPrice, gasLimit, to, value, data, accessList])`
+    /// - EIP-1559: `0x02 || rlp([chainId, nonce, maxPriorityFeePerGas, maxFeePerGas, gasLimit, to,
+    ///   value, data, accessList])`
+    pub fn signing_payload(&self) -> Vec<u8> {
+        self.rlp_payload(false)
+    }
 
-        // Simplified message construction (in production, use proper RLP encoding)
-        message.extend_from_slice(&self.chain_id.to_le_bytes());
-        message.extend_from_slice(&self.nonce.to_le_bytes());
+    /// EIP-2718 encoding of the signed transaction, as sent by wallets:
+    ///
+    /// - legacy: `rlp([nonce, gasPrice, gasLimit, to, value, data, v, r, s])`, with the EIP-155 `v`
+    /// - typed: `type || rlp([...signed fields, yParity, r, s])`
+    pub fn encoded_2718(&self) -> Vec<u8> {
+        self.rlp_payload(true)
+    }
 
-        // Convert U256 to bytes
-        message.extend_from_slice(&u256_to_le_bytes(&self.max_priority_fee_per_gas));
-        message.extend_from_slice(&u256_to_le_bytes(&self.max_fee_per_gas));
-        message.extend_from_slice(&self.gas_limit.to_le_bytes());
-        message.extend_from_slice(self.to.as_bytes());
-        message.extend_from_slice(&u256_to_le_bytes(&self.value));
+    /// Signing payload, or signed transaction if `signed`
+    fn rlp_payload(&self, signed: bool) -> Vec<u8> {
+        let to = Address::from(self.to.0);
+        let value = to_alloy_u256(&self.value);
+        let data = self.data.as_slice();
+        let gas_price = to_alloy_u256(&self.max_fee_per_gas);
+        let max_priority_fee_per_gas = to_alloy_u256(&self.max_priority_fee_per_gas);
+        let access_list = self
+            .access_list
+            .iter()
+            .map(|(address, keys)| AccessListItem {
+                address: Address::from(address.0),
+                storage_keys: keys.iter().map(|key| B256::from(key.0)).collect(),
+            })
+            .collect::<Vec<_>>();
+
+        let parity = self.recovery_id().map_or(self.v, u64::from);
+        let v = match self.transaction_type {
+            TransactionType::Legacy => self.chain_id.saturating_mul(2).saturating_add(35 + parity),
+            _ => parity,
+        };
+        let r = AlloyU256::from_be_bytes(self.r.0);
+        let s = AlloyU256::from_be_bytes(self.s.0);
+
+        let mut fields: Vec<&dyn Encodable> = match self.transaction_type {
+            TransactionType::Legacy => {
+                vec![&self.nonce, &gas_price, &self.gas_limit, &to, &value, &data]
+            }
+            TransactionType::Eip2930 => vec![
+                &self.chain_id,
+                &self.nonce,
+                &gas_price,
+                &self.gas_limit,
+                &to,
+                &value,
+                &data,
+                &access_list,
+            ],
+            TransactionType::Eip1559 => vec![
+                &self.chain_id,
+                &self.nonce,
+                &max_priority_fee_per_gas,
+                &gas_price,
+                &self.gas_limit,
+                &to,
+                &value,
+                &data,
+                &access_list,
+            ],
+        };
+        match (signed, self.transaction_type) {
+            (true, _) => fields.extend([&v as &dyn Encodable, &r, &s]),
+            (false, TransactionType::Legacy) => {
+                fields.extend([&self.chain_id as &dyn Encodable, &0u8, &0u8])
+            }
+            (false, _) => {}
+        }
 
-        message.extend_from_slice(&self.data);
+        let mut payload = Vec::new();
+        payload.extend(self.transaction_type.type_byte());
+        alloy_rlp::encode_list::<_, dyn Encodable>(&fields, &mut payload);
+        payload
+    }
 
-        keccak_256(&message)
+    /// Recovery id (y-parity) of the signature
+    ///
+    /// `v` can be the parity itself, a pre-EIP-155 value (27 or 28), or an EIP-155 value for the
+    /// transaction's chain id (`chain_id * 2 + 35 + parity`).
+    pub fn recovery_id(&self) -> Option<u8> {
+        match self.v {
+            0 | 1 => Some(self.v as u8),
+            27 | 28 => Some((self.v - 27) as u8),
+            v if v >= 35 && (v - 35) / 2 == self.chain_id => Some(((v - 35) % 2) as u8),
+            _ => None,
+        }
+    }
+
+    /// Whether `s` is in the lower half of the curve order, as required by EIP-2
+    ///
+    /// Negating `s` (and flipping the parity) gives another valid signature of the same
+    /// transaction, under another hash, so only the lower one is accepted.
+    pub fn has_low_s(&self) -> bool {
+        self.s.0 <= SECP256K1N_HALF
     }
 
     /// Get the signature in the format expected by secp256k1_ecdsa_recover
     ///
-    /// Returns a 65-byte signature: [r(32) || s(32) || v(1)]
+    /// Returns a 65-byte signature: [r(32) || s(32) || v(1)], or an error if the recovery id is
+    /// invalid or `s` is too high (see [`Self::has_low_s`])
     pub fn signature(&self) -> Result<[u8; 65], ()> {
+        if !self.has_low_s() {
+            return Err(());
+        }
         let mut signature = [0u8; 65];
 
         // Copy r (32 bytes)
@@ -95,25 +219,47 @@ impl EthereumTransaction {
         // Copy s (32 bytes)
         signature[32..64].copy_from_slice(self.s.as_bytes());
 
-        // Copy v (1 byte)
-        // For EIP-1559, v is either 0 or 1 (recovery id)
-        // If v is 27 or 28 (legacy format), convert to 0 or 1
-        let recovery_id = if self.v >= 27 {
-            (self.v - 27) as u8
-        } else {
-            self.v as u8
-        };
+        // Copy v (1 byte), as the recovery id
+        signature[64] = self.recovery_id().ok_or(())?;
 
-        if recovery_id > 1 {
-            return Err(());
-        }
+        Ok(signature)
+    }
+}
 
-        signature[64] = recovery_id;
+/// Access list entry, RLP encoded as `[address, [storageKey, ...]]`
+struct AccessListItem {
+    address: Address,
+    storage_keys: Vec<B256>,
+}
 
-        Ok(signature)
+impl AccessListItem {
+    fn payload_length(&self) -> usize {
+        self.address.length() + self.storage_keys.length()
+    }
+}
+
+impl Encodable for AccessListItem {
+    fn encode(&self, out: &mut dyn BufMut) {
+        Header {
+            list: true,
+            payload_length: self.payload_length(),
+        }
+        .encode(out);
+        Encodable::encode(&self.address, out);
+        Encodable::encode(&self.storage_keys, out);
+    }
+
+    fn length(&self) -> usize {
+        let payload_length = self.payload_length();
+        payload_length + alloy_rlp::length_of_length(payload_length)
     }
 }
 
+/// Convert sp_core U256 to alloy U256, both are little-endian `u64` limbs
+fn to_alloy_u256(value: &U256) -> AlloyU256 {
+    AlloyU256::from_limbs(value.0)
+}
+
 // Conversion utilities for adapter
 pub mod conversions {
     use super::*;
@@ -152,9 +298,17 @@ pub mod conversions {
     }
 }
 
-use alloc::string::{String, ToString};
+use alloc::string::String;
 use sp_core::blake2_256;
 
+/// Prefix of the addresses of `pallet_assets` assets, followed by the big-endian asset id
+///
+/// Pallet addresses are ASCII, so they can't start with it.
+pub const ASSET_ADDRESS_PREFIX: [u8; 4] = [0xff; 4];
+
+/// Prefix of the addresses of `pallet_nfts` collections, followed by the big-endian collection id
+pub const COLLECTION_ADDRESS_PREFIX: [u8; 4] = [0xff, 0xff, 0xff, 0xfe];
+
 /// Pallet to contract address mapping
 pub struct PalletContractMapping;
 
@@ -167,10 +321,59 @@ impl PalletContractMapping {
     }
 
     /// Get the pallet name for a given contract address
+    ///
+    /// Contract addresses of pallets are ASCII alphanumeric names of up to 8 characters, padded
+    /// with zeros, and the zero address is the empty name. Other addresses, like the ones of user
+    /// accounts, have no pallet name.
     pub fn pallet_name(address: Address) -> Option<String> {
-        // try to convert the address to a string
-        let prefix = String::from_utf8(address.to_vec()).ok()?;
-        Some(prefix.trim_end_matches('\0').to_string())
+        let name_len = address.iter().position(|b| *b == 0).unwrap_or(20);
+        let (name, padding) = address.split_at(name_len);
+        if name.len() > 8
+            || !name.iter().all(u8::is_ascii_alphanumeric)
+            || padding.iter().any(|b| *b != 0)
+        {
+            return None;
+        }
+
+        String::from_utf8(name.to_vec()).ok()
+    }
+
+    /// Get the contract address of a `pallet_assets` asset: [`ASSET_ADDRESS_PREFIX`] followed by
+    /// the asset id as a big-endian `u128`
+    pub fn asset_address(asset_id: u128) -> Address {
+        let mut address = [0u8; 20];
+        address[..4].copy_from_slice(&ASSET_ADDRESS_PREFIX);
+        address[4..].copy_from_slice(&asset_id.to_be_bytes());
+        Address::from(address)
+    }
+
+    /// Get the asset id for a given contract address, if it's the address of an asset
+    pub fn asset_id(address: Address) -> Option<u128> {
+        let (prefix, id) = address.split_at(ASSET_ADDRESS_PREFIX.len());
+        if prefix != ASSET_ADDRESS_PREFIX {
+            return None;
+        }
+
+        id.try_into().ok().map(u128::from_be_bytes)
+    }
+
+    /// Get the contract address of a `pallet_nfts` collection: [`COLLECTION_ADDRESS_PREFIX`]
+    /// followed by the collection id as a big-endian `u128`
+    pub fn collection_address(collection_id: u128) -> Address {
+        let mut address = [0u8; 20];
+        address[..4].copy_from_slice(&COLLECTION_ADDRESS_PREFIX);
+        address[4..].copy_from_slice(&collection_id.to_be_bytes());
+        Address::from(address)
+    }
+
+    /// Get the collection id for a given contract address, if it's the address of a collection
+    pub fn collection_id(address: Address) -> Option<u128> {
+        let (prefix, id) = address.split_at(COLLECTION_ADDRESS_PREFIX.len());
+        if prefix != COLLECTION_ADDRESS_PREFIX {
+            return None;
+        }
+
+        id.try_into().ok().map(u128::from_be_bytes)
     }
 
     /// Pad the given string to a valid Ethereum address (20 bytes)
@@ -183,6 +386,59 @@ impl PalletContractMapping {
     }
 }
 
+/// ERC-20 interface of the native token, implemented at the `Balances` pallet address
+///
+/// The pallet dispatches the transactions, the adapter answers the views and synthesizes the logs.
+pub mod erc20 {
+    /// `name()`
+    pub const NAME: [u8; 4] = [0x06, 0xfd, 0xde, 0x03];
+    /// `symbol()`
+    pub const SYMBOL: [u8; 4] = [0x95, 0xd8, 0x9b, 0x41];
+    /// `decimals()`
+    pub const DECIMALS: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+    /// `totalSupply()`
+    pub const TOTAL_SUPPLY: [u8; 4] = [0x18, 0x16, 0x0d, 0xdd];
+    /// `balanceOf(address)`
+    pub const BALANCE_OF: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+    /// `allowance(address,address)`
+    pub const ALLOWANCE: [u8; 4] = [0xdd, 0x62, 0xed, 0x3e];
+    /// `transfer(address,uint256)`
+    pub const TRANSFER: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+    /// `approve(address,uint256)`
+    pub const APPROVE: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+    /// `transferFrom(address,address,uint256)`
+    pub const TRANSFER_FROM: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+
+    /// Signature of the `Transfer` event
+    pub const TRANSFER_EVENT: &str = "Transfer(address,address,uint256)";
+    /// Signature of the `Approval` event
+    pub const APPROVAL_EVENT: &str = "Approval(address,address,uint256)";
+}
+
+/// ERC-721 interface of the `pallet_nfts` collections, implemented at the collection addresses
+///
+/// `balanceOf`, `approve` and `transferFrom` share their selectors with ERC-20, and `Transfer` its
+/// event signature, with the token id in place of the amount.
+pub mod erc721 {
+    /// `balanceOf(address)`
+    pub const BALANCE_OF: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+    /// `ownerOf(uint256)`
+    pub const OWNER_OF: [u8; 4] = [0x63, 0x52, 0x21, 0x1e];
+    /// `tokenURI(uint256)`
+    pub const TOKEN_URI: [u8; 4] = [0xc8, 0x7b, 0x56, 0xdd];
+    /// `approve(address,uint256)`
+    pub const APPROVE: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+    /// `transferFrom(address,address,uint256)`
+    pub const TRANSFER_FROM: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+    /// `safeTransferFrom(address,address,uint256)`
+    pub const SAFE_TRANSFER_FROM: [u8; 4] = [0x42, 0x84, 0x2e, 0x0e];
+    /// `safeTransferFrom(address,address,uint256,bytes)`
+    pub const SAFE_TRANSFER_FROM_WITH_DATA: [u8; 4] = [0xb8, 0x8d, 0x4f, 0xde];
+
+    /// Signature of the `Transfer` event
+    pub const TRANSFER_EVENT: &str = "Transfer(address,address,uint256)";
+}
+
 /// Address mapping logic
 pub struct AddressMapping;
 
@@ -199,3 +455,318 @@ impl AddressMapping {
         Address::from_slice(&account_id[..20])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, hex, PrimitiveSignature};
+
+    /// EIP-1559 transaction from mainnet, with its hash and sender
+    ///
+    /// <https://etherscan.io/tx/0xce4dc6d7a7549a98ee3b071b67e970879ff51b5b95d1c340bacd80fa1e1aab31>
+    fn mainnet_eip1559() -> EthereumTransaction {
+        EthereumTransaction {
+            transaction_type: TransactionType::Eip1559,
+            chain_id: 1,
+            nonce: 2,
+            max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+            max_fee_per_gas: U256::from(11_248_607_958u64),
+            gas_limit: 39_152,
+            to: H160(hex!("d9e1459a7a482635700cbc20bbaf52d495ab9c96")),
+            value: U256::zero(),
+            data: hex!("1b55ba3a").to_vec(),
+            access_list: vec![],
+            v: 0,
+            r: H256(hex!(
+                "c199674fcb29f353693dd779c017823b954b3c69dffa3cd6b2a6ff7888798039"
+            )),
+            s: H256(hex!(
+                "28ca912de909e7e6cdef9cdcaf24c54dd8c1032946dfa1d85c206b32a9064fe8"
+            )),
+        }
+    }
+
+    /// Legacy (EIP-155) transaction from mainnet
+    ///
+    /// <https://etherscan.io/tx/0x280cde7cdefe4b188750e76c888f13bd05ce9a4d7767730feefe8a0e50ca6fc4>
+    fn mainnet_legacy() -> EthereumTransaction {
+        EthereumTransaction {
+            transaction_type: TransactionType::Legacy,
+            chain_id: 1,
+            nonce: 1931,
+            max_priority_fee_per_gas: U256::from(25_000_000_000u64),
+            max_fee_per_gas: U256::from(25_000_000_000u64),
+            gas_limit: 143_089,
+            to: H160(hex!("7a250d5630b4cf539739df2c5dacb4c659f2488d")),
+            value: U256::from(884_487_398_604_084_184u64),
+            data: hex!("7ff36ab50000000000000000000000000000000000000000000066ab5a608bd00a23f2fe000000000000000000000000000000000000000000000000000000000000008000000000000000000000000048c04ed5691981c42154c6167398f95e8f38a7ff00000000000000000000000000000000000000000000000000000000632ceac70000000000000000000000000000000000000000000000000000000000000002000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc20000000000000000000000006c6ee5e31d828de241282b9606c8e98ea48526e2").to_vec(),
+            access_list: vec![],
+            v: 37,
+            r: H256(hex!(
+                "c9077369501641a92ef7399ff81c21639ed4fd8fc69cb793cfa1dbfab342e10a"
+            )),
+            s: H256(hex!(
+                "615facb2f1bcf3274a354cfe384a38d0cc008a11c2dd23a69111bc6930ba27a8"
+            )),
+        }
+    }
+
+    /// Recover the sender from the signing hash
+    fn sender(transaction: &EthereumTransaction) -> Address {
+        let signature = transaction.signature().unwrap();
+        PrimitiveSignature::try_from(&signature[..])
+            .unwrap()
+            .recover_address_from_prehash(&B256::from(transaction.message_hash()))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_mainnet_eip1559() {
+        let transaction = mainnet_eip1559();
+
+        assert_eq!(
+            transaction.hash(),
+            H256(hex!(
+                "ce4dc6d7a7549a98ee3b071b67e970879ff51b5b95d1c340bacd80fa1e1aab31"
+            ))
+        );
+        assert_eq!(
+            transaction.encoded_2718(),
+            hex!("02f86f0102843b9aca0085029e7822d68298f094d9e1459a7a482635700cbc20bbaf52d495ab9c9680841b55ba3ac080a0c199674fcb29f353693dd779c017823b954b3c69dffa3cd6b2a6ff7888798039a028ca912de909e7e6cdef9cdcaf24c54dd8c1032946dfa1d85c206b32a9064fe8")
+        );
+        assert_eq!(
+            sender(&transaction),
+            address!("001e2b7dE757bA469a57bF6b23d982458a07eFcE")
+        );
+    }
+
+    #[test]
+    fn test_mainnet_legacy() {
+        let expected_hash = H256(hex!(
+            "280cde7cdefe4b188750e76c888f13bd05ce9a4d7767730feefe8a0e50ca6fc4"
+        ));
+        let expected_sender = address!("a12e1462d0ceD572f396F58B6E2D03894cD7C8a4");
+
+        // `v` as sent by the wallet, or as the recovery id
+        for v in [37, 0] {
+            let transaction = EthereumTransaction {
+                v,
+                ..mainnet_legacy()
+            };
+            assert_eq!(transaction.hash(), expected_hash);
+            assert_eq!(sender(&transaction), expected_sender);
+        }
+    }
+
+    #[test]
+    fn test_eip155_example() {
+        // https://eips.ethereum.org/EIPS/eip-155#example
+        let transaction = EthereumTransaction {
+            transaction_type: TransactionType::Legacy,
+            chain_id: 1,
+            nonce: 9,
+            max_priority_fee_per_gas: U256::from(20_000_000_000u64),
+            max_fee_per_gas: U256::from(20_000_000_000u64),
+            gas_limit: 21_000,
+            to: H160([0x35; 20]),
+            value: U256::from(1_000_000_000_000_000_000u64),
+            data: vec![],
+            access_list: vec![],
+            v: 37,
+            r: H256(hex!(
+                "28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276"
+            )),
+            s: H256(hex!(
+                "67cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+            )),
+        };
+
+        assert_eq!(
+            transaction.signing_payload(),
+            hex!("ec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080")
+        );
+        assert_eq!(
+            transaction.message_hash(),
+            hex!("daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53")
+        );
+        assert_eq!(
+            sender(&transaction),
+            address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F")
+        );
+    }
+
+    #[test]
+    fn test_pallet_name() {
+        for name in ["Balances", "System", "Transact", ""] {
+            let address = PalletContractMapping::contract_address(name);
+            assert_eq!(
+                PalletContractMapping::pallet_name(address).as_deref(),
+                Some(name)
+            );
+        }
+        assert_eq!(
+            PalletContractMapping::pallet_name(PalletContractMapping::contract_address(
+                "TransactionPayment"
+            ))
+            .as_deref(),
+            Some("Transact")
+        );
+
+        // user accounts
+        for address in [
+            address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F"),
+            Address::repeat_byte(0x41),
+            address!("4261000000000000000000000000000000000001"),
+            address!("2d2d000000000000000000000000000000000000"),
+        ] {
+            assert_eq!(PalletContractMapping::pallet_name(address), None);
+        }
+    }
+
+    #[test]
+    fn test_asset_address() {
+        for asset_id in [0, 1, u32::MAX as u128, u128::MAX] {
+            let address = PalletContractMapping::asset_address(asset_id);
+            assert_eq!(PalletContractMapping::asset_id(address), Some(asset_id));
+            assert_eq!(PalletContractMapping::pallet_name(address), None);
+        }
+        assert_eq!(
+            PalletContractMapping::asset_address(1),
+            address!("ffffffff00000000000000000000000000000001")
+        );
+
+        assert_eq!(
+            PalletContractMapping::asset_id(PalletContractMapping::contract_address("Assets")),
+            None
+        );
+        assert_eq!(
+            PalletContractMapping::asset_id(address!("9d8A62f656a8d1615C1294fd71e9CFb3E4855A4F")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_collection_address() {
+        for collection_id in [0, 1, u32::MAX as u128, u128::MAX] {
+            let address = PalletContractMapping::collection_address(collection_id);
+            assert_eq!(
+                PalletContractMapping::collection_id(address),
+                Some(collection_id)
+            );
+            assert_eq!(PalletContractMapping::asset_id(address), None);
+            assert_eq!(PalletContractMapping::pallet_name(address), None);
+        }
+        assert_eq!(
+            PalletContractMapping::collection_address(1),
+            address!("fffffffe00000000000000000000000000000001")
+        );
+
+        assert_eq!(
+            PalletContractMapping::collection_id(PalletContractMapping::asset_address(1)),
+            None
+        );
+        assert_eq!(
+            PalletContractMapping::collection_id(PalletContractMapping::contract_address("Nfts")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_recovery_id() {
+        let with_v = |v| EthereumTransaction {
+            v,
+            chain_id: 1_000,
+            ..mainnet_eip1559()
+        };
+
+        assert_eq!(with_v(0).recovery_id(), Some(0));
+        assert_eq!(with_v(1).recovery_id(), Some(1));
+        assert_eq!(with_v(27).recovery_id(), Some(0));
+        assert_eq!(with_v(28).recovery_id(), Some(1));
+        assert_eq!(with_v(2_035).recovery_id(), Some(0));
+        assert_eq!(with_v(2_036).recovery_id(), Some(1));
+        // EIP-155 value for another chain
+        assert_eq!(with_v(37).recovery_id(), None);
+        assert_eq!(with_v(2).recovery_id(), None);
+        assert!(with_v(2).signature().is_err());
+    }
+
+    #[test]
+    fn test_high_s_rejected() {
+        let n = AlloyU256::from_be_bytes(hex!(
+            "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141"
+        ));
+        let with_s = |s: AlloyU256| EthereumTransaction {
+            s: H256(s.to_be_bytes()),
+            ..mainnet_eip1559()
+        };
+
+        let transaction = mainnet_eip1559();
+        assert!(transaction.has_low_s());
+        let s = AlloyU256::from_be_bytes(transaction.s.0);
+        // the same signature with `n - s` and the other parity
+        let malleated = EthereumTransaction {
+            v: 1,
+            ..with_s(n - s)
+        };
+        assert!(!malleated.has_low_s());
+        assert!(malleated.signature().is_err());
+        assert_ne!(malleated.hash(), transaction.hash());
+
+        let half = n / AlloyU256::from(2);
+        assert!(with_s(half).signature().is_ok());
+        assert!(with_s(half + AlloyU256::from(1)).signature().is_err());
+    }
+
+    #[test]
+    fn test_erc20_selectors() {
+        for (selector, signature) in [
+            (erc20::NAME, "name()"),
+            (erc20::SYMBOL, "symbol()"),
+            (erc20::DECIMALS, "decimals()"),
+            (erc20::TOTAL_SUPPLY, "totalSupply()"),
+            (erc20::BALANCE_OF, "balanceOf(address)"),
+            (erc20::ALLOWANCE, "allowance(address,address)"),
+            (erc20::TRANSFER, "transfer(address,uint256)"),
+            (erc20::APPROVE, "approve(address,uint256)"),
+            (
+                erc20::TRANSFER_FROM,
+                "transferFrom(address,address,uint256)",
+            ),
+        ] {
+            assert_eq!(
+                selector,
+                keccak_256(signature.as_bytes())[..4],
+                "{signature}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_erc721_selectors() {
+        for (selector, signature) in [
+            (erc721::BALANCE_OF, "balanceOf(address)"),
+            (erc721::OWNER_OF, "ownerOf(uint256)"),
+            (erc721::TOKEN_URI, "tokenURI(uint256)"),
+            (erc721::APPROVE, "approve(address,uint256)"),
+            (
+                erc721::TRANSFER_FROM,
+                "transferFrom(address,address,uint256)",
+            ),
+            (
+                erc721::SAFE_TRANSFER_FROM,
+                "safeTransferFrom(address,address,uint256)",
+            ),
+            (
+                erc721::SAFE_TRANSFER_FROM_WITH_DATA,
+                "safeTransferFrom(address,address,uint256,bytes)",
+            ),
+        ] {
+            assert_eq!(
+                selector,
+                keccak_256(signature.as_bytes())[..4],
+                "{signature}"
+            );
+        }
+    }
+}
@@ -28,6 +28,7 @@ extern crate alloc;
 use alloc::vec::Vec;
 use pallet_transaction_payment::{FeeDetails, RuntimeDispatchInfo};
 use polkadot_sdk::{
+    frame_support::traits::{AsEnsureOriginWithArg, ConstU32, ConstU64},
     polkadot_sdk_frame::{
         self as frame,
         deps::sp_genesis_builder,
@@ -35,6 +36,7 @@ use polkadot_sdk::{
     },
     *,
 };
+use subeth_primitives::EthereumTransaction;
 
 /// Provides getters for genesis configuration presets.
 pub mod genesis_config_presets {
@@ -42,24 +44,47 @@ pub mod genesis_config_presets {
     use crate::{
         interface::{Balance, MinimumBalance},
         sp_keyring::Sr25519Keyring,
-        BalancesConfig, RuntimeGenesisConfig, SudoConfig,
+        AssetsConfig, BalancesConfig, Runtime, RuntimeGenesisConfig, SudoConfig,
     };
 
     use alloc::{vec, vec::Vec};
     use serde_json::Value;
 
+    /// Id of the asset of the development chain, its ERC-20 address is `0xffffffff...01`
+    pub const DEV_ASSET_ID: u32 = 1;
+
+    /// One token of the development chain, which has 12 decimals
+    pub const UNIT: Balance = 1_000_000_000_000;
+
+    /// Ethereum account endowed in the development chain, the first dev account of Hardhat and
+    /// Anvil (private key `0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80`)
+    pub const DEV_ETH_ADDRESS: [u8; 20] = [
+        0xf3, 0x9f, 0xd6, 0xe5, 0x1a, 0xad, 0x88, 0xf6, 0xf4, 0xce, 0x6a, 0xb8, 0x82, 0x72, 0x79,
+        0xcf, 0xff, 0xb9, 0x22, 0x66,
+    ];
+
     /// Returns a development genesis config preset.
     pub fn development_config_genesis() -> Value {
-        let endowment = <MinimumBalance as Get<Balance>>::get().max(1) * 1000;
+        let endowment = (1_000_000 * UNIT).max(<MinimumBalance as Get<Balance>>::get());
+        let alice = Sr25519Keyring::Alice.to_account_id();
+        let dev_eth_account =
+            pallet_evm_adapter::Pallet::<Runtime>::hashed_account(sp_core::H160(DEV_ETH_ADDRESS));
         frame_support::build_struct_json_patch!(RuntimeGenesisConfig {
             balances: BalancesConfig {
                 balances: Sr25519Keyring::iter()
-                    .map(|a| (a.to_account_id(), endowment))
+                    .map(|a| a.to_account_id())
+                    .chain([dev_eth_account])
+                    .map(|account| (account, endowment))
                     .collect::<Vec<_>>(),
             },
-            sudo: SudoConfig {
-                key: Some(Sr25519Keyring::Alice.to_account_id())
+            assets: AssetsConfig {
+                assets: vec![(DEV_ASSET_ID, alice.clone(), true, 1)],
+                metadata: vec![(DEV_ASSET_ID, b"Test Token".to_vec(), b"TEST".to_vec(), 10)],
+                accounts: Sr25519Keyring::iter()
+                    .map(|a| (DEV_ASSET_ID, a.to_account_id(), endowment))
+                    .collect::<Vec<_>>(),
             },
+            sudo: SudoConfig { key: Some(alice) },
         })
     }
 
@@ -171,10 +196,30 @@ mod runtime {
     /// EVM Adapter pallet for Ethereum compatibility.
     #[runtime::pallet_index(6)]
     pub type EvmAdapter = pallet_evm_adapter::Pallet<Runtime>;
+
+    /// Provides fungible assets, with ERC-20 facades through the EVM adapter.
+    #[runtime::pallet_index(7)]
+    pub type Assets = pallet_assets::Pallet<Runtime>;
+
+    /// Provides non-fungible items, with ERC-721 facades through the EVM adapter.
+    #[runtime::pallet_index(8)]
+    pub type Nfts = pallet_nfts::Pallet<Runtime>;
 }
 
 parameter_types! {
     pub const Version: RuntimeVersion = VERSION;
+    /// Chain id of Ethereum transactions, the adapter's default `--chain-id`
+    pub const EvmChainId: u64 = 42;
+    /// `ref_time` per unit of gas, the adapter's `WEIGHT_PER_GAS`
+    pub const WeightPerGas: u64 = 20_000;
+    /// Proof size declared for the call of an Ethereum transaction
+    pub const EvmMaxProofSize: u64 = 64 * 1024;
+    /// Wei per unit of the native token, whose `tokenDecimals` is 12 in the dev chain spec
+    pub EvmWeiPerPlanck: sp_core::U256 = sp_core::U256::exp10(6);
+    /// Base fee per gas of Ethereum transactions, 1 gwei
+    pub BaseFeePerGas: sp_core::U256 = sp_core::U256::from(1_000);
+    /// Features of the NFTs pallet, all enabled
+    pub NftsFeatures: pallet_nfts::PalletFeatures = pallet_nfts::PalletFeatures::all_enabled();
 }
 
 /// Implements the types required for the system pallet.
@@ -192,6 +237,50 @@ impl pallet_balances::Config for Runtime {
     type AccountStore = System;
 }
 
+// Implements the types required for the assets pallet.
+#[derive_impl(pallet_assets::config_preludes::TestDefaultConfig)]
+impl pallet_assets::Config for Runtime {
+    type Currency = Balances;
+    // Anyone can create an asset
+    type CreateOrigin = AsEnsureOriginWithArg<
+        frame_system::EnsureSigned<<Runtime as frame_system::Config>::AccountId>,
+    >;
+    type ForceOrigin = frame_system::EnsureRoot<<Runtime as frame_system::Config>::AccountId>;
+    type Freezer = ();
+}
+
+// Implements the types required for the NFTs pallet.
+impl pallet_nfts::Config for Runtime {
+    type RuntimeEvent = RuntimeEvent;
+    type CollectionId = u32;
+    type ItemId = u32;
+    type Currency = Balances;
+    // Anyone can create a collection
+    type CreateOrigin = AsEnsureOriginWithArg<
+        frame_system::EnsureSigned<<Runtime as frame_system::Config>::AccountId>,
+    >;
+    type ForceOrigin = frame_system::EnsureRoot<<Runtime as frame_system::Config>::AccountId>;
+    type Locker = ();
+    type CollectionDeposit = ConstU64<2>;
+    type ItemDeposit = ConstU64<1>;
+    type MetadataDepositBase = ConstU64<1>;
+    type AttributeDepositBase = ConstU64<1>;
+    type DepositPerByte = ConstU64<1>;
+    type StringLimit = ConstU32<256>;
+    type KeyLimit = ConstU32<64>;
+    type ValueLimit = ConstU32<256>;
+    type ApprovalsLimit = ConstU32<20>;
+    type ItemAttributesApprovalsLimit = ConstU32<20>;
+    type MaxTips = ConstU32<10>;
+    type MaxDeadlineDuration = ConstU32<{ 30 * 24 * 60 * 10 }>;
+    type MaxAttributesPerCall = ConstU32<10>;
+    type Features = NftsFeatures;
+    type OffchainSignature = sp_runtime::MultiSignature;
+    type OffchainPublic = sp_runtime::MultiSigner;
+    type WeightInfo = ();
+    type BlockNumberProvider = System;
+}
+
 // Implements the types required for the sudo pallet.
 #[derive_impl(pallet_sudo::config_preludes::TestDefaultConfig)]
 impl pallet_sudo::Config for Runtime {}
@@ -214,6 +303,18 @@ impl pallet_transaction_payment::Config for Runtime {
 impl pallet_evm_adapter::Config for Runtime {
     type RuntimeEvent = RuntimeEvent;
     type RuntimeCall = RuntimeCall;
+    type ChainId = EvmChainId;
+    type GasWeightMapping = pallet_evm_adapter::FixedGasWeightMapping<WeightPerGas>;
+    type MaxProofSize = EvmMaxProofSize;
+    type WeiPerPlanck = EvmWeiPerPlanck;
+    type BaseFeePerGas = BaseFeePerGas;
+    // Burn the fees, like the extrinsic fees
+    type OnChargeFee = ();
+    type AssetIdConversion =
+        pallet_evm_adapter::NumericId<<Runtime as pallet_assets::Config>::AssetId>;
+    type CollectionIdConversion =
+        pallet_evm_adapter::NumericId<<Runtime as pallet_nfts::Config>::CollectionId>;
+    type ItemIdConversion = pallet_evm_adapter::NumericId<<Runtime as pallet_nfts::Config>::ItemId>;
 }
 
 type Block = frame::runtime::types_common::BlockOf<Runtime, TxExtension>;
@@ -323,6 +424,34 @@ impl_runtime_apis! {
         }
     }
 
+    impl pallet_evm_adapter::runtime_api::EvmAdapterApi<
+        Block,
+        interface::AccountId,
+        RuntimeCall,
+        RuntimeEvent,
+    > for Runtime {
+        fn map_address(address: sp_core::H160) -> interface::AccountId {
+            EvmAdapter::map_address_to_account(address)
+        }
+        fn decode_call(
+            transaction: EthereumTransaction,
+        ) -> Result<RuntimeCall, sp_runtime::DispatchError> {
+            EvmAdapter::decode_call(&transaction).map_err(Into::into)
+        }
+        fn dry_run(
+            from: sp_core::H160,
+            transaction: EthereumTransaction,
+        ) -> pallet_evm_adapter::runtime_api::DryRunResult<RuntimeEvent> {
+            EvmAdapter::dry_run(from, &transaction)
+        }
+        fn nonce(address: sp_core::H160) -> u64 {
+            EvmAdapter::nonce(address)
+        }
+        fn chain_id() -> u64 {
+            EvmChainId::get()
+        }
+    }
+
     impl apis::GenesisBuilder<Block> for Runtime {
         fn build_state(config: Vec<u8>) -> sp_genesis_builder::Result {
             build_state::<RuntimeGenesisConfig>(config)